@@ -0,0 +1,71 @@
+use std::cell::RefCell;
+use std::env;
+use std::rc::Rc;
+use std::time::Instant;
+
+use nes_emu::bus::cpu_bus::CPUBus;
+use nes_emu::bus::ppu_bus::PPUBus;
+use nes_emu::cartridge::NESCartridge;
+use nes_emu::controller::NESController;
+use nes_emu::cpu::NESCPU;
+use nes_emu::mapper::submapper_factory;
+use nes_emu::ppu::NESPPU;
+use nes_emu::util::extract_chr_rom;
+use nes_emu::util::extract_header;
+use nes_emu::util::extract_prg_rom;
+use nes_emu::util::read_bytes_from_file;
+
+//Emulated frames per second, running headless without tracing.
+//Usage: cargo bench --bench frames [-- <rom.nes> [frames]]
+//
+//Baseline, nestest.nes for 600 frames on a single core, range of 3 runs:
+//  4b4c235, decode builds operand strings:    123-128 frames/sec
+//  13db596, allocation-free decode:           234-253 frames/sec
+//  a9d62d5, statically dispatched bus access: 277-304 frames/sec
+//To reproduce, check out each commit and run
+//  cargo bench --bench frames -- tests/roms/nestest.nes 600
+//4b4c235 predates this file, so copy it from 13db596 along with the
+//[[bench]] entry in Cargo.toml
+fn main() {
+    let args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--bench").collect();
+    let rom = args
+        .first()
+        .cloned()
+        .unwrap_or("tests/roms/nestest.nes".to_owned());
+    let frames: u32 = args.get(1).map_or(600, |frames| frames.parse().unwrap());
+
+    let bytes = read_bytes_from_file(rom.clone());
+    let header = extract_header(&bytes);
+    let prg_rom = extract_prg_rom(&header, &bytes);
+    let chr_rom = extract_chr_rom(&header, &bytes);
+
+    let cartridge = Rc::new(NESCartridge::new(
+        prg_rom,
+        chr_rom,
+        header.prg_ram_bytes,
+        submapper_factory(header.mapper_num, header.submapper),
+        header.mirroring,
+    ));
+
+    let mut cpu = NESCPU::new();
+    let mut main_bus = CPUBus::new(
+        Box::new(NESPPU::new(Box::new(PPUBus::new(cartridge.clone())))),
+        cartridge,
+        Rc::new(RefCell::new(NESController::new())),
+        Rc::new(RefCell::new(NESController::new())),
+    );
+
+    let start = Instant::now();
+    for _ in 0..frames {
+        main_bus.run_frame(&mut cpu).unwrap();
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "{}: {} frames in {:.2?} ({:.1} frames/sec)",
+        rom,
+        frames,
+        elapsed,
+        frames as f64 / elapsed.as_secs_f64()
+    );
+}
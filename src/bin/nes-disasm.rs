@@ -0,0 +1,80 @@
+use std::env;
+
+use nes_emu::{
+    cpu::{disasm::disassemble, CpuVariant},
+    util::{extract_header, extract_prg_rom, read_bytes_from_file},
+};
+
+const PRG_BANK_SIZE: usize = 0x4000;
+const VECTORS: [(u16, &str); 3] = [(0xfffa, "NMI"), (0xfffc, "RESET"), (0xfffe, "IRQ")];
+
+fn main() {
+    let Some(file_path) = env::args().nth(1) else {
+        eprintln!("usage: nes-disasm <file.nes>");
+        std::process::exit(1);
+    };
+
+    let bytes = read_bytes_from_file(file_path);
+    let header = extract_header(&bytes);
+    let prg_rom = extract_prg_rom(&header, &bytes);
+
+    let banks: Vec<&[u8]> = prg_rom.chunks(PRG_BANK_SIZE).collect();
+    let last_bank = banks.len() - 1;
+
+    //The vectors are read from the bank fixed at $C000-$FFFF
+    let vector_bytes = &banks[last_bank][PRG_BANK_SIZE - 6..];
+    let labels: Vec<(u16, &str)> = VECTORS
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, name))| {
+            let target = (vector_bytes[2 * i + 1] as u16) << 8 | vector_bytes[2 * i] as u16;
+            (target, name)
+        })
+        .collect();
+
+    for (i, bank) in banks.iter().enumerate() {
+        let base_addr = if i == last_bank { 0xc000 } else { 0x8000 };
+        println!("; PRG bank {} at ${:04X}", i, base_addr);
+
+        let code = if i == last_bank {
+            &bank[..PRG_BANK_SIZE - 6]
+        } else {
+            &bank[..]
+        };
+
+        //Decoding restarts at each vector target so that data placed before
+        //a handler does not misalign it
+        let mut starts: Vec<usize> = vec![0, code.len()];
+        if i == last_bank {
+            starts.extend(
+                labels
+                    .iter()
+                    .map(|&(target, _)| target.wrapping_sub(base_addr) as usize)
+                    .filter(|&offset| offset < code.len()),
+            );
+        }
+        starts.sort_unstable();
+        starts.dedup();
+
+        for segment in starts.windows(2) {
+            let segment_addr = base_addr + segment[0] as u16;
+            let segment_bytes = &code[segment[0]..segment[1]];
+
+            for line in disassemble(segment_bytes, segment_addr, CpuVariant::Ricoh2A03) {
+                if i == last_bank {
+                    for (_, name) in labels.iter().filter(|(target, _)| *target == line.addr) {
+                        println!("{}:", name);
+                    }
+                }
+                println!("{}", line);
+            }
+        }
+
+        if i == last_bank {
+            for (i, (addr, name)) in VECTORS.iter().enumerate() {
+                println!("{:04X}  .word ${:04X} ; {}", addr, labels[i].0, name);
+            }
+        }
+        println!();
+    }
+}
@@ -7,4 +7,21 @@ pub mod ppu_bus;
 pub trait Bus {
     fn read(&self, addr: u16) -> u8;
     fn write(&mut self, addr: u16, data: u8);
+    //What read would return, without any side effects
+    fn peek(&self, addr: u16) -> u8;
+}
+
+//Lets a trait object be passed where a statically dispatched bus is expected
+impl<T: Bus + ?Sized> Bus for &mut T {
+    fn read(&self, addr: u16) -> u8 {
+        (**self).read(addr)
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        (**self).write(addr, data)
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        (**self).peek(addr)
+    }
 }
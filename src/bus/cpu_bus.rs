@@ -3,36 +3,46 @@ use std::{cell::RefCell, rc::Rc};
 use crate::{
     cartridge::Cartridge,
     controller::Controller,
-    cpu::CPU,
+    cpu::{JamEvent, StaticCPU, CPU},
     ppu::{Frame, PPU},
+    util::Region,
 };
 
 #[derive(Debug, PartialEq, Copy, Clone)]
-struct DMA {
+struct Dma {
     cycles: i16,
     page: u8,
 }
 
 use super::Bus;
-pub struct CPUBus<'a> {
-    ppu: Box<dyn PPU + 'a>,
-    cartridge: Rc<dyn Cartridge + 'a>,
+//Generic over the PPU and cartridge so the hot paths are statically
+//dispatched; the defaults keep the trait object form
+pub struct CPUBus<'a, P: PPU + ?Sized = dyn PPU + 'a, C: Cartridge + ?Sized = dyn Cartridge + 'a> {
+    ppu: Box<P>,
+    cartridge: Rc<C>,
 
     controller_1: Rc<RefCell<dyn Controller + 'a>>,
     controller_2: Rc<RefCell<dyn Controller + 'a>>,
 
-    dma: Option<DMA>,
+    dma: Option<Dma>,
+
+    region: Region,
+    //Progress towards the next CPU cycle, see Region::clock_ratio
+    clock_phase: u8,
 
     ram: [u8; 0x800],
+    //Last value on the data bus, seen when reading undriven lines
+    data_bus: RefCell<u8>,
 
+    //CPU cycles run so far, including those taken by DMA
     #[allow(arithmetic_overflow)]
-    nes_cycles: u64,
+    cpu_cycles: u64,
 }
 
-impl<'a> CPUBus<'a> {
+impl<'a, P: PPU + ?Sized, C: Cartridge + ?Sized> CPUBus<'a, P, C> {
     pub fn new(
-        ppu: Box<dyn PPU + 'a>,
-        cartridge: Rc<dyn Cartridge + 'a>,
+        ppu: Box<P>,
+        cartridge: Rc<C>,
         controller_1: Rc<RefCell<dyn Controller + 'a>>,
         controller_2: Rc<RefCell<dyn Controller + 'a>>,
     ) -> Self {
@@ -45,16 +55,25 @@ impl<'a> CPUBus<'a> {
 
             dma: None,
 
+            region: Region::NTSC,
+            clock_phase: 0,
+
             ram: [0; 0x800],
-            nes_cycles: 0,
+            data_bus: RefCell::new(0x0),
+            cpu_cycles: 0,
         }
     }
 
-    pub fn clock(&mut self, cpu: &mut dyn CPU) {
-        self.nes_cycles += 1;
-        self.ppu.clock(cpu);
+    pub fn clock<T: StaticCPU<Self>>(&mut self, cpu: &mut T) {
+        if self.ppu.clock() {
+            cpu.cpu_nmi();
+        }
 
-        if self.nes_cycles % 3 == 0 {
+        let (dots, cycles) = self.region.clock_ratio();
+        self.clock_phase += cycles;
+        if self.clock_phase >= dots {
+            self.clock_phase -= dots;
+            self.cpu_cycles += 1;
             if let Some(mut dma) = self.dma {
                 dma.cycles -= 1;
                 if dma.cycles <= 510 && dma.cycles % 2 == 0 {
@@ -64,52 +83,88 @@ impl<'a> CPUBus<'a> {
                 }
                 self.dma = if dma.cycles <= 0 { None } else { Some(dma) };
             } else {
-                cpu.clock(self);
+                cpu.clock_static(self);
             }
         }
     }
 
-    pub fn reset(&mut self, cpu: &mut dyn CPU) {
+    pub fn reset<T: CPU>(&mut self, cpu: &mut T) {
         cpu.cpu_reset();
         self.ppu.reset();
+        self.cartridge.reset();
+    }
+
+    //Clocks until the PPU completes a frame, stopping early if the
+    //CPU jams so headless runners do not spin until a timeout
+    pub fn run_frame<T: StaticCPU<Self>>(&mut self, cpu: &mut T) -> Result<(), JamEvent> {
+        while !self.is_frame_completed() {
+            self.clock(cpu);
+            if let Some(jam) = cpu.get_jam() {
+                return Err(jam);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_region(&self) -> Region {
+        self.region
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+        self.ppu.set_region(region);
     }
 
     pub fn is_frame_completed(&self) -> bool {
         self.ppu.is_frame_completed()
     }
 
-    pub fn get_frame_from_ppu(&self) -> Frame {
+    pub fn get_ppu(&self) -> &P {
+        &self.ppu
+    }
+
+    pub fn get_frame_from_ppu(&self) -> &Frame {
         self.ppu.get_frame()
     }
 }
 
-impl Bus for CPUBus<'_> {
+impl<P: PPU + ?Sized, C: Cartridge + ?Sized> Bus for CPUBus<'_, P, C> {
     fn read(&self, addr: u16) -> u8 {
-        match addr {
+        let open_bus = *self.data_bus.borrow();
+        let data = match addr {
             0x0000..=0x1fff => self.ram[(addr & 0x7ff) as usize],
             0x2000..=0x3fff => self.ppu.read(addr, false),
+            //APU status is internal to the CPU, so the external bus keeps
+            //its value; bit 5 is not driven
+            0x4015 => return open_bus & 0x20,
+            //The controller ports only drive the low 5 bits
             0x4016 => {
                 let c1 = (*self.controller_1.as_ref()).borrow();
-                c1.read()
+                (open_bus & 0xe0) | (c1.read() & 0x1f)
             }
             0x4017 => {
                 let c2 = (*self.controller_2.as_ref()).borrow();
-                c2.read()
+                (open_bus & 0xe0) | (c2.read() & 0x1f)
             }
-            0x8000..=0xffff => self.cartridge.cpu_read(addr),
-            _ => 0x0, //Open Bus Read
-        }
+            0x4020..=0xffff => self.cartridge.cpu_read(addr).unwrap_or(open_bus),
+            _ => open_bus, //Open Bus Read
+        };
+
+        *self.data_bus.borrow_mut() = data;
+        data
     }
 
     fn write(&mut self, addr: u16, data: u8) {
+        *self.data_bus.borrow_mut() = data;
         match addr {
             0x0000..=0x1fff => {
                 self.ram[(addr & 0x7ff) as usize] = data;
             }
             0x2000..=0x3fff => self.ppu.write(addr, data),
             0x4014 => {
-                self.dma = Some(DMA {
-                    cycles: 513 + (self.nes_cycles % 2 == 1) as i16,
+                self.dma = Some(Dma {
+                    cycles: 513 + (self.cpu_cycles % 2 == 1) as i16,
                     page: data,
                 });
                 self.ppu.write(addr, data);
@@ -120,10 +175,29 @@ impl Bus for CPUBus<'_> {
                 c1.write(data);
                 c2.write(data);
             }
-            0x8000..=0xffff => self.cartridge.cpu_write(addr, data),
+            0x4020..=0xffff => self.cartridge.cpu_write(addr, data),
             _ => {} //Open Bus Write
         }
     }
+
+    fn peek(&self, addr: u16) -> u8 {
+        let open_bus = *self.data_bus.borrow();
+        match addr {
+            0x0000..=0x1fff => self.ram[(addr & 0x7ff) as usize],
+            0x2000..=0x3fff => self.ppu.peek(addr),
+            0x4015 => open_bus & 0x20,
+            0x4016 => {
+                let c1 = (*self.controller_1.as_ref()).borrow();
+                (open_bus & 0xe0) | (c1.peek() & 0x1f)
+            }
+            0x4017 => {
+                let c2 = (*self.controller_2.as_ref()).borrow();
+                (open_bus & 0xe0) | (c2.peek() & 0x1f)
+            }
+            0x4020..=0xffff => self.cartridge.cpu_peek(addr).unwrap_or(open_bus),
+            _ => open_bus,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -185,19 +259,19 @@ mod cpu_bus_tests {
     fn test_cartridge_read() {
         let mut cartridge = MockCartridge::new();
 
-        cartridge.expect_cpu_read().with(eq(0x7fff)).never();
+        cartridge.expect_cpu_read().with(eq(0x401f)).never();
 
         cartridge
             .expect_cpu_read()
-            .with(eq(0x8000))
+            .with(eq(0x4020))
             .once()
-            .return_const(0x0);
+            .return_const(Some(0x0));
 
         cartridge
             .expect_cpu_read()
             .with(eq(0xffff))
             .once()
-            .return_const(0x0);
+            .return_const(Some(0x0));
 
         let main_bus = CPUBus::new(
             Box::new(MockPPU::new()),
@@ -206,8 +280,8 @@ mod cpu_bus_tests {
             Rc::new(RefCell::new(MockController::new())),
         );
 
-        main_bus.read(0x7fff);
-        main_bus.read(0x8000);
+        main_bus.read(0x401f);
+        main_bus.read(0x4020);
         main_bus.read(0xffff);
     }
 
@@ -217,12 +291,12 @@ mod cpu_bus_tests {
 
         cartridge
             .expect_cpu_write()
-            .with(eq(0x7fff), eq(0x0))
+            .with(eq(0x401f), eq(0x0))
             .never();
 
         cartridge
             .expect_cpu_write()
-            .with(eq(0x8000), eq(0x0))
+            .with(eq(0x4020), eq(0x0))
             .once()
             .return_const(());
 
@@ -239,8 +313,8 @@ mod cpu_bus_tests {
             Rc::new(RefCell::new(MockController::new())),
         );
 
-        main_bus.write(0x7fff, 0x0);
-        main_bus.write(0x8000, 0x0);
+        main_bus.write(0x401f, 0x0);
+        main_bus.write(0x4020, 0x0);
         main_bus.write(0xffff, 0x0);
     }
 
@@ -248,7 +322,7 @@ mod cpu_bus_tests {
     fn test_ppu_read() {
         let mut ppu = MockPPU::new();
         let mut cartridge = MockCartridge::new();
-        cartridge.expect_cpu_read().return_const(0x0);
+        cartridge.expect_cpu_read().return_const(Some(0x0));
 
         ppu.expect_read()
             .with(eq(0x2000), eq(false))
@@ -319,9 +393,119 @@ mod cpu_bus_tests {
         main_bus.write(0x4014, 0x0);
     }
 
+    #[test]
+    fn test_open_bus_read_returns_last_value_on_data_bus() {
+        let mut main_bus = CPUBus::new(
+            Box::new(MockPPU::new()),
+            Rc::new(MockCartridge::new()),
+            Rc::new(RefCell::new(MockController::new())),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+
+        main_bus.ram[0x10] = 0x5a;
+        main_bus.read(0x10);
+        assert_eq!(0x5a, main_bus.read(0x4000));
+        assert_eq!(0x5a, main_bus.read(0x401f));
+
+        main_bus.write(0x10, 0xa5);
+        assert_eq!(0xa5, main_bus.read(0x4018));
+    }
+
+    #[test]
+    fn test_undriven_cartridge_read_returns_open_bus() {
+        let mut cartridge = MockCartridge::new();
+        cartridge
+            .expect_cpu_read()
+            .with(eq(0x5000))
+            .return_const(None);
+
+        let main_bus = CPUBus::new(
+            Box::new(MockPPU::new()),
+            Rc::new(cartridge),
+            Rc::new(RefCell::new(MockController::new())),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+
+        *main_bus.data_bus.borrow_mut() = 0x50;
+        assert_eq!(0x50, main_bus.read(0x5000));
+    }
+
+    #[test]
+    fn test_controller_read_merges_open_bus_in_upper_bits() {
+        let mut controller = MockController::new();
+        controller.expect_read().return_const(0xff);
+
+        let mut main_bus = CPUBus::new(
+            Box::new(MockPPU::new()),
+            Rc::new(MockCartridge::new()),
+            Rc::new(RefCell::new(controller)),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+
+        main_bus.ram[0x0] = 0x40;
+        main_bus.read(0x0);
+        assert_eq!(0x5f, main_bus.read(0x4016));
+
+        //The data bus now holds the merged value
+        assert_eq!(0x5f, main_bus.read(0x4000));
+    }
+
+    #[test]
+    fn test_peek_has_no_side_effects() {
+        let mut ppu = MockPPU::new();
+        ppu.expect_read().never();
+        ppu.expect_peek().with(eq(0x2002)).once().return_const(0x80);
+
+        let mut cartridge = MockCartridge::new();
+        cartridge.expect_cpu_read().never();
+        cartridge
+            .expect_cpu_peek()
+            .with(eq(0x8000))
+            .once()
+            .return_const(Some(0x42));
+
+        let mut controller = MockController::new();
+        controller.expect_read().never();
+        controller.expect_peek().once().return_const(0x1);
+
+        let mut main_bus = CPUBus::new(
+            Box::new(ppu),
+            Rc::new(cartridge),
+            Rc::new(RefCell::new(controller)),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+
+        main_bus.write(0x10, 0xa5);
+        assert_eq!(0x80, main_bus.peek(0x2002));
+        assert_eq!(0x42, main_bus.peek(0x8000));
+        assert_eq!(0xa1, main_bus.peek(0x4016));
+        assert_eq!(0xa5, main_bus.peek(0x4000));
+
+        //The data bus still holds the last write
+        assert_eq!(0xa5, main_bus.read(0x4000));
+    }
+
+    #[test]
+    fn test_apu_status_read_does_not_update_data_bus() {
+        let mut main_bus = CPUBus::new(
+            Box::new(MockPPU::new()),
+            Rc::new(MockCartridge::new()),
+            Rc::new(RefCell::new(MockController::new())),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+
+        main_bus.ram[0x0] = 0x7f;
+        main_bus.read(0x0);
+        assert_eq!(0x20, main_bus.read(0x4015));
+        assert_eq!(0x7f, main_bus.read(0x4000));
+    }
+
     #[test]
     fn test_dma_init() {
-        let mut ppu = NESPPU::new(Box::new(MockBus::new()));
+        //The PPU shows the backdrop while rendering is off
+        let mut bus = MockBus::new();
+        bus.expect_read().with(eq(0x3f00)).return_const(0x0);
+        let ppu = NESPPU::new(Box::new(bus));
 
         let mut main_bus = CPUBus::new(
             Box::new(ppu),
@@ -332,7 +516,7 @@ mod cpu_bus_tests {
         assert_eq!(None, main_bus.dma);
         main_bus.write(0x4014, 0x03);
         assert_eq!(
-            Some(DMA {
+            Some(Dma {
                 cycles: 513,
                 page: 0x03
             }),
@@ -374,7 +558,7 @@ mod cpu_bus_tests {
 
     #[test]
     fn test_dma_init_with_alignment_cycle() {
-        let mut ppu = NESPPU::new(Box::new(MockBus::new()));
+        let ppu = NESPPU::new(Box::new(MockBus::new()));
 
         let mut main_bus = CPUBus::new(
             Box::new(ppu),
@@ -383,16 +567,144 @@ mod cpu_bus_tests {
             Rc::new(RefCell::new(MockController::new())),
         );
 
-        main_bus.clock(&mut MockCPU::new());
+        //One CPU cycle every 3 dots
+        let mut cpu = MockCPU::new();
+        cpu.expect_clock().once().return_const(());
+        for _ in 0..3 {
+            main_bus.clock(&mut cpu);
+        }
 
         assert_eq!(None, main_bus.dma);
         main_bus.write(0x4014, 0x0);
         assert_eq!(
-            Some(DMA {
+            Some(Dma {
+                cycles: 514,
+                page: 0x0
+            }),
+            main_bus.dma
+        );
+    }
+
+    #[test]
+    fn test_pal_dma_alignment_follows_cpu_cycles() {
+        let mut ppu = MockPPU::new();
+        ppu.expect_set_region().return_const(());
+        ppu.expect_clock().return_const(false);
+        ppu.expect_write().return_const(());
+
+        let mut main_bus = CPUBus::new(
+            Box::new(ppu),
+            Rc::new(MockCartridge::new()),
+            Rc::new(RefCell::new(MockController::new())),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+        main_bus.set_region(Region::PAL);
+
+        //16 dots are an even number of dots but 5 CPU cycles
+        let mut cpu = MockCPU::new();
+        cpu.expect_clock().times(5).return_const(());
+        for _ in 0..16 {
+            main_bus.clock(&mut cpu);
+        }
+
+        main_bus.write(0x4014, 0x0);
+        assert_eq!(
+            Some(Dma {
                 cycles: 514,
                 page: 0x0
             }),
             main_bus.dma
         );
     }
+
+    #[test]
+    fn test_pal_clocks_cpu_five_times_per_sixteen_dots() {
+        let mut ppu = MockPPU::new();
+        ppu.expect_set_region()
+            .with(eq(Region::PAL))
+            .once()
+            .return_const(());
+        ppu.expect_clock().times(16).return_const(false);
+
+        let mut main_bus = CPUBus::new(
+            Box::new(ppu),
+            Rc::new(MockCartridge::new()),
+            Rc::new(RefCell::new(MockController::new())),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+        main_bus.set_region(Region::PAL);
+
+        let mut cpu = MockCPU::new();
+        cpu.expect_clock().times(5).return_const(());
+        for _ in 0..16 {
+            main_bus.clock(&mut cpu);
+        }
+    }
+
+    #[test]
+    fn test_nmi_from_ppu_is_sent_to_cpu() {
+        let mut ppu = MockPPU::new();
+        ppu.expect_clock().once().return_const(true);
+        ppu.expect_clock().return_const(false);
+
+        let mut main_bus = CPUBus::new(
+            Box::new(ppu),
+            Rc::new(MockCartridge::new()),
+            Rc::new(RefCell::new(MockController::new())),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+
+        let mut cpu = MockCPU::new();
+        cpu.expect_clock().return_const(());
+        cpu.expect_cpu_nmi().once().return_const(());
+        for _ in 0..3 {
+            main_bus.clock(&mut cpu);
+        }
+    }
+
+    #[test]
+    fn test_reset_notifies_cartridge() {
+        let mut ppu = MockPPU::new();
+        ppu.expect_reset().once().return_const(());
+
+        let mut cartridge = MockCartridge::new();
+        cartridge.expect_reset().once().return_const(());
+
+        let mut cpu = MockCPU::new();
+        cpu.expect_cpu_reset().once().return_const(());
+
+        let mut main_bus = CPUBus::new(
+            Box::new(ppu),
+            Rc::new(cartridge),
+            Rc::new(RefCell::new(MockController::new())),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+
+        main_bus.reset(&mut cpu);
+    }
+
+    #[test]
+    fn test_run_frame_stops_when_cpu_jams() {
+        let mut ppu = MockPPU::new();
+        ppu.expect_is_frame_completed().return_const(false);
+        ppu.expect_clock().return_const(false);
+
+        let mut main_bus = CPUBus::new(
+            Box::new(ppu),
+            Rc::new(MockCartridge::new()),
+            Rc::new(RefCell::new(MockController::new())),
+            Rc::new(RefCell::new(MockController::new())),
+        );
+
+        let jam = JamEvent {
+            opcode: 0x2,
+            pc: 0x8000,
+            cycle: 10,
+        };
+        let mut cpu = MockCPU::new();
+        cpu.expect_clock().return_const(());
+        cpu.expect_get_jam().return_const(Some(jam));
+
+        assert_eq!(Err(jam), main_bus.run_frame(&mut cpu));
+    }
 }
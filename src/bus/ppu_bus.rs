@@ -5,15 +5,15 @@ use crate::util::Mirroring;
 
 use super::Bus;
 
-pub struct PPUBus<'a> {
-    cartridge: Rc<dyn Cartridge + 'a>,
+pub struct PPUBus<C: Cartridge + ?Sized = dyn Cartridge> {
+    cartridge: Rc<C>,
     nametable_0: [u8; 0x400],
     nametable_1: [u8; 0x400],
     palette: [u8; 0x20],
 }
 
-impl<'a> PPUBus<'a> {
-    pub fn new(cartridge: Rc<dyn Cartridge + 'a>) -> Self {
+impl<C: Cartridge + ?Sized> PPUBus<C> {
+    pub fn new(cartridge: Rc<C>) -> Self {
         PPUBus {
             cartridge,
             nametable_0: [0x0; 0x400],
@@ -21,13 +21,22 @@ impl<'a> PPUBus<'a> {
             palette: [0x0; 0x20],
         }
     }
-}
 
-impl Bus for PPUBus<'_> {
-    fn read(&self, addr: u16) -> u8 {
-        assert!(addr <= 0x3fff);
+    //The sprite palettes' first entries ($3F10/$3F14/$3F18/$3F1C) mirror
+    //the background ones; the palette repeats every 32 bytes
+    fn palette_index(addr: u16) -> usize {
+        let offset = (addr & 0x1f) as usize;
+        if offset & 0x13 == 0x10 {
+            offset & 0x0f
+        } else {
+            offset
+        }
+    }
+
+    //Nametables and palettes, which live on the console and have no read
+    //side effects
+    fn read_vram(&self, addr: u16) -> u8 {
         match addr {
-            0x0000..=0x1fff => self.cartridge.ppu_read(addr),
             0x2000..=0x23ff => self.nametable_0[(addr - 0x2000) as usize],
             0x2400..=0x27ff => match self.cartridge.get_mirroring() {
                 Mirroring::HORIZONTAL => self.nametable_0[(addr - 0x2400) as usize],
@@ -38,18 +47,21 @@ impl Bus for PPUBus<'_> {
                 Mirroring::VERTICAL => self.nametable_0[(addr - 0x2800) as usize],
             },
             0x2c00..=0x2fff => self.nametable_1[(addr - 0x2c00) as usize],
-            0x3000..=0x3eff => self.read(addr - 0x1000),
-            0x3f00..=0x3fff => {
-                let mut offset = addr - 0x3f00;
-                if offset % 4 == 0 {
-                    offset = 0;
-                }
-
-                self.palette[offset as usize]
-            }
+            0x3000..=0x3eff => self.read_vram(addr - 0x1000),
+            0x3f00..=0x3fff => self.palette[Self::palette_index(addr)],
             _ => 0x0, //Open bus read
         }
     }
+}
+
+impl<C: Cartridge + ?Sized> Bus for PPUBus<C> {
+    fn read(&self, addr: u16) -> u8 {
+        assert!(addr <= 0x3fff);
+        match addr {
+            0x0000..=0x1fff => self.cartridge.ppu_read(addr),
+            _ => self.read_vram(addr),
+        }
+    }
 
     fn write(&mut self, addr: u16, data: u8) {
         assert!(addr <= 0x3fff);
@@ -68,17 +80,19 @@ impl Bus for PPUBus<'_> {
             },
             0x2c00..=0x2fff => self.nametable_1[(addr - 0x2c00) as usize] = data,
             0x3000..=0x3eff => self.write(addr - 0x1000, data),
-            0x3f00..=0x3fff => {
-                let mut offset = addr - 0x3f00;
-                if offset % 4 == 0 {
-                    offset = 0;
-                }
-
-                self.palette[offset as usize] = data;
-            }
+            //Palette entries are 6 bits wide
+            0x3f00..=0x3fff => self.palette[Self::palette_index(addr)] = data & 0x3f,
             _ => {} //Open bus write
         }
     }
+
+    fn peek(&self, addr: u16) -> u8 {
+        assert!(addr <= 0x3fff);
+        match addr {
+            0x0000..=0x1fff => self.cartridge.ppu_peek(addr),
+            _ => self.read_vram(addr),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -136,6 +150,26 @@ mod ppu_bus_tests {
         ppu_bus.read(0x2000);
     }
 
+    #[test]
+    fn test_ppu_bus_peeks_chr() {
+        let mut cartridge = MockCartridge::new();
+        cartridge.expect_ppu_read().never();
+        cartridge
+            .expect_ppu_peek()
+            .with(eq(0x1fff))
+            .once()
+            .return_const(0x42);
+        cartridge
+            .expect_get_mirroring()
+            .return_const(Mirroring::VERTICAL);
+
+        let mut ppu_bus = PPUBus::new(Rc::new(cartridge));
+        ppu_bus.nametable_1[0x0] = 0x24;
+
+        assert_eq!(0x42, ppu_bus.peek(0x1fff));
+        assert_eq!(0x24, ppu_bus.peek(0x2400));
+    }
+
     #[test]
     fn test_ppu_bus_writes_to_chr() {
         let mut cartridge = MockCartridge::new();
@@ -245,20 +279,24 @@ mod ppu_bus_tests {
     fn test_read_from_palette_ram() {
         let cartridge = MockCartridge::new();
         let mut ppu_bus = PPUBus::new(Rc::new(cartridge));
-        ppu_bus.palette[0x0] = 0xff;
-        ppu_bus.palette[0x1] = 0xee;
-        ppu_bus.palette[0x2] = 0xdd;
-        ppu_bus.palette[0x3] = 0xcc;
+        ppu_bus.palette[0x0] = 0x3f;
+        ppu_bus.palette[0x1] = 0x2e;
+        ppu_bus.palette[0x2] = 0x1d;
+        ppu_bus.palette[0x3] = 0x0c;
         ppu_bus.palette[0x4] = 0x00;
-        ppu_bus.palette[0x5] = 0xbb;
-
-        assert_eq!(0xff, ppu_bus.read(0x3f00));
-        assert_eq!(0xee, ppu_bus.read(0x3f01));
-        assert_eq!(0xdd, ppu_bus.read(0x3f02));
-        assert_eq!(0xcc, ppu_bus.read(0x3f03));
-        assert_eq!(0xff, ppu_bus.read(0x3f04));
-        assert_eq!(0xbb, ppu_bus.read(0x3f05));
-        assert_eq!(0xff, ppu_bus.read(0x3f20));
+        ppu_bus.palette[0x5] = 0x0b;
+        ppu_bus.palette[0x14] = 0x14;
+
+        assert_eq!(0x3f, ppu_bus.read(0x3f00));
+        assert_eq!(0x2e, ppu_bus.read(0x3f01));
+        assert_eq!(0x1d, ppu_bus.read(0x3f02));
+        assert_eq!(0x0c, ppu_bus.read(0x3f03));
+        assert_eq!(0x00, ppu_bus.read(0x3f04));
+        assert_eq!(0x0b, ppu_bus.read(0x3f05));
+        assert_eq!(0x3f, ppu_bus.read(0x3f10));
+        assert_eq!(0x00, ppu_bus.read(0x3f14));
+        assert_eq!(0x3f, ppu_bus.read(0x3f20));
+        assert_eq!(0x2e, ppu_bus.read(0x3fe1));
     }
 
     #[test]
@@ -267,18 +305,23 @@ mod ppu_bus_tests {
         let mut ppu_bus = PPUBus::new(Rc::new(cartridge));
 
         ppu_bus.write(0x3f00, 0xff);
-        ppu_bus.write(0x3f01, 0xee);
-        ppu_bus.write(0x3f02, 0xdd);
-        ppu_bus.write(0x3f03, 0xcc);
-        ppu_bus.write(0x3f04, 0xbb);
-
-        assert_eq!(0xbb, ppu_bus.palette[0x0]);
-        assert_eq!(0xee, ppu_bus.palette[0x1]);
-        assert_eq!(0xdd, ppu_bus.palette[0x2]);
-        assert_eq!(0xcc, ppu_bus.palette[0x3]);
-
-        ppu_bus.write(0x3f20, 0xff);
-        assert_eq!(0xff, ppu_bus.palette[0x0]);
+        ppu_bus.write(0x3f01, 0x2e);
+        ppu_bus.write(0x3f02, 0x1d);
+        ppu_bus.write(0x3f03, 0x0c);
+        ppu_bus.write(0x3f04, 0x0b);
+
+        //Only 6 bits are stored
+        assert_eq!(0x3f, ppu_bus.palette[0x0]);
+        assert_eq!(0x2e, ppu_bus.palette[0x1]);
+        assert_eq!(0x1d, ppu_bus.palette[0x2]);
+        assert_eq!(0x0c, ppu_bus.palette[0x3]);
+        assert_eq!(0x0b, ppu_bus.palette[0x4]);
+
+        ppu_bus.write(0x3f1c, 0x1c);
+        assert_eq!(0x1c, ppu_bus.palette[0xc]);
+
+        ppu_bus.write(0x3f39, 0x19);
+        assert_eq!(0x19, ppu_bus.palette[0x19]);
     }
 
     #[test]
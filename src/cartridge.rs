@@ -1,20 +1,29 @@
+use std::cell::RefCell;
+
 use mockall::automock;
 
 use crate::{mapper::Mapper, util::Mirroring};
 
 #[automock]
 pub trait Cartridge {
-    fn cpu_read(&self, addr: u16) -> u8;
+    //None if the cartridge does not drive the data bus (open bus)
+    fn cpu_read(&self, addr: u16) -> Option<u8>;
     fn cpu_write(&self, addr: u16, data: u8);
     fn ppu_read(&self, addr: u16) -> u8;
     fn ppu_write(&self, addr: u16, data: u8);
+    //What cpu_read and ppu_read would return, without any side effects
+    fn cpu_peek(&self, addr: u16) -> Option<u8>;
+    fn ppu_peek(&self, addr: u16) -> u8;
 
     fn get_mirroring(&self) -> Mirroring;
+    fn reset(&self);
 }
 
 pub struct NESCartridge<'a> {
     prg_rom: &'a [u8],
     chr_rom: &'a [u8],
+    chr_ram: RefCell<Vec<u8>>,
+    prg_ram: RefCell<Vec<u8>>,
 
     prg_rom_banks: u8,
     chr_rom_banks: u8,
@@ -30,15 +39,27 @@ impl<'a> NESCartridge<'a> {
     pub fn new(
         prg_rom: &'a [u8],
         chr_rom: &'a [u8],
+        prg_ram_bytes: u32,
         mapper: Box<dyn Mapper + 'a>,
         mirroring: Mirroring,
     ) -> Self {
+        //Boards without CHR ROM have a single bank of CHR RAM instead
+        let chr_ram = if chr_rom.is_empty() {
+            vec![0x0; NESCartridge::BYTES_PER_CHR_BANK as usize]
+        } else {
+            vec![]
+        };
+
         Self {
             prg_rom,
             chr_rom,
 
             prg_rom_banks: (prg_rom.len() as u32 / NESCartridge::BYTES_PER_PRG_BANK) as u8,
-            chr_rom_banks: (chr_rom.len() as u32 / NESCartridge::BYTES_PER_CHR_BANK) as u8,
+            chr_rom_banks: (chr_rom.len() as u32 / NESCartridge::BYTES_PER_CHR_BANK).max(1) as u8,
+
+            chr_ram: RefCell::new(chr_ram),
+            //Work RAM at $6000-$7FFF, which test ROMs also use to report results
+            prg_ram: RefCell::new(vec![0x0; prg_ram_bytes as usize]),
 
             mapper,
             mirroring,
@@ -47,27 +68,71 @@ impl<'a> NESCartridge<'a> {
 }
 
 impl Cartridge for NESCartridge<'_> {
-    fn cpu_read(&self, addr: u16) -> u8 {
-        assert!((0x8000..=0xffff).contains(&addr));
-        self.prg_rom[self.mapper.read_prg(addr, self.prg_rom_banks) as usize]
+    fn cpu_read(&self, addr: u16) -> Option<u8> {
+        assert!((0x4020..=0xffff).contains(&addr));
+        match addr {
+            0x4020..=0x5fff => self.mapper.read_expansion(addr),
+            0x6000..=0x7fff => self
+                .mapper
+                .map_prg_ram(addr, self.prg_ram.borrow().len() as u32)
+                .map(|offset| self.prg_ram.borrow()[offset as usize]),
+            _ => Some(self.prg_rom[self.mapper.read_prg(addr, self.prg_rom_banks) as usize]),
+        }
     }
 
     fn cpu_write(&self, addr: u16, data: u8) {
-        assert!((0x8000..=0xffff).contains(&addr));
-        self.mapper.write_prg(addr, data, self.prg_rom_banks);
+        assert!((0x4020..=0xffff).contains(&addr));
+        match addr {
+            0x4020..=0x5fff => self.mapper.write_expansion(addr, data),
+            0x6000..=0x7fff => {
+                let prg_ram_bytes = self.prg_ram.borrow().len() as u32;
+                if let Some(offset) = self.mapper.map_prg_ram(addr, prg_ram_bytes) {
+                    self.prg_ram.borrow_mut()[offset as usize] = data;
+                }
+            }
+            _ => self.mapper.write_prg(addr, data, self.prg_rom_banks),
+        }
     }
 
     fn ppu_read(&self, addr: u16) -> u8 {
         assert!((..=0x1fff).contains(&addr));
-        self.chr_rom[self.mapper.read_chr(addr, self.chr_rom_banks) as usize]
+        let offset = self.mapper.read_chr(addr, self.chr_rom_banks) as usize;
+
+        if self.chr_rom.is_empty() {
+            self.chr_ram.borrow()[offset]
+        } else {
+            self.chr_rom[offset]
+        }
     }
 
-    fn ppu_write(&self, addr: u16, _data: u8) {
+    fn ppu_write(&self, addr: u16, data: u8) {
         assert!((..=0x1fff).contains(&addr));
+        self.mapper.write_chr(addr, data, self.chr_rom_banks);
+
+        if self.chr_rom.is_empty() {
+            let offset = self.mapper.read_chr(addr, self.chr_rom_banks) as usize;
+            self.chr_ram.borrow_mut()[offset] = data;
+        }
+    }
+
+    fn cpu_peek(&self, addr: u16) -> Option<u8> {
+        match addr {
+            0x4020..=0x5fff => self.mapper.peek_expansion(addr),
+            //PRG RAM and ROM reads only look up a bank
+            _ => self.cpu_read(addr),
+        }
+    }
+
+    fn ppu_peek(&self, addr: u16) -> u8 {
+        self.ppu_read(addr)
     }
 
     fn get_mirroring(&self) -> Mirroring {
-        self.mirroring
+        self.mapper.get_mirroring().unwrap_or(self.mirroring)
+    }
+
+    fn reset(&self) {
+        self.mapper.reset();
     }
 }
 
@@ -86,11 +151,12 @@ mod cartridge_tests {
             .expect_read_prg()
             .with(eq(0x8000), eq(1))
             .once()
-            .return_const(0x0 as u16);
+            .return_const(0x0_u32);
 
         let cartridge = NESCartridge::new(
             &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
             &[0; NESCartridge::BYTES_PER_CHR_BANK as usize],
+            0x0,
             Box::new(mapper),
             Mirroring::HORIZONTAL,
         );
@@ -111,6 +177,7 @@ mod cartridge_tests {
         let cartridge = NESCartridge::new(
             &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
             &[0; NESCartridge::BYTES_PER_CHR_BANK as usize],
+            0x0,
             Box::new(mapper),
             Mirroring::HORIZONTAL,
         );
@@ -118,6 +185,111 @@ mod cartridge_tests {
         cartridge.cpu_write(0x8000, 0x0);
     }
 
+    #[test]
+    fn test_cartridge_prg_ram() {
+        let mut mapper = MockMapper::new();
+
+        mapper.expect_read_prg().never();
+        mapper.expect_write_prg().never();
+        mapper
+            .expect_map_prg_ram()
+            .with(eq(0x6000), eq(0x2000))
+            .return_const(Some(0x0));
+        mapper
+            .expect_map_prg_ram()
+            .with(eq(0x7fff), eq(0x2000))
+            .return_const(Some(0x1fff));
+
+        let cartridge = NESCartridge::new(
+            &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
+            &[0; NESCartridge::BYTES_PER_CHR_BANK as usize],
+            0x2000,
+            Box::new(mapper),
+            Mirroring::HORIZONTAL,
+        );
+
+        cartridge.cpu_write(0x6000, 0xde);
+        cartridge.cpu_write(0x7fff, 0xad);
+
+        assert_eq!(Some(0xde), cartridge.cpu_read(0x6000));
+        assert_eq!(Some(0xad), cartridge.cpu_read(0x7fff));
+    }
+
+    #[test]
+    fn test_cartridge_without_prg_ram_is_open_bus() {
+        let mut mapper = MockMapper::new();
+        mapper
+            .expect_map_prg_ram()
+            .with(eq(0x6000), eq(0x0))
+            .return_const(None);
+
+        let cartridge = NESCartridge::new(
+            &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
+            &[0; NESCartridge::BYTES_PER_CHR_BANK as usize],
+            0x0,
+            Box::new(mapper),
+            Mirroring::HORIZONTAL,
+        );
+
+        cartridge.cpu_write(0x6000, 0xde);
+        assert_eq!(None, cartridge.cpu_read(0x6000));
+        assert_eq!(None, cartridge.cpu_peek(0x6000));
+    }
+
+    #[test]
+    fn test_cartridge_expansion_area_goes_to_mapper() {
+        let mut mapper = MockMapper::new();
+
+        mapper
+            .expect_read_expansion()
+            .with(eq(0x5000))
+            .once()
+            .return_const(Some(0x42));
+        mapper
+            .expect_read_expansion()
+            .with(eq(0x4020))
+            .once()
+            .return_const(None);
+        mapper
+            .expect_write_expansion()
+            .with(eq(0x5fff), eq(0x12))
+            .once()
+            .return_const(());
+
+        let cartridge = NESCartridge::new(
+            &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
+            &[0; NESCartridge::BYTES_PER_CHR_BANK as usize],
+            0x0,
+            Box::new(mapper),
+            Mirroring::HORIZONTAL,
+        );
+
+        assert_eq!(Some(0x42), cartridge.cpu_read(0x5000));
+        assert_eq!(None, cartridge.cpu_read(0x4020));
+        cartridge.cpu_write(0x5fff, 0x12);
+    }
+
+    #[test]
+    fn test_cartridge_peek_of_expansion_area_does_not_read_it() {
+        let mut mapper = MockMapper::new();
+        mapper.expect_read_expansion().never();
+        mapper
+            .expect_peek_expansion()
+            .with(eq(0x5000))
+            .once()
+            .return_const(Some(0x42));
+
+        let cartridge = NESCartridge::new(
+            &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
+            &[0; NESCartridge::BYTES_PER_CHR_BANK as usize],
+            0x0,
+            Box::new(mapper),
+            Mirroring::HORIZONTAL,
+        );
+
+        assert_eq!(Some(0x42), cartridge.cpu_peek(0x5000));
+    }
+
     #[test]
     fn test_cartridge_read_from_ppu() {
         let mut mapper = MockMapper::new();
@@ -129,15 +301,94 @@ mod cartridge_tests {
             .expect_read_chr()
             .with(eq(0x1234), eq(1))
             .once()
-            .return_const(0x1234u16);
+            .return_const(0x1234u32);
 
         let cartridge = NESCartridge::new(
             &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
             &chr_rom,
+            0x0,
             Box::new(mapper),
             Mirroring::HORIZONTAL,
         );
 
         assert_eq!(0xff, cartridge.ppu_read(0x1234));
     }
+
+    #[test]
+    fn test_cartridge_write_to_chr_ram() {
+        let mut mapper = MockMapper::new();
+
+        mapper.expect_write_chr().return_const(());
+        mapper
+            .expect_read_chr()
+            .with(eq(0x1234), eq(1))
+            .return_const(0x1234u32);
+
+        let cartridge = NESCartridge::new(
+            &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
+            &[],
+            0x0,
+            Box::new(mapper),
+            Mirroring::HORIZONTAL,
+        );
+
+        cartridge.ppu_write(0x1234, 0xee);
+        assert_eq!(0xee, cartridge.ppu_read(0x1234));
+    }
+
+    #[test]
+    fn test_cartridge_write_to_chr_rom_is_ignored() {
+        let mut mapper = MockMapper::new();
+
+        mapper.expect_write_chr().return_const(());
+        mapper.expect_read_chr().return_const(0x1234u32);
+
+        let cartridge = NESCartridge::new(
+            &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
+            &[0; NESCartridge::BYTES_PER_CHR_BANK as usize],
+            0x0,
+            Box::new(mapper),
+            Mirroring::HORIZONTAL,
+        );
+
+        cartridge.ppu_write(0x1234, 0xee);
+        assert_eq!(0x0, cartridge.ppu_read(0x1234));
+    }
+
+    #[test]
+    fn test_cartridge_mirroring_from_mapper() {
+        let mut mapper = MockMapper::new();
+        mapper
+            .expect_get_mirroring()
+            .once()
+            .return_const(Some(Mirroring::VERTICAL));
+        mapper.expect_get_mirroring().return_const(None);
+
+        let cartridge = NESCartridge::new(
+            &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
+            &[0; NESCartridge::BYTES_PER_CHR_BANK as usize],
+            0x0,
+            Box::new(mapper),
+            Mirroring::HORIZONTAL,
+        );
+
+        assert_eq!(Mirroring::VERTICAL, cartridge.get_mirroring());
+        assert_eq!(Mirroring::HORIZONTAL, cartridge.get_mirroring());
+    }
+
+    #[test]
+    fn test_cartridge_reset_resets_mapper() {
+        let mut mapper = MockMapper::new();
+        mapper.expect_reset().once().return_const(());
+
+        let cartridge = NESCartridge::new(
+            &[0; NESCartridge::BYTES_PER_PRG_BANK as usize],
+            &[0; NESCartridge::BYTES_PER_CHR_BANK as usize],
+            0x0,
+            Box::new(mapper),
+            Mirroring::HORIZONTAL,
+        );
+
+        cartridge.reset();
+    }
 }
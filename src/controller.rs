@@ -5,6 +5,8 @@ use std::cell::RefCell;
 pub trait Controller {
     fn read(&self) -> u8;
     fn write(&mut self, data: u8);
+    //The next bit read would return, without shifting it out
+    fn peek(&self) -> u8;
 }
 
 #[derive(Debug)]
@@ -58,6 +60,10 @@ impl Controller for NESController {
         buffer_data & 0x1
     }
 
+    fn peek(&self) -> u8 {
+        *self.buffer.borrow() & 0x1
+    }
+
     fn write(&mut self, data: u8) {
         if !self.polling && (data & 0x1) != 0 {
             self.polling = true;
@@ -118,4 +124,14 @@ mod controller_tests {
 
         assert_eq!(0x0, *controller.buffer.borrow());
     }
+
+    #[test]
+    fn test_controller_peek_does_not_shift() {
+        let controller = NESController::new();
+        *controller.buffer.borrow_mut() = 0b0101_0101;
+
+        assert_eq!(0x1, controller.peek());
+        assert_eq!(0x1, controller.peek());
+        assert_eq!(0b0101_0101, *controller.buffer.borrow());
+    }
 }
@@ -1,35 +1,114 @@
+use std::fmt;
+
 use mockall::automock;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
 use crate::bus::Bus;
 
-use self::addr::AddrModeResult;
+use self::{
+    addr::{AddrModeResult, AddrModeType},
+    cycles::Access,
+};
 
 mod addr;
+pub mod asm;
+mod cycles;
+pub mod disasm;
 mod ops;
 
 type Mnemonic = &'static str;
-type AddrModeFn = fn(&mut NESCPU, &dyn Bus) -> AddrModeResult;
 type CycleCountFn = fn(&NESCPU, &AddrModeResult) -> u8;
-type ExecuteFn = fn(&mut NESCPU, &AddrModeResult, &mut dyn Bus);
+type ExecuteFn<B> = fn(&mut NESCPU, &AddrModeResult, &mut B);
+type Instruction<B = dyn Bus> = (Mnemonic, AddrModeType, CycleCountFn, ExecuteFn<B>);
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 enum InstructionType {
+    Fetch,
     Jam,
+    Wait,
     Reset,
     Nmi,
     Irq,
-    Instruction {
-        opcode: u8,
-        addr_mode: AddrModeResult,
-    },
+    Instruction { opcode: u8, access: Access },
 }
 
 #[derive(PartialEq, Debug, Clone)]
 struct CurrentInstruction {
+    cycle: u8,
     remaining_cycles: u8,
     instruction_type: InstructionType,
 }
 
+//Decoded ahead of time so the upcoming instruction can be logged
+#[derive(PartialEq, Debug, Clone)]
+struct Trace {
+    pc: u16,
+    opcode: u8,
+    addr_mode: AddrModeResult,
+    elapsed_cycles: u64,
+}
+
+//Snapshot of the programmer-visible registers, with the flags packed
+//into the status byte as they would be pushed by PHP (minus the B flag)
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct CpuState {
+    pub pc: u16,
+    pub sp: u8,
+
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+
+    pub status: u8,
+
+    pub pending_nmi: bool,
+    pub elapsed_cycles: u64,
+}
+
+//Reported when the CPU halts on a JAM opcode (or STP on the 65C02).
+//The CPU stays halted, ignoring interrupts, until it is reset.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct JamEvent {
+    pub opcode: u8,
+    pub pc: u16,
+    //Elapsed cycles when the opcode was fetched
+    pub cycle: u64,
+}
+
+impl fmt::Display for JamEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CPU jammed on opcode ${:02X} at ${:04X} (cycle {})",
+            self.opcode, self.pc, self.cycle
+        )
+    }
+}
+
+//How the analogue effects of the unstable opcodes are resolved
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum UnstableOpcodes {
+    //XAA and LAX #imm always use this magic constant; SHA, SHX, SHY
+    //and SHS always AND the stored value with the high byte plus 1
+    Fixed { magic: u8 },
+    //The magic constant is drawn from the values seen on real chips, and
+    //the high byte term of SH* stores is sometimes dropped (as happens
+    //when RDY is pulled low), from an RNG with the given seed
+    Seeded(u64),
+}
+
+//Member of the 6502 family the core runs as
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum CpuVariant {
+    //The NES CPU: an NMOS 6502 whose decimal mode is disconnected
+    Ricoh2A03,
+    //ADC and SBC perform BCD arithmetic when the D flag is set
+    Nmos6502,
+    //New instructions and addressing modes in place of the undocumented
+    //opcodes, BCD arithmetic with valid flags and no JMP ($xxFF) bug
+    Wdc65C02,
+}
+
 #[automock]
 pub trait CPU {
     fn clock(&mut self, bus: &mut dyn Bus);
@@ -37,6 +116,19 @@ pub trait CPU {
     fn cpu_irq(&mut self, interrupt: bool);
     fn cpu_nmi(&mut self);
     fn cycles_remaining(&self) -> u8;
+    fn get_jam(&self) -> Option<JamEvent>;
+}
+
+//Clocks the CPU against a known bus type so memory accesses are statically
+//dispatched; CPU::clock keeps the trait object form for mocking
+pub trait StaticCPU<B: Bus>: CPU {
+    fn clock_static(&mut self, bus: &mut B);
+}
+
+impl<B: Bus> StaticCPU<B> for MockCPU {
+    fn clock_static(&mut self, bus: &mut B) {
+        self.clock(bus);
+    }
 }
 
 pub struct NESCPU {
@@ -56,68 +148,152 @@ pub struct NESCPU {
     z: bool, //Bit 1
     c: bool, //Bit 0
 
+    variant: CpuVariant,
     current_instruction: CurrentInstruction,
 
+    //Internal latches of the instruction in flight
+    operand: u16,
+    effective_addr: u16,
+    page_crossed: bool,
+    reads: [(u16, u8); 8],
+    reads_len: usize,
+
     pending_nmi: bool,
     pending_irq: bool,
+    interrupt_polled: bool,
+
+    tracing: bool,
+    trace: Option<Trace>,
+
+    jam: Option<JamEvent>,
+    jam_callback: Option<Box<dyn FnMut(JamEvent)>>,
+
+    unstable_opcodes: UnstableOpcodes,
+    rng: StdRng,
 
     #[allow(arithmetic_overflow)]
     elapsed_cycles: u64,
 }
 
-impl ToString for NESCPU {
-    fn to_string(&self) -> String {
-        match &self.current_instruction.instruction_type {
-            InstructionType::Instruction { opcode, addr_mode } => {
-                format!(
+impl fmt::Display for NESCPU {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.current_instruction.instruction_type, &self.trace) {
+            (InstructionType::Fetch | InstructionType::Instruction { .. }, Some(trace)) => {
+                write!(
+                    f,
                     "{:04X}  {:02X} {: <6} {} {: <27} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
-                    self.pc.wrapping_sub(addr_mode.bytes as u16),
-                    *opcode,
-                    addr_mode.operands,
-                    NESCPU::LOOKUP_TABLE[*opcode as usize].0,
-                    addr_mode.repr,
+                    trace.pc,
+                    trace.opcode,
+                    trace.addr_mode.operands(),
+                    self.decode(trace.opcode).0,
+                    trace.addr_mode.repr(),
                     self.a,
                     self.x,
                     self.y,
                     self.get_status_byte(false),
                     self.sp,
-                    self.elapsed_cycles
+                    trace.elapsed_cycles
                 )
             }
-            InstructionType::Jam => "JAM".to_owned(),
-            InstructionType::Irq => "IRQ".to_owned(),
-            InstructionType::Nmi => "NMI".to_owned(),
-            InstructionType::Reset => "RESET".to_owned(),
+            (InstructionType::Instruction { opcode, .. }, None) => {
+                write!(f, "{}", self.decode(*opcode).0)
+            }
+            (InstructionType::Fetch, None) => write!(f, "FETCH"),
+            (InstructionType::Jam, _) => write!(f, "JAM"),
+            (InstructionType::Wait, _) => write!(f, "WAIT"),
+            (InstructionType::Irq, _) => write!(f, "IRQ"),
+            (InstructionType::Nmi, _) => write!(f, "NMI"),
+            (InstructionType::Reset, _) => write!(f, "RESET"),
         }
     }
 }
 
+//The tables are built per bus type so execute functions are monomorphised
+//for the bus they run against instead of going through a trait object
+trait LookupTable<B: Bus + ?Sized = dyn Bus> {
+    const LOOKUP_TABLE: [Instruction<B>; 256];
+    const LOOKUP_TABLE_65C02: [Instruction<B>; 256];
+}
+
+impl<B: Bus + ?Sized> LookupTable<B> for NESCPU {
+    #[rustfmt::skip]
+    const LOOKUP_TABLE: [Instruction<B>; 256] = [
+        ("BRK", AddrModeType::Imm, NESCPU::brkc, NESCPU::brk), ("ORA", AddrModeType::Indx, NESCPU::orac, NESCPU::ora), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("SLO", AddrModeType::Indx, NESCPU::sloc, NESCPU::slo), ("NOP",  AddrModeType::Zp, NESCPU::nopc, NESCPU::nop), ("ORA",  AddrModeType::Zp, NESCPU::orac, NESCPU::ora), ("ASL",  AddrModeType::Zp, NESCPU::aslc, NESCPU::asl), ("SLO",  AddrModeType::Zp, NESCPU::sloc, NESCPU::slo), ("PHP", AddrModeType::Imp, NESCPU::phpc, NESCPU::php), ("ORA",  AddrModeType::Imm, NESCPU::orac, NESCPU::ora), ("ASL", AddrModeType::Acc, NESCPU::aslc, NESCPU::asl), ("ANC",  AddrModeType::Imm, NESCPU::ancc, NESCPU::anc), ("NOP",  AddrModeType::Abs, NESCPU::nopc, NESCPU::nop), ("ORA",  AddrModeType::Abs, NESCPU::orac, NESCPU::ora), ("ASL",  AddrModeType::Abs, NESCPU::aslc, NESCPU::asl), ("SLO",  AddrModeType::Abs, NESCPU::sloc, NESCPU::slo),
+        ("BPL", AddrModeType::Rel, NESCPU::bplc, NESCPU::bpl), ("ORA", AddrModeType::Indy, NESCPU::orac, NESCPU::ora), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("SLO", AddrModeType::Indy, NESCPU::sloc, NESCPU::slo), ("NOP", AddrModeType::Zpx, NESCPU::nopc, NESCPU::nop), ("ORA", AddrModeType::Zpx, NESCPU::orac, NESCPU::ora), ("ASL", AddrModeType::Zpx, NESCPU::aslc, NESCPU::asl), ("SLO", AddrModeType::Zpx, NESCPU::sloc, NESCPU::slo), ("CLC", AddrModeType::Imp, NESCPU::clcc, NESCPU::clc), ("ORA", AddrModeType::Absy, NESCPU::orac, NESCPU::ora), ("NOP", AddrModeType::Imp, NESCPU::nopc, NESCPU::nop), ("SLO", AddrModeType::Absy, NESCPU::sloc, NESCPU::slo), ("NOP", AddrModeType::Absx, NESCPU::nopc, NESCPU::nop), ("ORA", AddrModeType::Absx, NESCPU::orac, NESCPU::ora), ("ASL", AddrModeType::Absx, NESCPU::aslc, NESCPU::asl), ("SLO", AddrModeType::Absx, NESCPU::sloc, NESCPU::slo),
+        ("JSR", AddrModeType::Abs, NESCPU::jsrc, NESCPU::jsr), ("AND", AddrModeType::Indx, NESCPU::andc, NESCPU::and), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("RLA", AddrModeType::Indx, NESCPU::rlac, NESCPU::rla), ("BIT",  AddrModeType::Zp, NESCPU::bitc, NESCPU::bit), ("AND",  AddrModeType::Zp, NESCPU::andc, NESCPU::and), ("ROL",  AddrModeType::Zp, NESCPU::rolc, NESCPU::rol), ("RLA",  AddrModeType::Zp, NESCPU::rlac, NESCPU::rla), ("PLP", AddrModeType::Imp, NESCPU::plpc, NESCPU::plp), ("AND",  AddrModeType::Imm, NESCPU::andc, NESCPU::and), ("ROL", AddrModeType::Acc, NESCPU::rolc, NESCPU::rol), ("ANC",  AddrModeType::Imm, NESCPU::ancc, NESCPU::anc), ("BIT",  AddrModeType::Abs, NESCPU::bitc, NESCPU::bit), ("AND",  AddrModeType::Abs, NESCPU::andc, NESCPU::and), ("ROL",  AddrModeType::Abs, NESCPU::rolc, NESCPU::rol), ("RLA",  AddrModeType::Abs, NESCPU::rlac, NESCPU::rla),
+        ("BMI", AddrModeType::Rel, NESCPU::bmic, NESCPU::bmi), ("AND", AddrModeType::Indy, NESCPU::andc, NESCPU::and), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("RLA", AddrModeType::Indy, NESCPU::rlac, NESCPU::rla), ("NOP", AddrModeType::Zpx, NESCPU::nopc, NESCPU::nop), ("AND", AddrModeType::Zpx, NESCPU::andc, NESCPU::and), ("ROL", AddrModeType::Zpx, NESCPU::rolc, NESCPU::rol), ("RLA", AddrModeType::Zpx, NESCPU::rlac, NESCPU::rla), ("SEC", AddrModeType::Imp, NESCPU::secc, NESCPU::sec), ("AND", AddrModeType::Absy, NESCPU::andc, NESCPU::and), ("NOP", AddrModeType::Imp, NESCPU::nopc, NESCPU::nop), ("RLA", AddrModeType::Absy, NESCPU::rlac, NESCPU::rla), ("NOP", AddrModeType::Absx, NESCPU::nopc, NESCPU::nop), ("AND", AddrModeType::Absx, NESCPU::andc, NESCPU::and), ("ROL", AddrModeType::Absx, NESCPU::rolc, NESCPU::rol), ("RLA", AddrModeType::Absx, NESCPU::rlac, NESCPU::rla),
+        ("RTI", AddrModeType::Imp, NESCPU::rtic, NESCPU::rti), ("EOR", AddrModeType::Indx, NESCPU::eorc, NESCPU::eor), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("SRE", AddrModeType::Indx, NESCPU::srec, NESCPU::sre), ("NOP",  AddrModeType::Zp, NESCPU::nopc, NESCPU::nop), ("EOR",  AddrModeType::Zp, NESCPU::eorc, NESCPU::eor), ("LSR",  AddrModeType::Zp, NESCPU::lsrc, NESCPU::lsr), ("SRE",  AddrModeType::Zp, NESCPU::srec, NESCPU::sre), ("PHA", AddrModeType::Imp, NESCPU::phac, NESCPU::pha), ("EOR",  AddrModeType::Imm, NESCPU::eorc, NESCPU::eor), ("LSR", AddrModeType::Acc, NESCPU::lsrc, NESCPU::lsr), ("ASR",  AddrModeType::Imm, NESCPU::asrc, NESCPU::asr), ("JMP",  AddrModeType::Abs, NESCPU::jmpc, NESCPU::jmp), ("EOR",  AddrModeType::Abs, NESCPU::eorc, NESCPU::eor), ("LSR",  AddrModeType::Abs, NESCPU::lsrc, NESCPU::lsr), ("SRE",  AddrModeType::Abs, NESCPU::srec, NESCPU::sre),
+        ("BVC", AddrModeType::Rel, NESCPU::bvcc, NESCPU::bvc), ("EOR", AddrModeType::Indy, NESCPU::eorc, NESCPU::eor), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("SRE", AddrModeType::Indy, NESCPU::srec, NESCPU::sre), ("NOP", AddrModeType::Zpx, NESCPU::nopc, NESCPU::nop), ("EOR", AddrModeType::Zpx, NESCPU::eorc, NESCPU::eor), ("LSR", AddrModeType::Zpx, NESCPU::lsrc, NESCPU::lsr), ("SRE", AddrModeType::Zpx, NESCPU::srec, NESCPU::sre), ("CLI", AddrModeType::Imp, NESCPU::clic, NESCPU::cli), ("EOR", AddrModeType::Absy, NESCPU::eorc, NESCPU::eor), ("NOP", AddrModeType::Imp, NESCPU::nopc, NESCPU::nop), ("SRE", AddrModeType::Absy, NESCPU::srec, NESCPU::sre), ("NOP", AddrModeType::Absx, NESCPU::nopc, NESCPU::nop), ("EOR", AddrModeType::Absx, NESCPU::eorc, NESCPU::eor), ("LSR", AddrModeType::Absx, NESCPU::lsrc, NESCPU::lsr), ("SRE", AddrModeType::Absx, NESCPU::srec, NESCPU::sre),
+        ("RTS", AddrModeType::Imp, NESCPU::rtsc, NESCPU::rts), ("ADC", AddrModeType::Indx, NESCPU::adcc, NESCPU::adc), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("RRA", AddrModeType::Indx, NESCPU::rrac, NESCPU::rra), ("NOP",  AddrModeType::Zp, NESCPU::nopc, NESCPU::nop), ("ADC",  AddrModeType::Zp, NESCPU::adcc, NESCPU::adc), ("ROR",  AddrModeType::Zp, NESCPU::rorc, NESCPU::ror), ("RRA",  AddrModeType::Zp, NESCPU::rrac, NESCPU::rra), ("PLA", AddrModeType::Imp, NESCPU::plac, NESCPU::pla), ("ADC",  AddrModeType::Imm, NESCPU::adcc, NESCPU::adc), ("ROR", AddrModeType::Acc, NESCPU::rorc, NESCPU::ror), ("ARR",  AddrModeType::Imm, NESCPU::arrc, NESCPU::arr), ("JMP",  AddrModeType::Ind, NESCPU::jmpc, NESCPU::jmp), ("ADC",  AddrModeType::Abs, NESCPU::adcc, NESCPU::adc), ("ROR",  AddrModeType::Abs, NESCPU::rorc, NESCPU::ror), ("RRA",  AddrModeType::Abs, NESCPU::rrac, NESCPU::rra),
+        ("BVS", AddrModeType::Rel, NESCPU::bvsc, NESCPU::bvs), ("ADC", AddrModeType::Indy, NESCPU::adcc, NESCPU::adc), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("RRA", AddrModeType::Indy, NESCPU::rrac, NESCPU::rra), ("NOP", AddrModeType::Zpx, NESCPU::nopc, NESCPU::nop), ("ADC", AddrModeType::Zpx, NESCPU::adcc, NESCPU::adc), ("ROR", AddrModeType::Zpx, NESCPU::rorc, NESCPU::ror), ("RRA", AddrModeType::Zpx, NESCPU::rrac, NESCPU::rra), ("SEI", AddrModeType::Imp, NESCPU::seic, NESCPU::sei), ("ADC", AddrModeType::Absy, NESCPU::adcc, NESCPU::adc), ("NOP", AddrModeType::Imp, NESCPU::nopc, NESCPU::nop), ("RRA", AddrModeType::Absy, NESCPU::rrac, NESCPU::rra), ("NOP", AddrModeType::Absx, NESCPU::nopc, NESCPU::nop), ("ADC", AddrModeType::Absx, NESCPU::adcc, NESCPU::adc), ("ROR", AddrModeType::Absx, NESCPU::rorc, NESCPU::ror), ("RRA", AddrModeType::Absx, NESCPU::rrac, NESCPU::rra),
+        ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop), ("STA", AddrModeType::Indx, NESCPU::stac, NESCPU::sta), ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop),  ("SAX", AddrModeType::Indx, NESCPU::saxc, NESCPU::sax), ("STY",  AddrModeType::Zp, NESCPU::styc, NESCPU::sty), ("STA",  AddrModeType::Zp, NESCPU::stac, NESCPU::sta), ("STX",  AddrModeType::Zp, NESCPU::stxc, NESCPU::stx), ("SAX",  AddrModeType::Zp, NESCPU::saxc, NESCPU::sax), ("DEY", AddrModeType::Imp, NESCPU::deyc, NESCPU::dey), ("NOP",  AddrModeType::Imm, NESCPU::nopc, NESCPU::nop), ("TXA", AddrModeType::Imp, NESCPU::txac, NESCPU::txa), ("XAA",  AddrModeType::Imm, NESCPU::xaac, NESCPU::xaa), ("STY",  AddrModeType::Abs, NESCPU::styc, NESCPU::sty), ("STA",  AddrModeType::Abs, NESCPU::stac, NESCPU::sta), ("STX",  AddrModeType::Abs, NESCPU::stxc, NESCPU::stx), ("SAX",  AddrModeType::Abs, NESCPU::saxc, NESCPU::sax),
+        ("BCC", AddrModeType::Rel, NESCPU::bccc, NESCPU::bcc), ("STA", AddrModeType::Indy, NESCPU::stac, NESCPU::sta), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("SHA", AddrModeType::Indy, NESCPU::shac, NESCPU::sha), ("STY", AddrModeType::Zpx, NESCPU::styc, NESCPU::sty), ("STA", AddrModeType::Zpx, NESCPU::stac, NESCPU::sta), ("STX", AddrModeType::Zpy, NESCPU::stxc, NESCPU::stx), ("SAX", AddrModeType::Zpy, NESCPU::saxc, NESCPU::sax), ("TYA", AddrModeType::Imp, NESCPU::tyac, NESCPU::tya), ("STA", AddrModeType::Absy, NESCPU::stac, NESCPU::sta), ("TXS", AddrModeType::Imp, NESCPU::txsc, NESCPU::txs), ("SHS", AddrModeType::Absy, NESCPU::shsc, NESCPU::shs), ("SHY", AddrModeType::Absx, NESCPU::shyc, NESCPU::shy), ("STA", AddrModeType::Absx, NESCPU::stac, NESCPU::sta), ("SHX", AddrModeType::Absy, NESCPU::shxc, NESCPU::shx), ("SHA", AddrModeType::Absy, NESCPU::shac, NESCPU::sha),
+        ("LDY", AddrModeType::Imm, NESCPU::ldyc, NESCPU::ldy), ("LDA", AddrModeType::Indx, NESCPU::ldac, NESCPU::lda), ("LDX", AddrModeType::Imm, NESCPU::ldxc, NESCPU::ldx),  ("LAX", AddrModeType::Indx, NESCPU::laxc, NESCPU::lax), ("LDY",  AddrModeType::Zp, NESCPU::ldyc, NESCPU::ldy), ("LDA",  AddrModeType::Zp, NESCPU::ldac, NESCPU::lda), ("LDX",  AddrModeType::Zp, NESCPU::ldxc, NESCPU::ldx), ("LAX",  AddrModeType::Zp, NESCPU::laxc, NESCPU::lax), ("TAY", AddrModeType::Imp, NESCPU::tayc, NESCPU::tay), ("LDA",  AddrModeType::Imm, NESCPU::ldac, NESCPU::lda), ("TAX", AddrModeType::Imp, NESCPU::taxc, NESCPU::tax), ("LAX",  AddrModeType::Imm, NESCPU::laxc, NESCPU::lax), ("LDY",  AddrModeType::Abs, NESCPU::ldyc, NESCPU::ldy), ("LDA",  AddrModeType::Abs, NESCPU::ldac, NESCPU::lda), ("LDX",  AddrModeType::Abs, NESCPU::ldxc, NESCPU::ldx), ("LAX",  AddrModeType::Abs, NESCPU::laxc, NESCPU::lax),
+        ("BCS", AddrModeType::Rel, NESCPU::bcsc, NESCPU::bcs), ("LDA", AddrModeType::Indy, NESCPU::ldac, NESCPU::lda), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("LAX", AddrModeType::Indy, NESCPU::laxc, NESCPU::lax), ("LDY", AddrModeType::Zpx, NESCPU::ldyc, NESCPU::ldy), ("LDA", AddrModeType::Zpx, NESCPU::ldac, NESCPU::lda), ("LDX", AddrModeType::Zpy, NESCPU::ldxc, NESCPU::ldx), ("LAX", AddrModeType::Zpy, NESCPU::laxc, NESCPU::lax), ("CLV", AddrModeType::Imp, NESCPU::clvc, NESCPU::clv), ("LDA", AddrModeType::Absy, NESCPU::ldac, NESCPU::lda), ("TSX", AddrModeType::Imp, NESCPU::tsxc, NESCPU::tsx), ("LAS", AddrModeType::Absy, NESCPU::lasc, NESCPU::las), ("LDY", AddrModeType::Absx, NESCPU::ldyc, NESCPU::ldy), ("LDA", AddrModeType::Absx, NESCPU::ldac, NESCPU::lda), ("LDX", AddrModeType::Absy, NESCPU::ldxc, NESCPU::ldx), ("LAX", AddrModeType::Absy, NESCPU::laxc, NESCPU::lax),
+        ("CPY", AddrModeType::Imm, NESCPU::cpyc, NESCPU::cpy), ("CMP", AddrModeType::Indx, NESCPU::cmpc, NESCPU::cmp), ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop),  ("DCP", AddrModeType::Indx, NESCPU::dcpc, NESCPU::dcp), ("CPY",  AddrModeType::Zp, NESCPU::cpyc, NESCPU::cpy), ("CMP",  AddrModeType::Zp, NESCPU::cmpc, NESCPU::cmp), ("DEC",  AddrModeType::Zp, NESCPU::decc, NESCPU::dec), ("DCP",  AddrModeType::Zp, NESCPU::dcpc, NESCPU::dcp), ("INY", AddrModeType::Imp, NESCPU::inyc, NESCPU::iny), ("CMP",  AddrModeType::Imm, NESCPU::cmpc, NESCPU::cmp), ("DEX", AddrModeType::Imp, NESCPU::dexc, NESCPU::dex), ("SBX",  AddrModeType::Imm, NESCPU::sbxc, NESCPU::sbx), ("CPY",  AddrModeType::Abs, NESCPU::cpyc, NESCPU::cpy), ("CMP",  AddrModeType::Abs, NESCPU::cmpc, NESCPU::cmp), ("DEC",  AddrModeType::Abs, NESCPU::decc, NESCPU::dec), ("DCP",  AddrModeType::Abs, NESCPU::dcpc, NESCPU::dcp),
+        ("BNE", AddrModeType::Rel, NESCPU::bnec, NESCPU::bne), ("CMP", AddrModeType::Indy, NESCPU::cmpc, NESCPU::cmp), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("DCP", AddrModeType::Indy, NESCPU::dcpc, NESCPU::dcp), ("NOP", AddrModeType::Zpx, NESCPU::nopc, NESCPU::nop), ("CMP", AddrModeType::Zpx, NESCPU::cmpc, NESCPU::cmp), ("DEC", AddrModeType::Zpx, NESCPU::decc, NESCPU::dec), ("DCP", AddrModeType::Zpx, NESCPU::dcpc, NESCPU::dcp), ("CLD", AddrModeType::Imp, NESCPU::cldc, NESCPU::cld), ("CMP", AddrModeType::Absy, NESCPU::cmpc, NESCPU::cmp), ("NOP", AddrModeType::Imp, NESCPU::nopc, NESCPU::nop), ("DCP", AddrModeType::Absy, NESCPU::dcpc, NESCPU::dcp), ("NOP", AddrModeType::Absx, NESCPU::nopc, NESCPU::nop), ("CMP", AddrModeType::Absx, NESCPU::cmpc, NESCPU::cmp), ("DEC", AddrModeType::Absx, NESCPU::decc, NESCPU::dec), ("DCP", AddrModeType::Absx, NESCPU::dcpc, NESCPU::dcp),
+        ("CPX", AddrModeType::Imm, NESCPU::cpxc, NESCPU::cpx), ("SBC", AddrModeType::Indx, NESCPU::sbcc, NESCPU::sbc), ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop),  ("ISC", AddrModeType::Indx, NESCPU::iscc, NESCPU::isc), ("CPX",  AddrModeType::Zp, NESCPU::cpxc, NESCPU::cpx), ("SBC",  AddrModeType::Zp, NESCPU::sbcc, NESCPU::sbc), ("INC",  AddrModeType::Zp, NESCPU::incc, NESCPU::inc), ("ISC",  AddrModeType::Zp, NESCPU::iscc, NESCPU::isc), ("INX", AddrModeType::Imp, NESCPU::inxc, NESCPU::inx), ("SBC",  AddrModeType::Imm, NESCPU::sbcc, NESCPU::sbc), ("NOP", AddrModeType::Imp, NESCPU::nopc, NESCPU::nop), ("SBC",  AddrModeType::Imm, NESCPU::sbcc, NESCPU::sbc), ("CPX",  AddrModeType::Abs, NESCPU::cpxc, NESCPU::cpx), ("SBC",  AddrModeType::Abs, NESCPU::sbcc, NESCPU::sbc), ("INC",  AddrModeType::Abs, NESCPU::incc, NESCPU::inc), ("ISC",  AddrModeType::Abs, NESCPU::iscc, NESCPU::isc),
+        ("BEQ", AddrModeType::Rel, NESCPU::beqc, NESCPU::beq), ("SBC", AddrModeType::Indy, NESCPU::sbcc, NESCPU::sbc), ("JAM", AddrModeType::Imp, NESCPU::jamc, NESCPU::_jam), ("ISC", AddrModeType::Indy, NESCPU::iscc, NESCPU::isc), ("NOP", AddrModeType::Zpx, NESCPU::nopc, NESCPU::nop), ("SBC", AddrModeType::Zpx, NESCPU::sbcc, NESCPU::sbc), ("INC", AddrModeType::Zpx, NESCPU::incc, NESCPU::inc), ("ISC", AddrModeType::Zpx, NESCPU::iscc, NESCPU::isc), ("SED", AddrModeType::Imp, NESCPU::sedc, NESCPU::sed), ("SBC", AddrModeType::Absy, NESCPU::sbcc, NESCPU::sbc), ("NOP", AddrModeType::Imp, NESCPU::nopc, NESCPU::nop), ("ISC", AddrModeType::Absy, NESCPU::iscc, NESCPU::isc), ("NOP", AddrModeType::Absx, NESCPU::nopc, NESCPU::nop), ("SBC", AddrModeType::Absx, NESCPU::sbcc, NESCPU::sbc), ("INC", AddrModeType::Absx, NESCPU::incc, NESCPU::inc), ("ISC", AddrModeType::Absx, NESCPU::iscc, NESCPU::isc),
+    ];
+
+    #[rustfmt::skip]
+    const LOOKUP_TABLE_65C02: [Instruction<B>; 256] = NESCPU::patch_lookup_table(&[
+        (0x02, ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop)), (0x22, ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop)), (0x42, ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop)), (0x62, ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop)),
+        (0x82, ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop)), (0xc2, ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop)), (0xe2, ("NOP", AddrModeType::Imm, NESCPU::nopc, NESCPU::nop)),
+        (0x03, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x13, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x23, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x33, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)),
+        (0x43, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x53, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x63, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x73, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)),
+        (0x83, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x93, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0xa3, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0xb3, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)),
+        (0xc3, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0xd3, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0xe3, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0xf3, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)),
+        (0x0b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x1b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x2b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x3b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)),
+        (0x4b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x5b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x6b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x7b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)),
+        (0x8b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0x9b, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0xab, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0xbb, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)),
+        (0xeb, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)), (0xfb, ("NOP", AddrModeType::Imp, NESCPU::nop1c, NESCPU::nop)),
+        (0x44, ("NOP", AddrModeType::Zp, NESCPU::nopc, NESCPU::nop)), (0x54, ("NOP", AddrModeType::Zpx, NESCPU::nopc, NESCPU::nop)), (0xd4, ("NOP", AddrModeType::Zpx, NESCPU::nopc, NESCPU::nop)), (0xf4, ("NOP", AddrModeType::Zpx, NESCPU::nopc, NESCPU::nop)),
+        (0x5c, ("NOP", AddrModeType::Abs, NESCPU::nop8c, NESCPU::nop)), (0xdc, ("NOP", AddrModeType::Abs, NESCPU::nopc, NESCPU::nop)), (0xfc, ("NOP", AddrModeType::Abs, NESCPU::nopc, NESCPU::nop)),
+        (0x12, ("ORA", AddrModeType::Indzp, NESCPU::orac, NESCPU::ora)), (0x32, ("AND", AddrModeType::Indzp, NESCPU::andc, NESCPU::and)), (0x52, ("EOR", AddrModeType::Indzp, NESCPU::eorc, NESCPU::eor)), (0x72, ("ADC", AddrModeType::Indzp, NESCPU::adcc, NESCPU::adc)),
+        (0x92, ("STA", AddrModeType::Indzp, NESCPU::stac, NESCPU::sta)), (0xb2, ("LDA", AddrModeType::Indzp, NESCPU::ldac, NESCPU::lda)), (0xd2, ("CMP", AddrModeType::Indzp, NESCPU::cmpc, NESCPU::cmp)), (0xf2, ("SBC", AddrModeType::Indzp, NESCPU::sbcc, NESCPU::sbc)),
+        (0x04, ("TSB", AddrModeType::Zp, NESCPU::tsbc, NESCPU::tsb)), (0x0c, ("TSB", AddrModeType::Abs, NESCPU::tsbc, NESCPU::tsb)), (0x14, ("TRB", AddrModeType::Zp, NESCPU::trbc, NESCPU::trb)), (0x1c, ("TRB", AddrModeType::Abs, NESCPU::trbc, NESCPU::trb)),
+        (0x1a, ("INC", AddrModeType::Acc, NESCPU::incc, NESCPU::inc)), (0x3a, ("DEC", AddrModeType::Acc, NESCPU::decc, NESCPU::dec)),
+        (0x34, ("BIT", AddrModeType::Zpx, NESCPU::bitc, NESCPU::bit)), (0x3c, ("BIT", AddrModeType::Absx, NESCPU::bitc, NESCPU::bit)), (0x89, ("BIT", AddrModeType::Imm, NESCPU::bitc, NESCPU::bit)),
+        (0x5a, ("PHY", AddrModeType::Imp, NESCPU::phyc, NESCPU::phy)), (0x7a, ("PLY", AddrModeType::Imp, NESCPU::plyc, NESCPU::ply)), (0xda, ("PHX", AddrModeType::Imp, NESCPU::phxc, NESCPU::phx)), (0xfa, ("PLX", AddrModeType::Imp, NESCPU::plxc, NESCPU::plx)),
+        (0x64, ("STZ", AddrModeType::Zp, NESCPU::stzc, NESCPU::stz)), (0x74, ("STZ", AddrModeType::Zpx, NESCPU::stzc, NESCPU::stz)), (0x9c, ("STZ", AddrModeType::Abs, NESCPU::stzc, NESCPU::stz)), (0x9e, ("STZ", AddrModeType::Absx, NESCPU::stzc, NESCPU::stz)),
+        (0x7c, ("JMP", AddrModeType::Indabsx, NESCPU::jmpc, NESCPU::jmp)), (0x80, ("BRA", AddrModeType::Rel, NESCPU::brac, NESCPU::bra)),
+        (0xcb, ("WAI", AddrModeType::Imp, NESCPU::waic, NESCPU::wai)), (0xdb, ("STP", AddrModeType::Imp, NESCPU::stpc, NESCPU::stp)),
+        (0x07, ("RMB0", AddrModeType::Zp, NESCPU::rmbc, NESCPU::rmb::<0, B>)), (0x17, ("RMB1", AddrModeType::Zp, NESCPU::rmbc, NESCPU::rmb::<1, B>)), (0x27, ("RMB2", AddrModeType::Zp, NESCPU::rmbc, NESCPU::rmb::<2, B>)), (0x37, ("RMB3", AddrModeType::Zp, NESCPU::rmbc, NESCPU::rmb::<3, B>)),
+        (0x47, ("RMB4", AddrModeType::Zp, NESCPU::rmbc, NESCPU::rmb::<4, B>)), (0x57, ("RMB5", AddrModeType::Zp, NESCPU::rmbc, NESCPU::rmb::<5, B>)), (0x67, ("RMB6", AddrModeType::Zp, NESCPU::rmbc, NESCPU::rmb::<6, B>)), (0x77, ("RMB7", AddrModeType::Zp, NESCPU::rmbc, NESCPU::rmb::<7, B>)),
+        (0x87, ("SMB0", AddrModeType::Zp, NESCPU::smbc, NESCPU::smb::<0, B>)), (0x97, ("SMB1", AddrModeType::Zp, NESCPU::smbc, NESCPU::smb::<1, B>)), (0xa7, ("SMB2", AddrModeType::Zp, NESCPU::smbc, NESCPU::smb::<2, B>)), (0xb7, ("SMB3", AddrModeType::Zp, NESCPU::smbc, NESCPU::smb::<3, B>)),
+        (0xc7, ("SMB4", AddrModeType::Zp, NESCPU::smbc, NESCPU::smb::<4, B>)), (0xd7, ("SMB5", AddrModeType::Zp, NESCPU::smbc, NESCPU::smb::<5, B>)), (0xe7, ("SMB6", AddrModeType::Zp, NESCPU::smbc, NESCPU::smb::<6, B>)), (0xf7, ("SMB7", AddrModeType::Zp, NESCPU::smbc, NESCPU::smb::<7, B>)),
+        (0x0f, ("BBR0", AddrModeType::Zprel, NESCPU::bbrc::<0>, NESCPU::bbr::<0, B>)), (0x1f, ("BBR1", AddrModeType::Zprel, NESCPU::bbrc::<1>, NESCPU::bbr::<1, B>)), (0x2f, ("BBR2", AddrModeType::Zprel, NESCPU::bbrc::<2>, NESCPU::bbr::<2, B>)), (0x3f, ("BBR3", AddrModeType::Zprel, NESCPU::bbrc::<3>, NESCPU::bbr::<3, B>)),
+        (0x4f, ("BBR4", AddrModeType::Zprel, NESCPU::bbrc::<4>, NESCPU::bbr::<4, B>)), (0x5f, ("BBR5", AddrModeType::Zprel, NESCPU::bbrc::<5>, NESCPU::bbr::<5, B>)), (0x6f, ("BBR6", AddrModeType::Zprel, NESCPU::bbrc::<6>, NESCPU::bbr::<6, B>)), (0x7f, ("BBR7", AddrModeType::Zprel, NESCPU::bbrc::<7>, NESCPU::bbr::<7, B>)),
+        (0x8f, ("BBS0", AddrModeType::Zprel, NESCPU::bbsc::<0>, NESCPU::bbs::<0, B>)), (0x9f, ("BBS1", AddrModeType::Zprel, NESCPU::bbsc::<1>, NESCPU::bbs::<1, B>)), (0xaf, ("BBS2", AddrModeType::Zprel, NESCPU::bbsc::<2>, NESCPU::bbs::<2, B>)), (0xbf, ("BBS3", AddrModeType::Zprel, NESCPU::bbsc::<3>, NESCPU::bbs::<3, B>)),
+        (0xcf, ("BBS4", AddrModeType::Zprel, NESCPU::bbsc::<4>, NESCPU::bbs::<4, B>)), (0xdf, ("BBS5", AddrModeType::Zprel, NESCPU::bbsc::<5>, NESCPU::bbs::<5, B>)), (0xef, ("BBS6", AddrModeType::Zprel, NESCPU::bbsc::<6>, NESCPU::bbs::<6, B>)), (0xff, ("BBS7", AddrModeType::Zprel, NESCPU::bbsc::<7>, NESCPU::bbs::<7, B>)),
+    ]);
+}
+
 impl NESCPU {
     const NMI_VECTOR: u16 = 0xfffa;
     const RESET_VECTOR: u16 = 0xfffc;
     const IRQ_VECTOR: u16 = 0xfffe;
 
-    #[rustfmt::skip]
-    const LOOKUP_TABLE: [(Mnemonic, AddrModeFn, CycleCountFn, ExecuteFn); 256] = [
-        ("BRK", NESCPU::imm, NESCPU::brkc, NESCPU::brk), ("ORA", NESCPU::indx, NESCPU::orac, NESCPU::ora), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("SLO", NESCPU::indx, NESCPU::sloc, NESCPU::slo), ("NOP",  NESCPU::zp, NESCPU::nopc, NESCPU::nop), ("ORA",  NESCPU::zp, NESCPU::orac, NESCPU::ora), ("ASL",  NESCPU::zp, NESCPU::aslc, NESCPU::asl), ("SLO",  NESCPU::zp, NESCPU::sloc, NESCPU::slo), ("PHP", NESCPU::imp, NESCPU::phpc, NESCPU::php), ("ORA",  NESCPU::imm, NESCPU::orac, NESCPU::ora), ("ASL", NESCPU::acc, NESCPU::aslc, NESCPU::asl), ("ANC",  NESCPU::imm, NESCPU::ancc, NESCPU::anc), ("NOP",  NESCPU::abs, NESCPU::nopc, NESCPU::nop), ("ORA",  NESCPU::abs, NESCPU::orac, NESCPU::ora), ("ASL",  NESCPU::abs, NESCPU::aslc, NESCPU::asl), ("SLO",  NESCPU::abs, NESCPU::sloc, NESCPU::slo),
-        ("BPL", NESCPU::rel, NESCPU::bplc, NESCPU::bpl), ("ORA", NESCPU::indy, NESCPU::orac, NESCPU::ora), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("SLO", NESCPU::indy, NESCPU::sloc, NESCPU::slo), ("NOP", NESCPU::zpx, NESCPU::nopc, NESCPU::nop), ("ORA", NESCPU::zpx, NESCPU::orac, NESCPU::ora), ("ASL", NESCPU::zpx, NESCPU::aslc, NESCPU::asl), ("SLO", NESCPU::zpx, NESCPU::sloc, NESCPU::slo), ("CLC", NESCPU::imp, NESCPU::clcc, NESCPU::clc), ("ORA", NESCPU::absy, NESCPU::orac, NESCPU::ora), ("NOP", NESCPU::imp, NESCPU::nopc, NESCPU::nop), ("SLO", NESCPU::absy, NESCPU::sloc, NESCPU::slo), ("NOP", NESCPU::absx, NESCPU::nopc, NESCPU::nop), ("ORA", NESCPU::absx, NESCPU::orac, NESCPU::ora), ("ASL", NESCPU::absx, NESCPU::aslc, NESCPU::asl), ("SLO", NESCPU::absx, NESCPU::sloc, NESCPU::slo),
-        ("JSR", NESCPU::abs, NESCPU::jsrc, NESCPU::jsr), ("AND", NESCPU::indx, NESCPU::andc, NESCPU::and), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("RLA", NESCPU::indx, NESCPU::rlac, NESCPU::rla), ("BIT",  NESCPU::zp, NESCPU::bitc, NESCPU::bit), ("AND",  NESCPU::zp, NESCPU::andc, NESCPU::and), ("ROL",  NESCPU::zp, NESCPU::rolc, NESCPU::rol), ("RLA",  NESCPU::zp, NESCPU::rlac, NESCPU::rla), ("PLP", NESCPU::imp, NESCPU::plpc, NESCPU::plp), ("AND",  NESCPU::imm, NESCPU::andc, NESCPU::and), ("ROL", NESCPU::acc, NESCPU::rolc, NESCPU::rol), ("ANC",  NESCPU::imm, NESCPU::ancc, NESCPU::anc), ("BIT",  NESCPU::abs, NESCPU::bitc, NESCPU::bit), ("AND",  NESCPU::abs, NESCPU::andc, NESCPU::and), ("ROL",  NESCPU::abs, NESCPU::rolc, NESCPU::rol), ("RLA",  NESCPU::abs, NESCPU::rlac, NESCPU::rla),
-        ("BMI", NESCPU::rel, NESCPU::bmic, NESCPU::bmi), ("AND", NESCPU::indy, NESCPU::andc, NESCPU::and), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("RLA", NESCPU::indy, NESCPU::rlac, NESCPU::rla), ("NOP", NESCPU::zpx, NESCPU::nopc, NESCPU::nop), ("AND", NESCPU::zpx, NESCPU::andc, NESCPU::and), ("ROL", NESCPU::zpx, NESCPU::rolc, NESCPU::rol), ("RLA", NESCPU::zpx, NESCPU::rlac, NESCPU::rla), ("SEC", NESCPU::imp, NESCPU::secc, NESCPU::sec), ("AND", NESCPU::absy, NESCPU::andc, NESCPU::and), ("NOP", NESCPU::imp, NESCPU::nopc, NESCPU::nop), ("RLA", NESCPU::absy, NESCPU::rlac, NESCPU::rla), ("NOP", NESCPU::absx, NESCPU::nopc, NESCPU::nop), ("AND", NESCPU::absx, NESCPU::andc, NESCPU::and), ("ROL", NESCPU::absx, NESCPU::rolc, NESCPU::rol), ("RLA", NESCPU::absx, NESCPU::rlac, NESCPU::rla),
-        ("RTI", NESCPU::imp, NESCPU::rtic, NESCPU::rti), ("EOR", NESCPU::indx, NESCPU::eorc, NESCPU::eor), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("SRE", NESCPU::indx, NESCPU::srec, NESCPU::sre), ("NOP",  NESCPU::zp, NESCPU::nopc, NESCPU::nop), ("EOR",  NESCPU::zp, NESCPU::eorc, NESCPU::eor), ("LSR",  NESCPU::zp, NESCPU::lsrc, NESCPU::lsr), ("SRE",  NESCPU::zp, NESCPU::srec, NESCPU::sre), ("PHA", NESCPU::imp, NESCPU::phac, NESCPU::pha), ("EOR",  NESCPU::imm, NESCPU::eorc, NESCPU::eor), ("LSR", NESCPU::acc, NESCPU::lsrc, NESCPU::lsr), ("ASR",  NESCPU::imm, NESCPU::asrc, NESCPU::asr), ("JMP",  NESCPU::abs, NESCPU::jmpc, NESCPU::jmp), ("EOR",  NESCPU::abs, NESCPU::eorc, NESCPU::eor), ("LSR",  NESCPU::abs, NESCPU::lsrc, NESCPU::lsr), ("SRE",  NESCPU::abs, NESCPU::srec, NESCPU::sre),
-        ("BVC", NESCPU::rel, NESCPU::bvcc, NESCPU::bvc), ("EOR", NESCPU::indy, NESCPU::eorc, NESCPU::eor), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("SRE", NESCPU::indy, NESCPU::srec, NESCPU::sre), ("NOP", NESCPU::zpx, NESCPU::nopc, NESCPU::nop), ("EOR", NESCPU::zpx, NESCPU::eorc, NESCPU::eor), ("LSR", NESCPU::zpx, NESCPU::lsrc, NESCPU::lsr), ("SRE", NESCPU::zpx, NESCPU::srec, NESCPU::sre), ("CLI", NESCPU::imp, NESCPU::clic, NESCPU::cli), ("EOR", NESCPU::absy, NESCPU::eorc, NESCPU::eor), ("NOP", NESCPU::imp, NESCPU::nopc, NESCPU::nop), ("SRE", NESCPU::absy, NESCPU::srec, NESCPU::sre), ("NOP", NESCPU::absx, NESCPU::nopc, NESCPU::nop), ("EOR", NESCPU::absx, NESCPU::eorc, NESCPU::eor), ("LSR", NESCPU::absx, NESCPU::lsrc, NESCPU::lsr), ("SRE", NESCPU::absx, NESCPU::srec, NESCPU::sre),
-        ("RTS", NESCPU::imp, NESCPU::rtsc, NESCPU::rts), ("ADC", NESCPU::indx, NESCPU::adcc, NESCPU::adc), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("RRA", NESCPU::indx, NESCPU::rrac, NESCPU::rra), ("NOP",  NESCPU::zp, NESCPU::nopc, NESCPU::nop), ("ADC",  NESCPU::zp, NESCPU::adcc, NESCPU::adc), ("ROR",  NESCPU::zp, NESCPU::rorc, NESCPU::ror), ("RRA",  NESCPU::zp, NESCPU::rrac, NESCPU::rra), ("PLA", NESCPU::imp, NESCPU::plac, NESCPU::pla), ("ADC",  NESCPU::imm, NESCPU::adcc, NESCPU::adc), ("ROR", NESCPU::acc, NESCPU::rorc, NESCPU::ror), ("ARR",  NESCPU::imm, NESCPU::arrc, NESCPU::arr), ("JMP",  NESCPU::ind, NESCPU::jmpc, NESCPU::jmp), ("ADC",  NESCPU::abs, NESCPU::adcc, NESCPU::adc), ("ROR",  NESCPU::abs, NESCPU::rorc, NESCPU::ror), ("RRA",  NESCPU::abs, NESCPU::rrac, NESCPU::rra),
-        ("BVS", NESCPU::rel, NESCPU::bvsc, NESCPU::bvs), ("ADC", NESCPU::indy, NESCPU::adcc, NESCPU::adc), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("RRA", NESCPU::indy, NESCPU::rrac, NESCPU::rra), ("NOP", NESCPU::zpx, NESCPU::nopc, NESCPU::nop), ("ADC", NESCPU::zpx, NESCPU::adcc, NESCPU::adc), ("ROR", NESCPU::zpx, NESCPU::rorc, NESCPU::ror), ("RRA", NESCPU::zpx, NESCPU::rrac, NESCPU::rra), ("SEI", NESCPU::imp, NESCPU::seic, NESCPU::sei), ("ADC", NESCPU::absy, NESCPU::adcc, NESCPU::adc), ("NOP", NESCPU::imp, NESCPU::nopc, NESCPU::nop), ("RRA", NESCPU::absy, NESCPU::rrac, NESCPU::rra), ("NOP", NESCPU::absx, NESCPU::nopc, NESCPU::nop), ("ADC", NESCPU::absx, NESCPU::adcc, NESCPU::adc), ("ROR", NESCPU::absx, NESCPU::rorc, NESCPU::ror), ("RRA", NESCPU::absx, NESCPU::rrac, NESCPU::rra),
-        ("NOP", NESCPU::imm, NESCPU::nopc, NESCPU::nop), ("STA", NESCPU::indx, NESCPU::stac, NESCPU::sta), ("NOP", NESCPU::imm, NESCPU::nopc, NESCPU::nop),  ("SAX", NESCPU::indx, NESCPU::saxc, NESCPU::sax), ("STY",  NESCPU::zp, NESCPU::styc, NESCPU::sty), ("STA",  NESCPU::zp, NESCPU::stac, NESCPU::sta), ("STX",  NESCPU::zp, NESCPU::stxc, NESCPU::stx), ("SAX",  NESCPU::zp, NESCPU::saxc, NESCPU::sax), ("DEY", NESCPU::imp, NESCPU::deyc, NESCPU::dey), ("NOP",  NESCPU::imm, NESCPU::nopc, NESCPU::nop), ("TXA", NESCPU::imp, NESCPU::txac, NESCPU::txa), ("XAA",  NESCPU::imm, NESCPU::xaac, NESCPU::xaa), ("STY",  NESCPU::abs, NESCPU::styc, NESCPU::sty), ("STA",  NESCPU::abs, NESCPU::stac, NESCPU::sta), ("STX",  NESCPU::abs, NESCPU::stxc, NESCPU::stx), ("SAX",  NESCPU::abs, NESCPU::saxc, NESCPU::sax),
-        ("BCC", NESCPU::rel, NESCPU::bccc, NESCPU::bcc), ("STA", NESCPU::indy, NESCPU::stac, NESCPU::sta), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("SHA", NESCPU::indy, NESCPU::shac, NESCPU::sha), ("STY", NESCPU::zpx, NESCPU::styc, NESCPU::sty), ("STA", NESCPU::zpx, NESCPU::stac, NESCPU::sta), ("STX", NESCPU::zpy, NESCPU::stxc, NESCPU::stx), ("SAX", NESCPU::zpy, NESCPU::saxc, NESCPU::sax), ("TYA", NESCPU::imp, NESCPU::tyac, NESCPU::tya), ("STA", NESCPU::absy, NESCPU::stac, NESCPU::sta), ("TXS", NESCPU::imp, NESCPU::txsc, NESCPU::txs), ("SHS", NESCPU::absy, NESCPU::shsc, NESCPU::shs), ("SHY", NESCPU::absx, NESCPU::shyc, NESCPU::shy), ("STA", NESCPU::absx, NESCPU::stac, NESCPU::sta), ("SHX", NESCPU::absy, NESCPU::shxc, NESCPU::shx), ("SHA", NESCPU::absy, NESCPU::shac, NESCPU::sha),
-        ("LDY", NESCPU::imm, NESCPU::ldyc, NESCPU::ldy), ("LDA", NESCPU::indx, NESCPU::ldac, NESCPU::lda), ("LDX", NESCPU::imm, NESCPU::ldxc, NESCPU::ldx),  ("LAX", NESCPU::indx, NESCPU::laxc, NESCPU::lax), ("LDY",  NESCPU::zp, NESCPU::ldyc, NESCPU::ldy), ("LDA",  NESCPU::zp, NESCPU::ldac, NESCPU::lda), ("LDX",  NESCPU::zp, NESCPU::ldxc, NESCPU::ldx), ("LAX",  NESCPU::zp, NESCPU::laxc, NESCPU::lax), ("TAY", NESCPU::imp, NESCPU::tayc, NESCPU::tay), ("LDA",  NESCPU::imm, NESCPU::ldac, NESCPU::lda), ("TAX", NESCPU::imp, NESCPU::taxc, NESCPU::tax), ("LAX",  NESCPU::imm, NESCPU::laxc, NESCPU::lax), ("LDY",  NESCPU::abs, NESCPU::ldyc, NESCPU::ldy), ("LDA",  NESCPU::abs, NESCPU::ldac, NESCPU::lda), ("LDX",  NESCPU::abs, NESCPU::ldxc, NESCPU::ldx), ("LAX",  NESCPU::abs, NESCPU::laxc, NESCPU::lax),
-        ("BCS", NESCPU::rel, NESCPU::bcsc, NESCPU::bcs), ("LDA", NESCPU::indy, NESCPU::ldac, NESCPU::lda), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("LAX", NESCPU::indy, NESCPU::laxc, NESCPU::lax), ("LDY", NESCPU::zpx, NESCPU::ldyc, NESCPU::ldy), ("LDA", NESCPU::zpx, NESCPU::ldac, NESCPU::lda), ("LDX", NESCPU::zpy, NESCPU::ldxc, NESCPU::ldx), ("LAX", NESCPU::zpy, NESCPU::laxc, NESCPU::lax), ("CLV", NESCPU::imp, NESCPU::clvc, NESCPU::clv), ("LDA", NESCPU::absy, NESCPU::ldac, NESCPU::lda), ("TSX", NESCPU::imp, NESCPU::tsxc, NESCPU::tsx), ("LAS", NESCPU::absy, NESCPU::lasc, NESCPU::las), ("LDY", NESCPU::absx, NESCPU::ldyc, NESCPU::ldy), ("LDA", NESCPU::absx, NESCPU::ldac, NESCPU::lda), ("LDX", NESCPU::absy, NESCPU::ldxc, NESCPU::ldx), ("LAX", NESCPU::absy, NESCPU::laxc, NESCPU::lax),
-        ("CPY", NESCPU::imm, NESCPU::cpyc, NESCPU::cpy), ("CMP", NESCPU::indx, NESCPU::cmpc, NESCPU::cmp), ("NOP", NESCPU::imm, NESCPU::nopc, NESCPU::nop),  ("DCP", NESCPU::indx, NESCPU::dcpc, NESCPU::dcp), ("CPY",  NESCPU::zp, NESCPU::cpyc, NESCPU::cpy), ("CMP",  NESCPU::zp, NESCPU::cmpc, NESCPU::cmp), ("DEC",  NESCPU::zp, NESCPU::decc, NESCPU::dec), ("DCP",  NESCPU::zp, NESCPU::dcpc, NESCPU::dcp), ("INY", NESCPU::imp, NESCPU::inyc, NESCPU::iny), ("CMP",  NESCPU::imm, NESCPU::cmpc, NESCPU::cmp), ("DEX", NESCPU::imp, NESCPU::dexc, NESCPU::dex), ("SBX",  NESCPU::imm, NESCPU::sbxc, NESCPU::sbx), ("CPY",  NESCPU::abs, NESCPU::cpyc, NESCPU::cpy), ("CMP",  NESCPU::abs, NESCPU::cmpc, NESCPU::cmp), ("DEC",  NESCPU::abs, NESCPU::decc, NESCPU::dec), ("DCP",  NESCPU::abs, NESCPU::dcpc, NESCPU::dcp),
-        ("BNE", NESCPU::rel, NESCPU::bnec, NESCPU::bne), ("CMP", NESCPU::indy, NESCPU::cmpc, NESCPU::cmp), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("DCP", NESCPU::indy, NESCPU::dcpc, NESCPU::dcp), ("NOP", NESCPU::zpx, NESCPU::nopc, NESCPU::nop), ("CMP", NESCPU::zpx, NESCPU::cmpc, NESCPU::cmp), ("DEC", NESCPU::zpx, NESCPU::decc, NESCPU::dec), ("DCP", NESCPU::zpx, NESCPU::dcpc, NESCPU::dcp), ("CLD", NESCPU::imp, NESCPU::cldc, NESCPU::cld), ("CMP", NESCPU::absy, NESCPU::cmpc, NESCPU::cmp), ("NOP", NESCPU::imp, NESCPU::nopc, NESCPU::nop), ("DCP", NESCPU::absy, NESCPU::dcpc, NESCPU::dcp), ("NOP", NESCPU::absx, NESCPU::nopc, NESCPU::nop), ("CMP", NESCPU::absx, NESCPU::cmpc, NESCPU::cmp), ("DEC", NESCPU::absx, NESCPU::decc, NESCPU::dec), ("DCP", NESCPU::absx, NESCPU::dcpc, NESCPU::dcp),
-        ("CPX", NESCPU::imm, NESCPU::cpxc, NESCPU::cpx), ("SBC", NESCPU::indx, NESCPU::sbcc, NESCPU::sbc), ("NOP", NESCPU::imm, NESCPU::nopc, NESCPU::nop),  ("ISC", NESCPU::indx, NESCPU::iscc, NESCPU::isc), ("CPX",  NESCPU::zp, NESCPU::cpxc, NESCPU::cpx), ("SBC",  NESCPU::zp, NESCPU::sbcc, NESCPU::sbc), ("INC",  NESCPU::zp, NESCPU::incc, NESCPU::inc), ("ISC",  NESCPU::zp, NESCPU::iscc, NESCPU::isc), ("INX", NESCPU::imp, NESCPU::inxc, NESCPU::inx), ("SBC",  NESCPU::imm, NESCPU::sbcc, NESCPU::sbc), ("NOP", NESCPU::imp, NESCPU::nopc, NESCPU::nop), ("SBC",  NESCPU::imm, NESCPU::sbcc, NESCPU::sbc), ("CPX",  NESCPU::abs, NESCPU::cpxc, NESCPU::cpx), ("SBC",  NESCPU::abs, NESCPU::sbcc, NESCPU::sbc), ("INC",  NESCPU::abs, NESCPU::incc, NESCPU::inc), ("ISC",  NESCPU::abs, NESCPU::iscc, NESCPU::isc),
-        ("BEQ", NESCPU::rel, NESCPU::beqc, NESCPU::beq), ("SBC", NESCPU::indy, NESCPU::sbcc, NESCPU::sbc), ("JAM", NESCPU::imp, NESCPU::jamc, NESCPU::_jam), ("ISC", NESCPU::indy, NESCPU::iscc, NESCPU::isc), ("NOP", NESCPU::zpx, NESCPU::nopc, NESCPU::nop), ("SBC", NESCPU::zpx, NESCPU::sbcc, NESCPU::sbc), ("INC", NESCPU::zpx, NESCPU::incc, NESCPU::inc), ("ISC", NESCPU::zpx, NESCPU::iscc, NESCPU::isc), ("SED", NESCPU::imp, NESCPU::sedc, NESCPU::sed), ("SBC", NESCPU::absy, NESCPU::sbcc, NESCPU::sbc), ("NOP", NESCPU::imp, NESCPU::nopc, NESCPU::nop), ("ISC", NESCPU::absy, NESCPU::iscc, NESCPU::isc), ("NOP", NESCPU::absx, NESCPU::nopc, NESCPU::nop), ("SBC", NESCPU::absx, NESCPU::sbcc, NESCPU::sbc), ("INC", NESCPU::absx, NESCPU::incc, NESCPU::inc), ("ISC", NESCPU::absx, NESCPU::iscc, NESCPU::isc),
-    ];
+    //Builds a variant's table by replacing entries of the NMOS one
+    const fn patch_lookup_table<B: Bus + ?Sized>(
+        patches: &[(u8, Instruction<B>)],
+    ) -> [Instruction<B>; 256] {
+        let mut table = <NESCPU as LookupTable<B>>::LOOKUP_TABLE;
+        let mut i = 0;
+        while i < patches.len() {
+            table[patches[i].0 as usize] = patches[i].1;
+            i += 1;
+        }
+
+        table
+    }
 
     pub fn new() -> Self {
+        Self::with_variant(CpuVariant::Ricoh2A03)
+    }
+
+    pub fn with_variant(variant: CpuVariant) -> Self {
         Self {
             pc: 0,
             sp: 0xff,
@@ -135,22 +311,101 @@ impl NESCPU {
             z: false, //Bit 1
             c: false, //Bit 0
 
+            variant,
             current_instruction: CurrentInstruction {
+                cycle: 0,
                 remaining_cycles: 7,
                 instruction_type: InstructionType::Reset,
             },
 
+            operand: 0,
+            effective_addr: 0,
+            page_crossed: false,
+            reads: [(0, 0); 8],
+            reads_len: 0,
+
             pending_nmi: false,
             pending_irq: false,
+            interrupt_polled: false,
+
+            tracing: false,
+            trace: None,
+
+            jam: None,
+            jam_callback: None,
+
+            unstable_opcodes: UnstableOpcodes::Fixed { magic: 0xee },
+            rng: StdRng::seed_from_u64(0),
 
             elapsed_cycles: 0,
         }
     }
+
+    pub fn get_variant(&self) -> CpuVariant {
+        self.variant
+    }
+
+    pub fn get_state(&self) -> CpuState {
+        CpuState {
+            pc: self.pc,
+            sp: self.sp,
+
+            a: self.a,
+            x: self.x,
+            y: self.y,
+
+            status: self.get_status_byte(false),
+
+            pending_nmi: self.pending_nmi,
+            elapsed_cycles: self.elapsed_cycles,
+        }
+    }
+
+    //Bits 4 and 5 of the status byte are ignored. Registers are replaced
+    //immediately, so an instruction in flight continues with the new values.
+    pub fn set_state(&mut self, state: &CpuState) {
+        self.pc = state.pc;
+        self.sp = state.sp;
+
+        self.a = state.a;
+        self.x = state.x;
+        self.y = state.y;
+
+        self.set_status_byte(state.status);
+
+        self.pending_nmi = state.pending_nmi;
+        self.elapsed_cycles = state.elapsed_cycles;
+
+        //The decoded instruction may no longer be the one at PC
+        self.trace = None;
+    }
+
+    pub fn set_unstable_opcodes(&mut self, unstable_opcodes: UnstableOpcodes) {
+        self.unstable_opcodes = unstable_opcodes;
+        if let UnstableOpcodes::Seeded(seed) = unstable_opcodes {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+    }
+
+    //Called once each time the CPU jams
+    pub fn set_jam_callback(&mut self, callback: Option<Box<dyn FnMut(JamEvent)>>) {
+        self.jam_callback = callback;
+    }
+
+    //When enabled, the next instruction is decoded at every instruction
+    //boundary so that to_string() and cycles_remaining() describe it.
+    //Decoding reads the instruction bytes (and any pointer) ahead of time.
+    pub fn set_tracing(&mut self, tracing: bool) {
+        self.tracing = tracing;
+        if !tracing {
+            self.trace = None;
+        }
+    }
 }
 
 impl CPU for NESCPU {
     fn cpu_nmi(&mut self) {
-        //Edge-detected
+        //Called on a rising edge of the NMI line, latched until serviced
         self.pending_nmi = true;
     }
 
@@ -161,23 +416,42 @@ impl CPU for NESCPU {
 
     fn cpu_reset(&mut self) {
         self.current_instruction = CurrentInstruction {
+            cycle: 0,
             remaining_cycles: self.resetc(),
             instruction_type: InstructionType::Reset,
-        }
+        };
+        self.trace = None;
+        self.jam = None;
     }
 
-    fn clock(&mut self, bus: &mut dyn Bus) {
-        self.elapsed_cycles += 1;
-        self.current_instruction.remaining_cycles -= 1;
-
-        if self.current_instruction.remaining_cycles == 0 {
-            self.execute_operation(bus);
-        }
+    fn clock(&mut self, mut bus: &mut dyn Bus) {
+        self.clock_static(&mut bus);
     }
 
     fn cycles_remaining(&self) -> u8 {
         self.current_instruction.remaining_cycles
     }
+
+    fn get_jam(&self) -> Option<JamEvent> {
+        self.jam
+    }
+}
+
+impl<B: Bus> StaticCPU<B> for NESCPU {
+    fn clock_static(&mut self, bus: &mut B) {
+        self.elapsed_cycles += 1;
+        self.current_instruction.cycle = self.current_instruction.cycle.wrapping_add(1);
+        self.current_instruction.remaining_cycles =
+            self.current_instruction.remaining_cycles.saturating_sub(1);
+
+        if self.execute_cycle(bus) {
+            self.poll_for_interrupts_or_fetch_next_instruction(bus);
+        } else if self.polls_interrupts() {
+            //Interrupts are polled at the end of every cycle, so the last
+            //poll before an instruction completes is its penultimate cycle
+            self.interrupt_polled = self.pending_nmi || (self.pending_irq && !self.i);
+        }
+    }
 }
 
 impl Default for NESCPU {
@@ -187,81 +461,84 @@ impl Default for NESCPU {
 }
 
 impl NESCPU {
-    fn execute_operation(&mut self, bus: &mut dyn Bus) {
-        let current_instruction = self.current_instruction.clone();
-        match current_instruction.instruction_type {
-            InstructionType::Jam => {
-                self.current_instruction.remaining_cycles = 0xff;
-            }
-            InstructionType::Reset => {
-                self.reset(bus);
-                self.current_instruction = self.fetch_next_instruction(bus);
-            }
-            InstructionType::Nmi => {
-                self.nmi(bus);
-                self.current_instruction = self.fetch_next_instruction(bus);
+    fn poll_for_interrupts_or_fetch_next_instruction<B: Bus>(&mut self, bus: &mut B) {
+        //We only poll for interrupts in non-interrupt routines (i.e. regular
+        //instructions); BRK shares the interrupt sequence, so the first
+        //instruction of its handler always runs
+        let polled = match self.current_instruction.instruction_type {
+            InstructionType::Instruction { access, .. } => access != Access::Brk,
+            InstructionType::Wait => true,
+            _ => false,
+        } && self.interrupt_polled;
+
+        self.trace = None;
+        self.current_instruction = if polled && self.pending_nmi {
+            CurrentInstruction {
+                cycle: 0,
+                remaining_cycles: self.nmic(),
+                instruction_type: InstructionType::Nmi,
             }
-            InstructionType::Irq => {
-                self.irq(bus);
-                self.current_instruction = self.fetch_next_instruction(bus);
+        } else if polled {
+            CurrentInstruction {
+                cycle: 0,
+                remaining_cycles: self.irqc(),
+                instruction_type: InstructionType::Irq,
             }
-            InstructionType::Instruction {
-                opcode,
-                addr_mode: addressing_mode,
-            } => {
-                let i_flag_before = self.i;
-
-                let exec_fn = NESCPU::LOOKUP_TABLE[opcode as usize].3;
-                exec_fn(self, &addressing_mode, bus);
-
-                let i_flag_after = self.i;
-
-                let polled_i_flag = match opcode {
-                    0x28 | 0x58 | 0x78 => i_flag_before,
-                    _ => i_flag_after,
-                };
-                //We only poll for interrupts in non-interrupt routines
-                //(i.e. regular instructions)
-                self.poll_for_interrupts_or_fetch_next_instruction(bus, polled_i_flag);
+        } else {
+            CurrentInstruction {
+                cycle: 0,
+                remaining_cycles: 0,
+                instruction_type: InstructionType::Fetch,
             }
+        };
+
+        if self.tracing && self.current_instruction.instruction_type == InstructionType::Fetch {
+            self.trace_next_instruction(bus);
         }
     }
 
-    fn poll_for_interrupts_or_fetch_next_instruction(&mut self, bus: &mut dyn Bus, i_flag: bool) {
-        if self.pending_nmi {
-            self.current_instruction = CurrentInstruction {
-                remaining_cycles: self.nmic(),
-                instruction_type: InstructionType::Nmi,
-            };
-        } else if self.pending_irq && !i_flag {
-            self.current_instruction = CurrentInstruction {
-                remaining_cycles: self.irqc(),
-                instruction_type: InstructionType::Irq,
-            };
-        } else {
-            self.current_instruction = self.fetch_next_instruction(bus);
+    //Halts until reset on the opcode fetched at pc, cycle cycles ago
+    fn jam(&mut self, opcode: u8, pc: u16, cycle: u8) {
+        self.current_instruction = CurrentInstruction {
+            cycle,
+            remaining_cycles: 0xff,
+            instruction_type: InstructionType::Jam,
+        };
+
+        let jam = JamEvent {
+            opcode,
+            pc,
+            cycle: self.elapsed_cycles.saturating_sub(cycle as u64),
+        };
+        self.jam = Some(jam);
+        if let Some(callback) = self.jam_callback.as_mut() {
+            callback(jam);
         }
     }
 
-    fn fetch_next_instruction(&mut self, bus: &mut dyn Bus) -> CurrentInstruction {
-        let opcode = self.fetch_byte(bus);
-        let (_, addr_mode_fn, cycles_fn, _) = NESCPU::LOOKUP_TABLE[opcode as usize];
-        let addr_mode = addr_mode_fn(self, bus);
-        let cycles = cycles_fn(self, &addr_mode);
-        if cycles == 0 {
-            //Jam
-            CurrentInstruction {
-                remaining_cycles: 0xff,
-                instruction_type: InstructionType::Jam,
-            }
-        } else {
-            CurrentInstruction {
-                remaining_cycles: cycles,
-                instruction_type: InstructionType::Instruction { opcode, addr_mode },
+    fn decode(&self, opcode: u8) -> Instruction {
+        NESCPU::lookup(self.variant, opcode)
+    }
+
+    fn lookup(variant: CpuVariant, opcode: u8) -> Instruction {
+        NESCPU::lookup_for_bus(variant, opcode)
+    }
+
+    //The instruction with an execute function taking the given bus type
+    fn lookup_for_bus<B: Bus + ?Sized>(variant: CpuVariant, opcode: u8) -> Instruction<B> {
+        match variant {
+            CpuVariant::Wdc65C02 => <NESCPU as LookupTable<B>>::LOOKUP_TABLE_65C02[opcode as usize],
+            CpuVariant::Ricoh2A03 | CpuVariant::Nmos6502 => {
+                <NESCPU as LookupTable<B>>::LOOKUP_TABLE[opcode as usize]
             }
         }
     }
 
+    //ADC and SBC use BCD arithmetic
+    fn decimal_mode(&self) -> bool {
+        self.d && self.variant != CpuVariant::Ricoh2A03
+    }
+
     fn get_status_byte(&self, brk: bool) -> u8 {
         (self.n as u8) << 7
             | (self.v as u8) << 6
@@ -273,31 +550,52 @@ impl NESCPU {
             | (self.c as u8)
     }
 
-    fn fetch_byte(&mut self, bus: &dyn Bus) -> u8 {
-        let data = bus.read(self.pc);
-        self.pc = self.pc.wrapping_add(1);
+    fn set_status_byte(&mut self, status: u8) {
+        self.n = (status & 0x80) > 0;
+        self.v = (status & 0x40) > 0;
+        self.d = (status & 0x8) > 0;
+        self.i = (status & 0x4) > 0;
+        self.z = (status & 0x2) > 0;
+        self.c = (status & 0x1) > 0;
+    }
 
-        data
+    //Magic constant ORed into A by XAA and LAX #imm
+    fn unstable_magic(&mut self) -> u8 {
+        match self.unstable_opcodes {
+            UnstableOpcodes::Fixed { magic } => magic,
+            UnstableOpcodes::Seeded(_) => *[0x00, 0xee, 0xef, 0xfe, 0xff]
+                .choose(&mut self.rng)
+                .unwrap(),
+        }
+    }
+
+    //Value ANDed into the data stored by SHA, SHX, SHY and SHS
+    fn unstable_high_byte(&mut self, high_byte: u8) -> u8 {
+        match self.unstable_opcodes {
+            UnstableOpcodes::Seeded(_) if self.rng.gen_ratio(1, 4) => 0xff,
+            _ => high_byte.wrapping_add(1),
+        }
     }
 
-    fn fetch_two_bytes_as_u16(&mut self, bus: &dyn Bus) -> u16 {
-        let low_byte: u16 = bus.read(self.pc.wrapping_add(0)) as u16;
-        let high_byte: u16 = bus.read(self.pc.wrapping_add(1)) as u16;
-        self.pc = self.pc.wrapping_add(2);
+    fn fetch_byte<B: Bus>(&mut self, bus: &B) -> u8 {
+        let data = bus.read(self.pc);
+        self.pc = self.pc.wrapping_add(1);
 
-        high_byte << 8 | low_byte
+        data
     }
 }
 
 #[cfg(test)]
 mod cpu_tests {
+    use std::{cell::RefCell, rc::Rc};
+
     use mockall::predicate::eq;
 
     use crate::{
         bus::MockBus,
         cpu::{
-            addr::{AddrModeResult, AddrModeType},
-            CurrentInstruction, InstructionType, CPU, NESCPU,
+            addr::AddrModeType, CpuState, CpuVariant, CurrentInstruction, InstructionType,
+            JamEvent, UnstableOpcodes, CPU, NESCPU,
         },
     };
 
@@ -323,6 +621,7 @@ mod cpu_tests {
 
         assert_eq!(
             CurrentInstruction {
+                cycle: 0,
                 remaining_cycles: 7,
                 instruction_type: InstructionType::Reset
             },
@@ -331,6 +630,17 @@ mod cpu_tests {
 
         assert_eq!(false, cpu.pending_nmi);
         assert_eq!(false, cpu.pending_irq);
+        assert_eq!(false, cpu.tracing);
+        assert_eq!(None, cpu.trace);
+    }
+
+    #[test]
+    fn test_cpu_variant() {
+        assert_eq!(CpuVariant::Ricoh2A03, NESCPU::new().get_variant());
+        assert_eq!(
+            CpuVariant::Wdc65C02,
+            NESCPU::with_variant(CpuVariant::Wdc65C02).get_variant()
+        );
     }
 
     #[test]
@@ -423,40 +733,164 @@ mod cpu_tests {
     }
 
     #[test]
-    fn test_fetch_next_two_bytes_as_u16() {
+    fn test_fixed_unstable_opcodes() {
         let mut cpu = NESCPU::new();
-        let mut bus = MockBus::new();
+        assert_eq!(0xee, cpu.unstable_magic());
+        assert_eq!(0x21, cpu.unstable_high_byte(0x20));
 
-        cpu.pc = 0xffff;
-        bus.expect_read()
-            .with(eq(0xffff))
-            .times(1)
-            .return_const(0x40);
-        bus.expect_read().with(eq(0x0)).times(1).return_const(0x20);
+        cpu.set_unstable_opcodes(UnstableOpcodes::Fixed { magic: 0xff });
+        for _ in 0..100 {
+            assert_eq!(0xff, cpu.unstable_magic());
+            assert_eq!(0x0, cpu.unstable_high_byte(0xff));
+        }
+    }
+
+    #[test]
+    fn test_seeded_unstable_opcodes_are_reproducible() {
+        let mut cpu_1 = NESCPU::new();
+        let mut cpu_2 = NESCPU::new();
+        cpu_1.set_unstable_opcodes(UnstableOpcodes::Seeded(42));
+        cpu_2.set_unstable_opcodes(UnstableOpcodes::Seeded(42));
+
+        let magics: Vec<u8> = (0..100).map(|_| cpu_1.unstable_magic()).collect();
+        let high_bytes: Vec<u8> = (0..100).map(|_| cpu_1.unstable_high_byte(0x20)).collect();
+
+        assert_eq!(
+            magics,
+            (0..100)
+                .map(|_| cpu_2.unstable_magic())
+                .collect::<Vec<u8>>()
+        );
+        assert_eq!(
+            high_bytes,
+            (0..100)
+                .map(|_| cpu_2.unstable_high_byte(0x20))
+                .collect::<Vec<u8>>()
+        );
 
-        let two_bytes = cpu.fetch_two_bytes_as_u16(&bus);
-        assert_eq!(0x2040, two_bytes);
-        assert_eq!(0x1, cpu.pc);
+        assert!(magics
+            .iter()
+            .all(|magic| [0x00, 0xee, 0xef, 0xfe, 0xff].contains(magic)));
+        assert!(high_bytes.iter().all(|high| [0x21, 0xff].contains(high)));
     }
 
     #[test]
-    fn test_cpu_reset() {
+    fn test_set_status_byte() {
+        let mut cpu = NESCPU::new();
+        cpu.set_status_byte(0b1100_1011);
+
+        assert_eq!(true, cpu.n);
+        assert_eq!(true, cpu.v);
+        assert_eq!(true, cpu.d);
+        assert_eq!(false, cpu.i);
+        assert_eq!(true, cpu.z);
+        assert_eq!(true, cpu.c);
+        assert_eq!(0b1110_1011, cpu.get_status_byte(false));
+    }
+
+    #[test]
+    fn test_get_state() {
+        let mut cpu = NESCPU::new();
+        cpu.pc = 0x1234;
+        cpu.sp = 0xfd;
+        cpu.a = 0x1;
+        cpu.x = 0x2;
+        cpu.y = 0x3;
+        cpu.c = true;
+        cpu.cpu_nmi();
+        cpu.elapsed_cycles = 7;
+
+        assert_eq!(
+            CpuState {
+                pc: 0x1234,
+                sp: 0xfd,
+                a: 0x1,
+                x: 0x2,
+                y: 0x3,
+                status: 0b0010_0101,
+                pending_nmi: true,
+                elapsed_cycles: 7
+            },
+            cpu.get_state()
+        );
+    }
+
+    #[test]
+    fn test_set_state() {
+        let mut cpu = NESCPU::new();
+        let state = CpuState {
+            pc: 0xc000,
+            sp: 0xfd,
+            a: 0x10,
+            x: 0x20,
+            y: 0x30,
+            status: 0b1111_1111,
+            pending_nmi: true,
+            elapsed_cycles: 100,
+        };
+
+        cpu.set_state(&state);
+
+        assert_eq!(0xc000, cpu.pc);
+        assert_eq!(0xfd, cpu.sp);
+        assert_eq!(0x10, cpu.a);
+        assert_eq!(0x20, cpu.x);
+        assert_eq!(0x30, cpu.y);
+        assert_eq!(true, cpu.n);
+        assert_eq!(true, cpu.v);
+        assert_eq!(true, cpu.d);
+        assert_eq!(true, cpu.i);
+        assert_eq!(true, cpu.z);
+        assert_eq!(true, cpu.c);
+        assert_eq!(true, cpu.pending_nmi);
+        assert_eq!(100, cpu.elapsed_cycles);
+
+        //The B flag is not a register
+        assert_eq!(
+            CpuState {
+                status: 0b1110_1111,
+                ..state
+            },
+            cpu.get_state()
+        );
+    }
+
+    #[test]
+    fn test_set_state_clears_trace() {
         let mut cpu = NESCPU::new();
         let mut bus = MockBus::new();
+        bus.expect_read().return_const(0xea);
+        bus.expect_peek().return_const(0xea);
+
+        cpu.set_tracing(true);
+        for _ in 0..7 {
+            cpu.clock(&mut bus);
+        }
+        assert!(cpu.trace.is_some());
+
+        cpu.set_state(&cpu.get_state());
+        assert_eq!(None, cpu.trace);
+    }
 
+    fn mock_reset_vector(bus: &mut MockBus) {
         bus.expect_read()
             .with(eq(NESCPU::RESET_VECTOR))
             .once()
             .return_const(0x40);
-
         bus.expect_read()
             .with(eq(NESCPU::RESET_VECTOR + 1))
             .once()
             .return_const(0x20);
+    }
 
-        bus.expect_read().with(eq(0x2040)).once().return_const(0x69);
+    #[test]
+    fn test_cpu_reset() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
 
-        bus.expect_read().with(eq(0x2041)).once().return_const(0xff);
+        mock_reset_vector(&mut bus);
+        bus.expect_read().times(5).return_const(0x0);
+        bus.expect_write().never();
 
         for _ in 0..7 {
             cpu.clock(&mut bus);
@@ -464,46 +898,75 @@ mod cpu_tests {
 
         assert_eq!(
             CurrentInstruction {
-                remaining_cycles: 2,
-                instruction_type: InstructionType::Instruction {
-                    opcode: 0x69,
-                    addr_mode: AddrModeResult {
-                        addr: None,
-                        data: Some(0xff),
-                        cycles: 0,
-                        mode: AddrModeType::Imm,
-                        bytes: 2,
-                        operands: "FF".to_owned(),
-                        repr: "#$FF".to_owned()
-                    }
-                }
+                cycle: 0,
+                remaining_cycles: 0,
+                instruction_type: InstructionType::Fetch
             },
             cpu.current_instruction
         );
 
-        assert_eq!(0x2042, cpu.pc);
+        assert_eq!(0x2040, cpu.pc);
+        assert_eq!(0xfd, cpu.sp);
+        assert_eq!(true, cpu.i);
         assert_eq!(0x0, cpu.a);
     }
 
     #[test]
-    fn test_cpu_system_reset() {
+    fn test_cpu_reset_with_tracing() {
         let mut cpu = NESCPU::new();
         let mut bus = MockBus::new();
 
+        mock_reset_vector(&mut bus);
+        bus.expect_read().with(eq(0x2040)).return_const(0x69);
+        bus.expect_read().with(eq(0x2041)).return_const(0xff);
         bus.expect_read().return_const(0x0);
+        //The trace looks ahead without side effects
+        bus.expect_peek().with(eq(0x2040)).return_const(0x69);
+        bus.expect_peek().with(eq(0x2041)).return_const(0xff);
+        bus.expect_peek().return_const(0x0);
+
+        cpu.set_tracing(true);
+        for _ in 0..7 {
+            cpu.clock(&mut bus);
+        }
+
+        assert_eq!(2, cpu.cycles_remaining());
+        assert_eq!(
+            AddrModeType::Imm,
+            cpu.trace.as_ref().unwrap().addr_mode.mode
+        );
+        assert_eq!(
+            "2040  69 FF     ADC #$FF                        A:00 X:00 Y:00 P:24 SP:FD CYC:7",
+            cpu.to_string()
+        );
+
+        cpu.clock(&mut bus);
+        assert_eq!(1, cpu.cycles_remaining());
+        assert_eq!(0x2041, cpu.pc);
+
+        cpu.clock(&mut bus);
+        assert_eq!(0x2042, cpu.pc);
+        assert_eq!(0xff, cpu.a);
+
+        cpu.set_tracing(false);
+        assert_eq!(None, cpu.trace);
+    }
+
+    #[test]
+    fn test_cpu_system_reset() {
+        let mut cpu = NESCPU::new();
 
         cpu.current_instruction = CurrentInstruction {
+            cycle: 1,
             remaining_cycles: 1,
-            instruction_type: InstructionType::Instruction {
-                opcode: 0x0,
-                addr_mode: cpu._imp(),
-            },
+            instruction_type: InstructionType::Fetch,
         };
 
         cpu.cpu_reset();
 
         assert_eq!(
             CurrentInstruction {
+                cycle: 0,
                 remaining_cycles: 7,
                 instruction_type: InstructionType::Reset
             },
@@ -516,12 +979,7 @@ mod cpu_tests {
         let mut cpu = NESCPU::new();
         let mut bus = MockBus::new();
 
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR))
-            .return_const(0x40);
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR + 1))
-            .return_const(0x20);
+        mock_reset_vector(&mut bus);
         bus.expect_read().with(eq(0x2040)).return_const(0x69);
         bus.expect_read().with(eq(0x2041)).return_const(0x69);
         bus.expect_read().return_const(0x0);
@@ -536,24 +994,20 @@ mod cpu_tests {
             cpu.clock(&mut bus);
         }
 
+        //The first instruction after reset always runs
         assert_eq!(
-            CurrentInstruction {
-                remaining_cycles: 2,
-                instruction_type: InstructionType::Instruction {
-                    opcode: 0x69,
-                    addr_mode: cpu._imm(0x69)
-                }
-            },
-            cpu.current_instruction
+            InstructionType::Fetch,
+            cpu.current_instruction.instruction_type
         );
-        assert_eq!(0x2042, cpu.pc);
 
         for _ in 0..2 {
             cpu.clock(&mut bus);
         }
 
+        assert_eq!(0x2042, cpu.pc);
         assert_eq!(
             CurrentInstruction {
+                cycle: 0,
                 remaining_cycles: 7,
                 instruction_type: InstructionType::Nmi
             },
@@ -562,19 +1016,36 @@ mod cpu_tests {
     }
 
     #[test]
-    fn test_irq_request_triggered() {
+    fn test_nmi_on_last_cycle_waits_for_next_instruction() {
         let mut cpu = NESCPU::new();
         let mut bus = MockBus::new();
 
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR))
-            .return_const(0x40);
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR + 1))
-            .return_const(0x20);
+        mock_reset_vector(&mut bus);
         bus.expect_read().with(eq(0x2040)).return_const(0x69);
         bus.expect_read().with(eq(0x2041)).return_const(0x69);
+        bus.expect_read().return_const(0x0);
 
+        for _ in 0..8 {
+            cpu.clock(&mut bus);
+        }
+
+        cpu.cpu_nmi();
+        cpu.clock(&mut bus);
+
+        assert_eq!(
+            InstructionType::Fetch,
+            cpu.current_instruction.instruction_type
+        );
+    }
+
+    #[test]
+    fn test_irq_request_triggered() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+
+        mock_reset_vector(&mut bus);
+        bus.expect_read().with(eq(0x2040)).return_const(0x69);
+        bus.expect_read().with(eq(0x2041)).return_const(0x69);
         bus.expect_read().return_const(0x0);
 
         bus.expect_write().return_const(());
@@ -591,6 +1062,7 @@ mod cpu_tests {
 
         assert_eq!(
             CurrentInstruction {
+                cycle: 0,
                 remaining_cycles: 7,
                 instruction_type: InstructionType::Irq
             },
@@ -603,18 +1075,11 @@ mod cpu_tests {
         let mut cpu = NESCPU::new();
         let mut bus = MockBus::new();
 
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR))
-            .return_const(0x40);
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR + 1))
-            .return_const(0x20);
+        mock_reset_vector(&mut bus);
         bus.expect_read().with(eq(0x2040)).return_const(0x69);
         bus.expect_read().with(eq(0x2041)).return_const(0x69);
         bus.expect_read().return_const(0x0);
 
-        bus.expect_write().return_const(());
-
         for _ in 0..7 {
             cpu.clock(&mut bus);
         }
@@ -627,14 +1092,8 @@ mod cpu_tests {
         }
 
         assert_eq!(
-            CurrentInstruction {
-                remaining_cycles: 7,
-                instruction_type: InstructionType::Instruction {
-                    opcode: 0x0,
-                    addr_mode: cpu._imm(0x0)
-                }
-            },
-            cpu.current_instruction
+            InstructionType::Fetch,
+            cpu.current_instruction.instruction_type
         );
     }
 
@@ -643,17 +1102,9 @@ mod cpu_tests {
         let mut cpu = NESCPU::new();
         let mut bus = MockBus::new();
 
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR))
-            .once()
-            .return_const(0x40);
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR + 1))
-            .once()
-            .return_const(0x20);
-        bus.expect_read().with(eq(0x2040)).once().return_const(0x58); //CLI
-        bus.expect_read().with(eq(0x2041)).once().return_const(0x58);
-
+        mock_reset_vector(&mut bus);
+        bus.expect_read().with(eq(0x2040)).return_const(0x58); //CLI
+        bus.expect_read().with(eq(0x2041)).return_const(0x58);
         bus.expect_read().return_const(0x0);
 
         cpu.cpu_irq(true);
@@ -662,31 +1113,15 @@ mod cpu_tests {
             cpu.clock(&mut bus);
         }
 
-        assert_eq!(
-            CurrentInstruction {
-                remaining_cycles: 2,
-                instruction_type: InstructionType::Instruction {
-                    opcode: 0x58,
-                    addr_mode: cpu._imp()
-                }
-            },
-            cpu.current_instruction
-        );
-
         for _ in 0..2 {
             cpu.clock(&mut bus);
         }
 
         //Interrupt delayed until end of next instruction
+        assert_eq!(false, cpu.i);
         assert_eq!(
-            CurrentInstruction {
-                remaining_cycles: 2,
-                instruction_type: InstructionType::Instruction {
-                    opcode: 0x58,
-                    addr_mode: cpu._imp()
-                }
-            },
-            cpu.current_instruction
+            InstructionType::Fetch,
+            cpu.current_instruction.instruction_type
         );
 
         for _ in 0..2 {
@@ -694,11 +1129,8 @@ mod cpu_tests {
         }
 
         assert_eq!(
-            CurrentInstruction {
-                remaining_cycles: 7,
-                instruction_type: InstructionType::Irq
-            },
-            cpu.current_instruction
+            InstructionType::Irq,
+            cpu.current_instruction.instruction_type
         );
     }
 
@@ -707,16 +1139,8 @@ mod cpu_tests {
         let mut cpu = NESCPU::new();
         let mut bus = MockBus::new();
 
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR))
-            .once()
-            .return_const(0x40);
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR + 1))
-            .once()
-            .return_const(0x20);
-        bus.expect_read().with(eq(0x2040)).once().return_const(0x78); //SEI
-
+        mock_reset_vector(&mut bus);
+        bus.expect_read().with(eq(0x2040)).return_const(0x78); //SEI
         bus.expect_read().return_const(0x0);
 
         cpu.cpu_irq(true);
@@ -727,28 +1151,15 @@ mod cpu_tests {
 
         cpu.i = false;
 
-        assert_eq!(
-            CurrentInstruction {
-                remaining_cycles: 2,
-                instruction_type: InstructionType::Instruction {
-                    opcode: 0x78,
-                    addr_mode: cpu._imp()
-                }
-            },
-            cpu.current_instruction
-        );
-
         for _ in 0..2 {
             cpu.clock(&mut bus);
         }
 
         //Interrupt triggered immediately (despite I flag set)
+        assert_eq!(true, cpu.i);
         assert_eq!(
-            CurrentInstruction {
-                remaining_cycles: 7,
-                instruction_type: InstructionType::Irq
-            },
-            cpu.current_instruction
+            InstructionType::Irq,
+            cpu.current_instruction.instruction_type
         );
     }
 
@@ -757,21 +1168,9 @@ mod cpu_tests {
         let mut cpu = NESCPU::new();
         let mut bus = MockBus::new();
 
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR))
-            .once()
-            .return_const(0x40);
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR + 1))
-            .once()
-            .return_const(0x20);
-
-        bus.expect_read().with(eq(0x2040)).once().return_const(0x28); //PLP
-        bus.expect_read()
-            .with(eq(0x1fe))
-            .once()
-            .return_const(0b1111_1111);
-
+        mock_reset_vector(&mut bus);
+        bus.expect_read().with(eq(0x2040)).return_const(0x28); //PLP
+        bus.expect_read().with(eq(0x1fe)).return_const(0b1111_1111);
         bus.expect_read().return_const(0x0);
 
         cpu.cpu_irq(true);
@@ -782,28 +1181,15 @@ mod cpu_tests {
 
         cpu.i = false;
 
-        assert_eq!(
-            CurrentInstruction {
-                remaining_cycles: 4,
-                instruction_type: InstructionType::Instruction {
-                    opcode: 0x28,
-                    addr_mode: cpu._imp()
-                }
-            },
-            cpu.current_instruction
-        );
-
         for _ in 0..4 {
             cpu.clock(&mut bus);
         }
 
         //Interrupt triggered immediately (despite I flag set)
+        assert_eq!(true, cpu.i);
         assert_eq!(
-            CurrentInstruction {
-                remaining_cycles: 7,
-                instruction_type: InstructionType::Irq
-            },
-            cpu.current_instruction
+            InstructionType::Irq,
+            cpu.current_instruction.instruction_type
         );
     }
 
@@ -812,24 +1198,17 @@ mod cpu_tests {
         let mut cpu = NESCPU::new();
         let mut bus = MockBus::new();
 
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR))
-            .once()
-            .return_const(0x40);
-        bus.expect_read()
-            .with(eq(NESCPU::RESET_VECTOR + 1))
-            .once()
-            .return_const(0x20);
-
+        mock_reset_vector(&mut bus);
         bus.expect_read().with(eq(0x2040)).once().return_const(0x2);
         bus.expect_read().return_const(0x0);
 
-        for _ in 0..7 {
+        for _ in 0..8 {
             cpu.clock(&mut bus);
         }
 
         assert_eq!(
             CurrentInstruction {
+                cycle: 1,
                 remaining_cycles: 0xff,
                 instruction_type: InstructionType::Jam
             },
@@ -866,15 +1245,56 @@ mod cpu_tests {
             InstructionType::Jam,
             cpu.current_instruction.instruction_type
         );
+        assert_eq!(
+            Some(JamEvent {
+                opcode: 0x2,
+                pc: 0x2040,
+                cycle: 7
+            }),
+            cpu.get_jam()
+        );
 
         cpu.cpu_reset();
+        assert_eq!(None, cpu.get_jam());
 
         assert_eq!(
             CurrentInstruction {
+                cycle: 0,
                 remaining_cycles: 7,
                 instruction_type: InstructionType::Reset
             },
             cpu.current_instruction
         );
     }
+
+    #[test]
+    fn test_jam_callback() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+
+        mock_reset_vector(&mut bus);
+        bus.expect_read().with(eq(0x2040)).return_const(0x2);
+        bus.expect_read().return_const(0x0);
+
+        let jams = Rc::new(RefCell::new(vec![]));
+        let jams_clone = Rc::clone(&jams);
+        cpu.set_jam_callback(Some(Box::new(move |jam| jams_clone.borrow_mut().push(jam))));
+
+        for _ in 0..100 {
+            cpu.clock(&mut bus);
+        }
+
+        assert_eq!(
+            vec![JamEvent {
+                opcode: 0x2,
+                pc: 0x2040,
+                cycle: 7
+            }],
+            *jams.borrow()
+        );
+        assert_eq!(
+            "CPU jammed on opcode $02 at $2040 (cycle 7)",
+            cpu.get_jam().unwrap().to_string()
+        );
+    }
 }
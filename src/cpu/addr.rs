@@ -5,10 +5,13 @@ mod acc;
 mod imm;
 mod imp;
 mod ind;
+mod indabsx; //65C02
 mod indx;
 mod indy;
+mod indzp; //65C02
 mod rel;
 mod zp;
+mod zprel; //65C02
 mod zpx;
 mod zpy;
 
@@ -27,15 +30,129 @@ pub(in crate::cpu) enum AddrModeType {
     Indy,
     Rel,
     Imp,
+    Indzp,
+    Indabsx,
+    Zprel,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+//Only numeric decode data is kept here; the text shown in traces and
+//disassembly is formatted on demand by operands() and repr()
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub(in crate::cpu) struct AddrModeResult {
     pub addr: Option<u16>,
     pub data: Option<u8>,
     pub cycles: u8,
     pub mode: AddrModeType,
     pub bytes: u8,
-    pub operands: String,
-    pub repr: String,
+    //Operand bytes as fetched, little endian
+    pub operand: u16,
+}
+
+impl AddrModeResult {
+    //Stand-in for a mode whose operands have not been fetched yet
+    //(no page crossing assumed)
+    pub fn unresolved(mode: AddrModeType) -> AddrModeResult {
+        let (cycles, bytes) = match mode {
+            AddrModeType::Acc | AddrModeType::Imp => (0, 1),
+            AddrModeType::Imm | AddrModeType::Rel => (0, 2),
+            AddrModeType::Zp => (1, 2),
+            AddrModeType::Zpx | AddrModeType::Zpy => (2, 2),
+            AddrModeType::Indy | AddrModeType::Indzp => (3, 2),
+            AddrModeType::Indx => (4, 2),
+            AddrModeType::Abs | AddrModeType::Absx | AddrModeType::Absy => (2, 3),
+            AddrModeType::Ind => (4, 3),
+            AddrModeType::Indabsx => (5, 3),
+            AddrModeType::Zprel => (0, 3),
+        };
+
+        AddrModeResult {
+            addr: None,
+            data: None,
+            cycles,
+            mode,
+            bytes,
+            operand: 0x0,
+        }
+    }
+
+    //Operand bytes in hex, e.g. "34 12"
+    pub fn operands(&self) -> String {
+        match self.bytes {
+            2 => format!("{:02X}", self.operand as u8),
+            3 => format!("{:02X} {:02X}", self.operand as u8, self.operand >> 8),
+            _ => String::new(),
+        }
+    }
+
+    //Operand in assembler syntax, e.g. "($12),Y"; branch targets are
+    //shown as the resolved address
+    pub fn repr(&self) -> String {
+        let operand = self.operand;
+        let zp = operand as u8;
+        match self.mode {
+            AddrModeType::Acc => "A".to_owned(),
+            AddrModeType::Imp => String::new(),
+            AddrModeType::Imm => format!("#${:02X}", zp),
+            AddrModeType::Zp => format!("${:02X}", zp),
+            AddrModeType::Zpx => format!("${:02X},X", zp),
+            AddrModeType::Zpy => format!("${:02X},Y", zp),
+            AddrModeType::Abs => format!("${:04X}", operand),
+            AddrModeType::Absx => format!("${:04X},X", operand),
+            AddrModeType::Absy => format!("${:04X},Y", operand),
+            AddrModeType::Ind => format!("(${:04X})", operand),
+            AddrModeType::Indx => format!("(${:02X},X)", zp),
+            AddrModeType::Indy => format!("(${:02X}),Y", zp),
+            AddrModeType::Indzp => format!("(${:02X})", zp),
+            AddrModeType::Indabsx => format!("(${:04X},X)", operand),
+            AddrModeType::Rel => format!("${:04X}", self.addr.unwrap_or(0x0)),
+            AddrModeType::Zprel => format!("${:02X},${:04X}", zp, self.addr.unwrap_or(0x0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod addr_tests {
+    use crate::bus::MockBus;
+    use crate::cpu::NESCPU;
+
+    use super::*;
+
+    fn format(mode: AddrModeType, operand: u16) -> (String, String) {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        bus.expect_read().return_const(0x0);
+        cpu.pc = 0x8003;
+
+        let result = cpu.addr_mode(mode, operand, &bus);
+        (result.operands(), result.repr())
+    }
+
+    #[test]
+    fn test_operands_and_repr_are_formatted_per_mode() {
+        for (mode, operand, expected) in [
+            (AddrModeType::Acc, 0x0, ("", "A")),
+            (AddrModeType::Imp, 0x0, ("", "")),
+            (AddrModeType::Imm, 0x88, ("88", "#$88")),
+            (AddrModeType::Zp, 0x10, ("10", "$10")),
+            (AddrModeType::Zpx, 0xff, ("FF", "$FF,X")),
+            (AddrModeType::Zpy, 0xff, ("FF", "$FF,Y")),
+            (AddrModeType::Abs, 0x1234, ("34 12", "$1234")),
+            (AddrModeType::Absx, 0xffff, ("FF FF", "$FFFF,X")),
+            (AddrModeType::Absy, 0x0, ("00 00", "$0000,Y")),
+            (AddrModeType::Ind, 0x80ff, ("FF 80", "($80FF)")),
+            (AddrModeType::Indx, 0xff, ("FF", "($FF,X)")),
+            (AddrModeType::Indy, 0xff, ("FF", "($FF),Y")),
+            (AddrModeType::Indzp, 0x10, ("10", "($10)")),
+            (AddrModeType::Indabsx, 0x80fd, ("FD 80", "($80FD,X)")),
+            (AddrModeType::Rel, 0xff, ("FF", "$8002")),
+            (AddrModeType::Zprel, 0x1010, ("10 10", "$10,$8013")),
+        ] {
+            assert_eq!(
+                (expected.0.to_owned(), expected.1.to_owned()),
+                format(mode, operand),
+                "{:?}",
+                mode
+            );
+        }
+    }
 }
@@ -19,12 +19,7 @@ use crate::{bus::Bus, cpu::NESCPU};
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn absx(&mut self, bus: &dyn Bus) -> AddrModeResult {
-        let addr = self.fetch_two_bytes_as_u16(bus);
-        self._absx(addr, bus)
-    }
-
-    pub(in crate::cpu) fn _absx(&self, addr: u16, _bus: &dyn Bus) -> AddrModeResult {
+    pub(in crate::cpu) fn _absx<B: Bus + ?Sized>(&self, addr: u16, _bus: &B) -> AddrModeResult {
         let page_before: u8 = (addr >> 8) as u8;
         let resolved_addr = addr.wrapping_add(self.x as u16);
         let page_after: u8 = (resolved_addr >> 8) as u8;
@@ -35,28 +30,23 @@ impl NESCPU {
             mode: AddrModeType::Absx,
             addr: Some(resolved_addr),
             bytes: 3,
-            operands: format!("{:02X} {:02X}", (addr & 0xff) as u8, (addr >> 8) as u8),
-            repr: format!("${:04X},X", addr),
+            operand: addr,
         }
     }
 }
 
 #[cfg(test)]
 mod absx_tests {
-    use mockall::predicate::eq;
-
     use super::*;
     use crate::{bus::MockBus, cpu::addr::AddrModeResult};
 
     #[test]
     fn test_absx_addressing_mode_no_page_cross() {
         let mut cpu = NESCPU::new();
-        let mut mock_bus = MockBus::new();
+        let mock_bus = MockBus::new();
 
         cpu.x = 0x2;
 
-        mock_bus.expect_read().with(eq(0x2)).return_const(0x88);
-
         let result = cpu._absx(0x0, &mock_bus);
         assert_eq!(
             AddrModeResult {
@@ -65,8 +55,7 @@ mod absx_tests {
                 addr: Some(0x2),
                 mode: AddrModeType::Absx,
                 bytes: 3,
-                operands: "00 00".to_owned(),
-                repr: "$0000,X".to_owned()
+                operand: 0x0
             },
             result
         );
@@ -75,12 +64,10 @@ mod absx_tests {
     #[test]
     fn test_absx_addressing_mode_with_page_cross() {
         let mut cpu = NESCPU::new();
-        let mut mock_bus = MockBus::new();
+        let mock_bus = MockBus::new();
 
         cpu.x = 0x2;
 
-        mock_bus.expect_read().with(eq(0x1)).return_const(0x88);
-
         let result = cpu._absx(0xffff, &mock_bus);
         assert_eq!(
             AddrModeResult {
@@ -89,8 +76,7 @@ mod absx_tests {
                 mode: AddrModeType::Absx,
                 addr: Some(0x1),
                 bytes: 3,
-                operands: "FF FF".to_owned(),
-                repr: "$FFFF,X".to_owned()
+                operand: 0xffff
             },
             result
         );
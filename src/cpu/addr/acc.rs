@@ -7,15 +7,11 @@
     Bytes: 1
 */
 
-use crate::{bus::Bus, cpu::NESCPU};
+use crate::cpu::NESCPU;
 
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn acc(&mut self, _bus: &dyn Bus) -> AddrModeResult {
-        self._acc()
-    }
-
     pub(in crate::cpu) fn _acc(&self) -> AddrModeResult {
         AddrModeResult {
             data: Some(self.a),
@@ -23,8 +19,7 @@ impl NESCPU {
             mode: AddrModeType::Acc,
             addr: None,
             bytes: 1,
-            operands: "".to_owned(),
-            repr: "A".to_owned(),
+            operand: 0x0,
         }
     }
 }
@@ -47,8 +42,7 @@ mod acc_tests {
                 mode: AddrModeType::Acc,
                 addr: None,
                 bytes: 1,
-                operands: "".to_owned(),
-                repr: "A".to_owned()
+                operand: 0x0
             },
             cpu._acc()
         );
@@ -8,16 +8,11 @@
     Bytes: 2
 */
 
-use crate::{bus::Bus, cpu::NESCPU};
+use crate::cpu::NESCPU;
 
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn imm(&mut self, bus: &dyn Bus) -> AddrModeResult {
-        let data = self.fetch_byte(bus);
-        self._imm(data)
-    }
-
     pub(in crate::cpu) fn _imm(&self, imm: u8) -> AddrModeResult {
         AddrModeResult {
             data: Some(imm),
@@ -25,8 +20,7 @@ impl NESCPU {
             mode: AddrModeType::Imm,
             addr: None,
             bytes: 2,
-            operands: format!("{:02X}", imm),
-            repr: format!("#${:02X}", imm),
+            operand: imm as u16,
         }
     }
 }
@@ -49,8 +43,7 @@ mod imm_tests {
                 mode: AddrModeType::Imm,
                 addr: None,
                 bytes: 2,
-                operands: "88".to_owned(),
-                repr: "#$88".to_owned()
+                operand: 0x88
             },
             imm
         );
@@ -8,15 +8,11 @@
     Bytes: 1
 */
 
-use crate::{bus::Bus, cpu::NESCPU};
+use crate::cpu::NESCPU;
 
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn imp(&mut self, _bus: &dyn Bus) -> AddrModeResult {
-        self._imp()
-    }
-
     pub(in crate::cpu) fn _imp(&self) -> AddrModeResult {
         AddrModeResult {
             data: None,
@@ -24,8 +20,7 @@ impl NESCPU {
             mode: AddrModeType::Imp,
             addr: None,
             bytes: 1,
-            operands: "".to_owned(),
-            repr: "".to_owned(),
+            operand: 0x0,
         }
     }
 }
@@ -46,8 +41,7 @@ mod imp_tests {
                 mode: AddrModeType::Imp,
                 addr: None,
                 bytes: 1,
-                operands: "".to_owned(),
-                repr: "".to_owned()
+                operand: 0x0
             },
             imp
         );
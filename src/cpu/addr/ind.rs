@@ -13,24 +13,26 @@
     The indirect jump instruction does not increment the page
     address when the indirect pointer crosses a page boundary.
     JMP ($xxFF) will fetch the address from $xxFF and $xx00.
+    The 65C02 fixes this and fetches from $xxFF and $xx00 + $100.
 
     Bytes: 3
 */
 
-use crate::{bus::Bus, cpu::NESCPU};
+use crate::{
+    bus::Bus,
+    cpu::{CpuVariant, NESCPU},
+};
 
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn ind(&mut self, bus: &dyn Bus) -> AddrModeResult {
-        let addr = self.fetch_two_bytes_as_u16(bus);
-        self._ind(addr, bus)
-    }
-
-    pub(in crate::cpu) fn _ind(&self, addr: u16, bus: &dyn Bus) -> AddrModeResult {
+    pub(in crate::cpu) fn _ind<B: Bus + ?Sized>(&self, addr: u16, bus: &B) -> AddrModeResult {
         let low_byte = bus.read(addr) as u16;
-        let high_byte =
-            bus.read((addr & 0xff00) + ((addr & 0xff) as u8).wrapping_add(1) as u16) as u16;
+        let high_byte_addr = match self.variant {
+            CpuVariant::Wdc65C02 => addr.wrapping_add(1),
+            _ => (addr & 0xff00) + ((addr & 0xff) as u8).wrapping_add(1) as u16,
+        };
+        let high_byte = bus.read(high_byte_addr) as u16;
 
         let resolved_addr = high_byte << 8 | low_byte;
 
@@ -40,8 +42,7 @@ impl NESCPU {
             mode: AddrModeType::Ind,
             addr: Some(resolved_addr),
             bytes: 3,
-            operands: format!("{:02X} {:02X}", (addr & 0xff) as u8, (addr >> 8) as u8),
-            repr: format!("(${:04X})", addr),
+            operand: addr,
         }
     }
 }
@@ -77,8 +78,7 @@ mod ind_tests {
                 mode: AddrModeType::Ind,
                 addr: Some(0x2040),
                 bytes: 3,
-                operands: "00 00".to_owned(),
-                repr: "($0000)".to_owned()
+                operand: 0x0
             },
             ind
         );
@@ -107,10 +107,27 @@ mod ind_tests {
                 mode: AddrModeType::Ind,
                 addr: Some(0x2040),
                 bytes: 3,
-                operands: "FF 80".to_owned(),
-                repr: "($80FF)".to_owned()
+                operand: 0x80ff
             },
             ind
         );
     }
+
+    #[test]
+    fn test_ind_addressing_mode_65c02_no_hardware_bug() {
+        let cpu = NESCPU::with_variant(CpuVariant::Wdc65C02);
+        let mut bus = MockBus::new();
+
+        bus.expect_read()
+            .with(eq(0x80ff))
+            .times(1)
+            .return_const(0x40);
+
+        bus.expect_read()
+            .with(eq(0x8100))
+            .times(1)
+            .return_const(0x20);
+
+        assert_eq!(Some(0x2040), cpu._ind(0x80ff, &bus).addr);
+    }
 }
@@ -0,0 +1,69 @@
+/*
+    Absolute Indexed Indirect (65C02)
+
+    Used only by JMP. The contents of the X index register are
+    added to the address contained in the second and third bytes
+    of the instruction. The contents of the resulting memory
+    location is the low order byte of the effective address and
+    the next memory location contains the high order byte.
+
+    Bytes: 3
+*/
+
+use crate::{bus::Bus, cpu::NESCPU};
+
+use super::{AddrModeResult, AddrModeType};
+
+impl NESCPU {
+    pub(in crate::cpu) fn _indabsx<B: Bus + ?Sized>(&self, addr: u16, bus: &B) -> AddrModeResult {
+        let ptr = addr.wrapping_add(self.x as u16);
+        let low_byte = bus.read(ptr) as u16;
+        let high_byte = bus.read(ptr.wrapping_add(1)) as u16;
+
+        AddrModeResult {
+            data: None,
+            cycles: 5,
+            mode: AddrModeType::Indabsx,
+            addr: Some(high_byte << 8 | low_byte),
+            bytes: 3,
+            operand: addr,
+        }
+    }
+}
+
+#[cfg(test)]
+mod indabsx_tests {
+    use mockall::predicate::eq;
+
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_indabsx_addressing_mode() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.x = 0x2;
+
+        bus.expect_read()
+            .with(eq(0x80ff))
+            .times(1)
+            .return_const(0x40);
+        bus.expect_read()
+            .with(eq(0x8100))
+            .times(1)
+            .return_const(0x20);
+
+        assert_eq!(
+            AddrModeResult {
+                data: None,
+                cycles: 5,
+                mode: AddrModeType::Indabsx,
+                addr: Some(0x2040),
+                bytes: 3,
+                operand: 0x80fd
+            },
+            cpu._indabsx(0x80fd, &bus)
+        );
+    }
+}
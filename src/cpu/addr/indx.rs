@@ -19,12 +19,7 @@ use crate::{bus::Bus, cpu::NESCPU};
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn indx(&mut self, bus: &dyn Bus) -> AddrModeResult {
-        let addr = self.fetch_byte(bus);
-        self._indx(addr, bus)
-    }
-
-    pub(in crate::cpu) fn _indx(&self, addr: u8, bus: &dyn Bus) -> AddrModeResult {
+    pub(in crate::cpu) fn _indx<B: Bus + ?Sized>(&self, addr: u8, bus: &B) -> AddrModeResult {
         let low_byte_addr = addr.wrapping_add(self.x);
         let high_byte_addr = low_byte_addr.wrapping_add(1);
 
@@ -37,8 +32,7 @@ impl NESCPU {
             mode: AddrModeType::Indx,
             addr: Some(resolved_addr),
             bytes: 2,
-            operands: format!("{:02X}", addr),
-            repr: format!("(${:02X},X)", addr),
+            operand: addr as u16,
         }
     }
 }
@@ -58,8 +52,6 @@ mod indx_tests {
         mock_bus.expect_read().with(eq(0x1)).return_const(0x77);
         mock_bus.expect_read().with(eq(0x2)).return_const(0x88);
 
-        mock_bus.expect_read().with(eq(0x8877)).return_const(0xaa);
-
         cpu.x = 0x2;
         let result = cpu._indx(0xff, &mock_bus);
         assert_eq!(
@@ -69,8 +61,7 @@ mod indx_tests {
                 mode: AddrModeType::Indx,
                 addr: Some(0x8877),
                 bytes: 2,
-                operands: "FF".to_owned(),
-                repr: "($FF,X)".to_owned()
+                operand: 0xff
             },
             result
         );
@@ -17,12 +17,7 @@ use crate::{bus::Bus, cpu::NESCPU};
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn indy(&mut self, bus: &dyn Bus) -> AddrModeResult {
-        let addr = self.fetch_byte(bus);
-        self._indy(addr, bus)
-    }
-
-    pub(in crate::cpu) fn _indy(&self, addr: u8, bus: &dyn Bus) -> AddrModeResult {
+    pub(in crate::cpu) fn _indy<B: Bus + ?Sized>(&self, addr: u8, bus: &B) -> AddrModeResult {
         let low_byte_addr = addr;
         let high_byte_addr = low_byte_addr.wrapping_add(1);
 
@@ -39,8 +34,7 @@ impl NESCPU {
             mode: AddrModeType::Indy,
             addr: Some(resolved_addr),
             bytes: 2,
-            operands: format!("{:02X}", addr),
-            repr: format!("(${:02X}),Y", addr),
+            operand: addr as u16,
         }
     }
 }
@@ -61,7 +55,6 @@ mod indy_tests {
         mock_bus.expect_read().with(eq(0x0)).return_const(0x88);
 
         cpu.y = 0x2;
-        mock_bus.expect_read().with(eq(0x8879)).return_const(0xbb);
 
         let result = cpu._indy(0xff, &mock_bus);
         assert_eq!(
@@ -71,8 +64,7 @@ mod indy_tests {
                 mode: AddrModeType::Indy,
                 addr: Some(0x8879),
                 bytes: 2,
-                operands: "FF".to_owned(),
-                repr: "($FF),Y".to_owned()
+                operand: 0xff
             },
             result
         );
@@ -87,7 +79,6 @@ mod indy_tests {
         mock_bus.expect_read().with(eq(0x0)).return_const(0x88);
 
         cpu.y = 0xff;
-        mock_bus.expect_read().with(eq(0x8976)).return_const(0xcc);
 
         let result = cpu._indy(0xff, &mock_bus);
         assert_eq!(
@@ -97,8 +88,7 @@ mod indy_tests {
                 mode: AddrModeType::Indy,
                 addr: Some(0x8976),
                 bytes: 2,
-                operands: "FF".to_owned(),
-                repr: "($FF),Y".to_owned()
+                operand: 0xff
             },
             result
         );
@@ -0,0 +1,72 @@
+/*
+    Zero Page Indirect (65C02)
+
+    The second byte of the instruction points to a memory location
+    in page zero. The contents of this memory location is the low
+    order byte of the effective address, and the next page zero
+    memory location contains the high order byte. It behaves like
+    (indirect),Y with Y fixed to zero.
+
+    Bytes: 2
+*/
+
+use crate::{bus::Bus, cpu::NESCPU};
+
+use super::{AddrModeResult, AddrModeType};
+
+impl NESCPU {
+    pub(in crate::cpu) fn _indzp<B: Bus + ?Sized>(&self, addr: u8, bus: &B) -> AddrModeResult {
+        let low_byte = bus.read(addr as u16) as u16;
+        let high_byte = bus.read(addr.wrapping_add(1) as u16) as u16;
+
+        AddrModeResult {
+            data: None,
+            cycles: 3,
+            mode: AddrModeType::Indzp,
+            addr: Some(high_byte << 8 | low_byte),
+            bytes: 2,
+            operand: addr as u16,
+        }
+    }
+}
+
+#[cfg(test)]
+mod indzp_tests {
+    use mockall::predicate::eq;
+
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_indzp_addressing_mode() {
+        let cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+
+        bus.expect_read().with(eq(0x10)).times(1).return_const(0x40);
+        bus.expect_read().with(eq(0x11)).times(1).return_const(0x20);
+
+        assert_eq!(
+            AddrModeResult {
+                data: None,
+                cycles: 3,
+                mode: AddrModeType::Indzp,
+                addr: Some(0x2040),
+                bytes: 2,
+                operand: 0x10
+            },
+            cpu._indzp(0x10, &bus)
+        );
+    }
+
+    #[test]
+    fn test_indzp_addressing_mode_wraps_in_zero_page() {
+        let cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+
+        bus.expect_read().with(eq(0xff)).times(1).return_const(0x40);
+        bus.expect_read().with(eq(0x00)).times(1).return_const(0x20);
+
+        assert_eq!(Some(0x2040), cpu._indzp(0xff, &bus).addr);
+    }
+}
@@ -13,16 +13,11 @@
     Bytes: 2
 */
 
-use crate::{bus::Bus, cpu::NESCPU};
+use crate::cpu::NESCPU;
 
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn rel(&mut self, bus: &dyn Bus) -> AddrModeResult {
-        let offset = self.fetch_byte(bus);
-        self._rel(offset)
-    }
-
     pub(in crate::cpu) fn _rel(&self, offset: u8) -> AddrModeResult {
         let page_before = (self.pc >> 8) as u8;
         let resolved_offset = if (offset & 0x80) > 0 {
@@ -39,8 +34,7 @@ impl NESCPU {
             mode: AddrModeType::Rel,
             addr: Some(resolved_addr),
             bytes: 2,
-            operands: format!("{:02X}", offset),
-            repr: format!("${:04X}", resolved_addr),
+            operand: offset as u16,
         }
     }
 }
@@ -64,8 +58,7 @@ mod rel_tests {
                 mode: AddrModeType::Rel,
                 addr: Some(0x1),
                 bytes: 2,
-                operands: "01".to_owned(),
-                repr: "$0001".to_owned()
+                operand: 0x1
             },
             result
         );
@@ -84,8 +77,7 @@ mod rel_tests {
                 mode: AddrModeType::Rel,
                 addr: Some(0x1233),
                 bytes: 2,
-                operands: "FF".to_owned(),
-                repr: "$1233".to_owned()
+                operand: 0xff
             },
             result
         );
@@ -104,8 +96,7 @@ mod rel_tests {
                 mode: AddrModeType::Rel,
                 addr: Some(0x1),
                 bytes: 2,
-                operands: "02".to_owned(),
-                repr: "$0001".to_owned()
+                operand: 0x2
             },
             result
         );
@@ -124,8 +115,7 @@ mod rel_tests {
                 mode: AddrModeType::Rel,
                 addr: Some(0xfffe),
                 bytes: 2,
-                operands: "FE".to_owned(),
-                repr: "$FFFE".to_owned()
+                operand: 0xfe
             },
             result
         );
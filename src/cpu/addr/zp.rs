@@ -15,28 +15,20 @@ use crate::{bus::Bus, cpu::NESCPU};
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn zp(&mut self, bus: &dyn Bus) -> AddrModeResult {
-        let addr = self.fetch_byte(bus);
-        self._zp(addr, bus)
-    }
-
-    pub(in crate::cpu) fn _zp(&self, addr: u8, _bus: &dyn Bus) -> AddrModeResult {
+    pub(in crate::cpu) fn _zp<B: Bus + ?Sized>(&self, addr: u8, _bus: &B) -> AddrModeResult {
         AddrModeResult {
             data: None,
             cycles: 1,
             mode: AddrModeType::Zp,
             addr: Some(addr as u16),
             bytes: 2,
-            operands: format!("{:02X}", addr),
-            repr: format!("${:02X}", addr),
+            operand: addr as u16,
         }
     }
 }
 
 #[cfg(test)]
 mod zp_tests {
-    use mockall::predicate::eq;
-
     use crate::{bus::MockBus, cpu::addr::AddrModeResult};
 
     use super::*;
@@ -44,8 +36,7 @@ mod zp_tests {
     #[test]
     fn test_zp_addressing_mode() {
         let cpu = NESCPU::new();
-        let mut mock_bus = MockBus::new();
-        mock_bus.expect_read().with(eq(0x0)).return_const(0x77);
+        let mock_bus = MockBus::new();
 
         let result = cpu._zp(0x0, &mock_bus);
         assert_eq!(
@@ -55,8 +46,7 @@ mod zp_tests {
                 mode: AddrModeType::Zp,
                 addr: Some(0x0),
                 bytes: 2,
-                operands: "00".to_owned(),
-                repr: "$00".to_owned()
+                operand: 0x0
             },
             result
         );
@@ -0,0 +1,78 @@
+/*
+    Zero Page Relative (65C02)
+
+    Used only by the BBR and BBS bit branch instructions. The
+    second byte of the instruction is a zero page address whose
+    contents are tested, and the third byte is an offset added to
+    the program counter (set at the next instruction) if the
+    branch is taken, as with relative addressing.
+
+    Bytes: 3
+*/
+
+use crate::{bus::Bus, cpu::NESCPU};
+
+use super::{AddrModeResult, AddrModeType};
+
+impl NESCPU {
+    pub(in crate::cpu) fn _zprel<B: Bus + ?Sized>(
+        &self,
+        addr: u8,
+        offset: u8,
+        bus: &B,
+    ) -> AddrModeResult {
+        let rel = self._rel(offset);
+
+        AddrModeResult {
+            data: Some(bus.read(addr as u16)),
+            cycles: rel.cycles,
+            mode: AddrModeType::Zprel,
+            addr: rel.addr,
+            bytes: 3,
+            operand: (offset as u16) << 8 | addr as u16,
+        }
+    }
+}
+
+#[cfg(test)]
+mod zprel_tests {
+    use mockall::predicate::eq;
+
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_zprel_addressing_mode() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.pc = 0x8003;
+
+        bus.expect_read().with(eq(0x10)).times(1).return_const(0x42);
+
+        assert_eq!(
+            AddrModeResult {
+                data: Some(0x42),
+                cycles: 0,
+                mode: AddrModeType::Zprel,
+                addr: Some(0x8013),
+                bytes: 3,
+                operand: 0x1010
+            },
+            cpu._zprel(0x10, 0x10, &bus)
+        );
+    }
+
+    #[test]
+    fn test_zprel_addressing_mode_with_page_cross() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.pc = 0x8003;
+
+        bus.expect_read().return_const(0x0);
+
+        let result = cpu._zprel(0x10, 0x80, &bus);
+        assert_eq!(Some(0x7f83), result.addr);
+        assert_eq!(1, result.cycles);
+    }
+}
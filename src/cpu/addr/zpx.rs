@@ -19,12 +19,7 @@ use crate::{bus::Bus, cpu::NESCPU};
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn zpx(&mut self, bus: &dyn Bus) -> AddrModeResult {
-        let addr = self.fetch_byte(bus);
-        self._zpx(addr, bus)
-    }
-
-    pub(in crate::cpu) fn _zpx(&self, addr: u8, _bus: &dyn Bus) -> AddrModeResult {
+    pub(in crate::cpu) fn _zpx<B: Bus + ?Sized>(&self, addr: u8, _bus: &B) -> AddrModeResult {
         let resolved_addr = addr.wrapping_add(self.x) as u16;
 
         AddrModeResult {
@@ -33,27 +28,22 @@ impl NESCPU {
             mode: AddrModeType::Zpx,
             addr: Some(resolved_addr),
             bytes: 2,
-            operands: format!("{:02X}", addr),
-            repr: format!("${:02X},X", addr),
+            operand: addr as u16,
         }
     }
 }
 
 #[cfg(test)]
 mod zpx_tests {
-    use mockall::predicate::eq;
-
     use super::*;
     use crate::{bus::MockBus, cpu::addr::AddrModeResult};
 
     #[test]
     fn test_zpx_addressing_mode() {
         let mut cpu = NESCPU::new();
-        let mut mock_bus = MockBus::new();
+        let mock_bus = MockBus::new();
         cpu.x = 0x2;
 
-        mock_bus.expect_read().with(eq(0x1)).return_const(0x77);
-
         let result = cpu._zpx(0xff, &mock_bus);
         assert_eq!(
             AddrModeResult {
@@ -62,8 +52,7 @@ mod zpx_tests {
                 mode: AddrModeType::Zpx,
                 addr: Some(0x1),
                 bytes: 2,
-                operands: "FF".to_owned(),
-                repr: "$FF,X".to_owned()
+                operand: 0xff
             },
             result
         );
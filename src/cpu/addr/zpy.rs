@@ -19,12 +19,7 @@ use crate::{bus::Bus, cpu::NESCPU};
 use super::{AddrModeResult, AddrModeType};
 
 impl NESCPU {
-    pub(in crate::cpu) fn zpy(&mut self, bus: &dyn Bus) -> AddrModeResult {
-        let addr = self.fetch_byte(bus);
-        self._zpy(addr, bus)
-    }
-
-    pub(in crate::cpu) fn _zpy(&self, addr: u8, _bus: &dyn Bus) -> AddrModeResult {
+    pub(in crate::cpu) fn _zpy<B: Bus + ?Sized>(&self, addr: u8, _bus: &B) -> AddrModeResult {
         let resolved_addr = addr.wrapping_add(self.y) as u16;
 
         AddrModeResult {
@@ -33,27 +28,22 @@ impl NESCPU {
             mode: AddrModeType::Zpy,
             addr: Some(resolved_addr),
             bytes: 2,
-            operands: format!("{:02X}", addr),
-            repr: format!("${:02X},Y", addr),
+            operand: addr as u16,
         }
     }
 }
 
 #[cfg(test)]
 mod zpy_tests {
-    use mockall::predicate::eq;
-
     use super::*;
     use crate::{bus::MockBus, cpu::addr::AddrModeResult};
 
     #[test]
     fn test_zpy_addressing_mode() {
         let mut cpu = NESCPU::new();
-        let mut mock_bus = MockBus::new();
+        let mock_bus = MockBus::new();
         cpu.y = 0x2;
 
-        mock_bus.expect_read().with(eq(0x1)).return_const(0x77);
-
         let result = cpu._zpy(0xff, &mock_bus);
         assert_eq!(
             AddrModeResult {
@@ -62,8 +52,7 @@ mod zpy_tests {
                 mode: AddrModeType::Zpy,
                 addr: Some(0x1),
                 bytes: 2,
-                operands: "FF".to_owned(),
-                repr: "$FF,Y".to_owned()
+                operand: 0xff
             },
             result
         );
@@ -0,0 +1,591 @@
+/*
+    Two-pass assembler
+
+    Turns 6502 source into machine code using the same lookup table as
+    the CPU, so every opcode of the selected variant (undocumented ones
+    included, by their table names) can be assembled.
+
+    The first pass works out the address of every label and the size of
+    every line. Operands that refer to a label which is not yet known are
+    assumed to be absolute. The second pass emits the bytes.
+
+    Syntax:
+        label:                  Defines a label at the current address
+        NAME = expr             Defines a constant
+        .org expr               Moves forward to expr, padding with $00
+        .byte expr, ...         Emits bytes
+        .word expr, ...         Emits little endian words
+        ; comment
+
+    Operands use the same notation as the trace log (#$10, $10,X, ($10),Y,
+    ($1234), A, ...). Expressions are $hex, %binary, decimal, labels and *
+    (the current address), joined with + and -. A leading < or > takes the
+    low or high byte of the result. Zero page addressing is picked whenever
+    the value is known to fit in a byte.
+*/
+
+use std::{collections::HashMap, fmt};
+
+use super::{addr::AddrModeResult, disasm::is_undocumented, AddrModeType, CpuVariant, NESCPU};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct AsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    None,
+    Acc,
+    Imm(String),
+    Direct(String),
+    DirectX(String),
+    DirectY(String),
+    Ind(String),
+    IndX(String),
+    IndY(String),
+    Pair(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Statement<'a> {
+    Empty,
+    Constant(&'a str, &'a str),
+    Org(&'a str),
+    Byte(Vec<&'a str>),
+    Word(Vec<&'a str>),
+    Instruction(String, Operand),
+}
+
+type Labels = HashMap<String, u16>;
+
+pub fn assemble(source: &str, origin: u16, variant: CpuVariant) -> Result<Vec<u8>, AsmError> {
+    let mut labels = Labels::new();
+    let mut statements = vec![];
+
+    //Pass 1: addresses of labels and the addressing mode of each instruction
+    let mut pc = origin;
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| AsmError {
+            line: i + 1,
+            message,
+        };
+
+        let (label, statement) = parse_line(line).map_err(error)?;
+        if let Some(label) = label {
+            define(&mut labels, label, pc).map_err(error)?;
+        }
+
+        let mut mode = None;
+        match &statement {
+            Statement::Constant(name, expr) => {
+                let value = eval(expr, &labels, pc).map_err(error)?.ok_or_else(|| {
+                    error(format!("constant {} must be defined before use", name))
+                })?;
+                define(&mut labels, name, value).map_err(error)?;
+            }
+            Statement::Org(expr) => {
+                pc = eval(expr, &labels, pc)
+                    .map_err(error)?
+                    .ok_or_else(|| error(".org must be defined before use".to_owned()))?;
+            }
+            Statement::Byte(exprs) => pc = pc.wrapping_add(exprs.len() as u16),
+            Statement::Word(exprs) => pc = pc.wrapping_add(2 * exprs.len() as u16),
+            Statement::Instruction(mnemonic, operand) => {
+                let value = match operand {
+                    Operand::Direct(expr) | Operand::DirectX(expr) | Operand::DirectY(expr) => {
+                        eval(expr, &labels, pc).map_err(error)?
+                    }
+                    _ => None,
+                };
+                let (opcode, selected) =
+                    select(variant, mnemonic, operand, value).map_err(error)?;
+                pc = pc.wrapping_add(AddrModeResult::unresolved(selected).bytes as u16);
+                mode = Some((opcode, selected));
+            }
+            Statement::Empty => {}
+        }
+
+        statements.push((i + 1, statement, mode));
+    }
+
+    //Pass 2: emit bytes now that every label is known
+    let mut pc = origin;
+    let mut bytes = vec![];
+    for (line, statement, mode) in statements {
+        let error = |message: String| AsmError { line, message };
+        let resolve = |expr: &str, pc: u16| -> Result<u16, AsmError> {
+            eval(expr, &labels, pc)
+                .map_err(error)?
+                .ok_or_else(|| error(format!("undefined label in '{}'", expr)))
+        };
+
+        match statement {
+            Statement::Org(expr) => {
+                let addr = resolve(expr, pc)?;
+                if addr < pc {
+                    return Err(error(format!(".org ${:04X} is behind ${:04X}", addr, pc)));
+                }
+                bytes.resize(bytes.len() + (addr - pc) as usize, 0x0);
+                pc = addr;
+            }
+            Statement::Byte(exprs) => {
+                for expr in exprs {
+                    bytes.push(byte(resolve(expr, pc)?).map_err(error)?);
+                    pc = pc.wrapping_add(1);
+                }
+            }
+            Statement::Word(exprs) => {
+                for expr in exprs {
+                    let value = resolve(expr, pc)?;
+                    bytes.extend([value as u8, (value >> 8) as u8]);
+                    pc = pc.wrapping_add(2);
+                }
+            }
+            Statement::Instruction(_, operand) => {
+                let (opcode, mode) = mode.unwrap();
+                let length = AddrModeResult::unresolved(mode).bytes as u16;
+                let next = pc.wrapping_add(length);
+
+                bytes.push(opcode);
+                match (mode, operand) {
+                    (AddrModeType::Imp | AddrModeType::Acc, _) => {}
+                    //BRK written without an operand gets a padding byte
+                    (AddrModeType::Imm, Operand::None) => bytes.push(0x0),
+                    (AddrModeType::Rel, Operand::Direct(expr)) => {
+                        bytes.push(offset(resolve(&expr, pc)?, next).map_err(error)?);
+                    }
+                    (AddrModeType::Zprel, Operand::Pair(zp, target)) => {
+                        bytes.push(byte(resolve(&zp, pc)?).map_err(error)?);
+                        bytes.push(offset(resolve(&target, pc)?, next).map_err(error)?);
+                    }
+                    (
+                        _,
+                        Operand::Imm(expr)
+                        | Operand::Direct(expr)
+                        | Operand::DirectX(expr)
+                        | Operand::DirectY(expr)
+                        | Operand::Ind(expr)
+                        | Operand::IndX(expr)
+                        | Operand::IndY(expr),
+                    ) => {
+                        let value = resolve(&expr, pc)?;
+                        if length == 2 {
+                            bytes.push(byte(value).map_err(error)?);
+                        } else {
+                            bytes.extend([value as u8, (value >> 8) as u8]);
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+                pc = next;
+            }
+            Statement::Constant(..) | Statement::Empty => {}
+        }
+    }
+
+    Ok(bytes)
+}
+
+fn define(labels: &mut Labels, name: &str, value: u16) -> Result<(), String> {
+    if !is_identifier(name) {
+        return Err(format!("invalid label '{}'", name));
+    }
+    if labels.insert(name.to_owned(), value).is_some() {
+        return Err(format!("label {} is already defined", name));
+    }
+
+    Ok(())
+}
+
+fn parse_line(line: &str) -> Result<(Option<&str>, Statement<'_>), String> {
+    let mut line = line.split(';').next().unwrap().trim();
+
+    let mut label = None;
+    if let Some((name, rest)) = line.split_once(':') {
+        label = Some(name.trim());
+        line = rest.trim();
+    }
+
+    if line.is_empty() {
+        return Ok((label, Statement::Empty));
+    }
+
+    if let Some((name, expr)) = line.split_once('=') {
+        return Ok((label, Statement::Constant(name.trim(), expr.trim())));
+    }
+
+    let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    let list = || rest.split(',').map(str::trim).collect::<Vec<&str>>();
+
+    let statement = match keyword.to_ascii_lowercase().as_str() {
+        ".org" => Statement::Org(rest),
+        ".byte" => Statement::Byte(list()),
+        ".word" => Statement::Word(list()),
+        directive if directive.starts_with('.') => {
+            return Err(format!("unknown directive {}", keyword))
+        }
+        _ => Statement::Instruction(keyword.to_ascii_uppercase(), parse_operand(rest)),
+    };
+
+    Ok((label, statement))
+}
+
+fn parse_operand(operand: &str) -> Operand {
+    let operand: String = operand.chars().filter(|c| !c.is_whitespace()).collect();
+    let upper = operand.to_ascii_uppercase();
+    let inner = |prefix: usize, suffix: usize| operand[prefix..operand.len() - suffix].to_owned();
+
+    match upper.as_str() {
+        "" => Operand::None,
+        "A" => Operand::Acc,
+        _ if upper.starts_with('#') => Operand::Imm(inner(1, 0)),
+        _ if upper.starts_with('(') && upper.ends_with(",X)") => Operand::IndX(inner(1, 3)),
+        _ if upper.starts_with('(') && upper.ends_with("),Y") => Operand::IndY(inner(1, 3)),
+        _ if upper.starts_with('(') && upper.ends_with(')') => Operand::Ind(inner(1, 1)),
+        _ if upper.ends_with(",X") => Operand::DirectX(inner(0, 2)),
+        _ if upper.ends_with(",Y") => Operand::DirectY(inner(0, 2)),
+        _ => match operand.split_once(',') {
+            Some((zp, target)) => Operand::Pair(zp.to_owned(), target.to_owned()),
+            None => Operand::Direct(operand),
+        },
+    }
+}
+
+fn select(
+    variant: CpuVariant,
+    mnemonic: &str,
+    operand: &Operand,
+    value: Option<u16>,
+) -> Result<(u8, AddrModeType), String> {
+    if find_opcode(variant, mnemonic, None).is_none() {
+        return Err(format!("unknown mnemonic {}", mnemonic));
+    }
+
+    let zp = matches!(value, Some(value) if value <= 0xff);
+    let candidates: &[AddrModeType] = match operand {
+        Operand::None if mnemonic == "BRK" => &[AddrModeType::Imm],
+        Operand::None => &[AddrModeType::Imp, AddrModeType::Acc],
+        Operand::Acc => &[AddrModeType::Acc],
+        Operand::Imm(_) => &[AddrModeType::Imm],
+        Operand::Direct(_) if find_opcode(variant, mnemonic, Some(AddrModeType::Rel)).is_some() => {
+            &[AddrModeType::Rel]
+        }
+        Operand::Direct(_) if zp => &[AddrModeType::Zp, AddrModeType::Abs],
+        Operand::Direct(_) => &[AddrModeType::Abs],
+        Operand::DirectX(_) if zp => &[AddrModeType::Zpx, AddrModeType::Absx],
+        Operand::DirectX(_) => &[AddrModeType::Absx],
+        Operand::DirectY(_) if zp => &[AddrModeType::Zpy, AddrModeType::Absy],
+        Operand::DirectY(_) => &[AddrModeType::Absy],
+        Operand::Ind(_) => &[AddrModeType::Ind, AddrModeType::Indzp],
+        Operand::IndX(_) => &[AddrModeType::Indx, AddrModeType::Indabsx],
+        Operand::IndY(_) => &[AddrModeType::Indy],
+        Operand::Pair(..) => &[AddrModeType::Zprel],
+    };
+
+    candidates
+        .iter()
+        .find_map(|&mode| find_opcode(variant, mnemonic, Some(mode)).map(|opcode| (opcode, mode)))
+        .ok_or_else(|| format!("invalid addressing mode for {}", mnemonic))
+}
+
+fn find_opcode(variant: CpuVariant, mnemonic: &str, mode: Option<AddrModeType>) -> Option<u8> {
+    let mut opcodes = (0x0..=0xff).filter(|&opcode| {
+        let (name, opcode_mode, _, _) = NESCPU::lookup(variant, opcode);
+        name == mnemonic && mode.is_none_or(|mode| mode == opcode_mode)
+    });
+
+    //Prefer the documented encoding when several opcodes do the same thing
+    let first = opcodes.next()?;
+    Some(
+        std::iter::once(first)
+            .chain(opcodes)
+            .find(|&opcode| !is_undocumented(variant, opcode))
+            .unwrap_or(first),
+    )
+}
+
+fn byte(value: u16) -> Result<u8, String> {
+    match value {
+        0x0..=0xff => Ok(value as u8),
+        _ => Err(format!("${:04X} does not fit in a byte", value)),
+    }
+}
+
+fn offset(target: u16, next: u16) -> Result<u8, String> {
+    let offset = target.wrapping_sub(next) as i16;
+    match offset {
+        -128..=127 => Ok(offset as u8),
+        _ => Err(format!("branch to ${:04X} is out of range", target)),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+//Returns None when the expression uses a label that is not defined yet
+fn eval(expr: &str, labels: &Labels, pc: u16) -> Result<Option<u16>, String> {
+    let expr = expr.trim();
+    if let Some(rest) = expr.strip_prefix('<') {
+        return Ok(eval(rest, labels, pc)?.map(|value| value & 0xff));
+    }
+    if let Some(rest) = expr.strip_prefix('>') {
+        return Ok(eval(rest, labels, pc)?.map(|value| value >> 8));
+    }
+
+    let mut result = Some(0u16);
+    let mut sign = '+';
+    let mut start = 0;
+    for (i, c) in expr
+        .char_indices()
+        .chain(std::iter::once((expr.len(), '+')))
+    {
+        //A '*' at the start of a term is the current address, not an operator
+        if (c == '+' || c == '-') && i > start {
+            let term = match expr[start..i].trim() {
+                "*" => Some(pc),
+                term => eval_term(term, labels)?,
+            };
+            result = match (result, term) {
+                (Some(result), Some(term)) if sign == '+' => Some(result.wrapping_add(term)),
+                (Some(result), Some(term)) => Some(result.wrapping_sub(term)),
+                _ => None,
+            };
+            sign = c;
+            start = i + 1;
+        }
+    }
+
+    if start <= expr.len() {
+        return Err(format!("invalid expression '{}'", expr));
+    }
+
+    Ok(result)
+}
+
+fn eval_term(term: &str, labels: &Labels) -> Result<Option<u16>, String> {
+    let invalid = |_| format!("invalid number '{}'", term);
+
+    if let Some(hex) = term.strip_prefix('$') {
+        u16::from_str_radix(hex, 16).map(Some).map_err(invalid)
+    } else if let Some(binary) = term.strip_prefix('%') {
+        u16::from_str_radix(binary, 2).map(Some).map_err(invalid)
+    } else if term.starts_with(|c: char| c.is_ascii_digit()) {
+        term.parse::<u16>().map(Some).map_err(invalid)
+    } else if is_identifier(term) {
+        Ok(labels.get(term).copied())
+    } else {
+        Err(format!("invalid expression '{}'", term))
+    }
+}
+
+#[cfg(test)]
+mod asm_tests {
+    use super::*;
+
+    use crate::{
+        bus::Bus,
+        cpu::{disasm::disassemble, CPU},
+    };
+
+    fn asm(source: &str) -> Vec<u8> {
+        assemble(source, 0x8000, CpuVariant::Ricoh2A03).unwrap()
+    }
+
+    #[test]
+    fn test_assemble_addressing_modes() {
+        assert_eq!(vec![0xea], asm("NOP"));
+        assert_eq!(vec![0x0a], asm("ASL"));
+        assert_eq!(vec![0x0a], asm("asl a"));
+        assert_eq!(vec![0xa9, 0x10], asm("LDA #$10"));
+        assert_eq!(vec![0xa5, 0x10], asm("LDA $10"));
+        assert_eq!(vec![0xb5, 0x10], asm("LDA $10,X"));
+        assert_eq!(vec![0xb6, 0x10], asm("LDX $10,Y"));
+        assert_eq!(vec![0xad, 0x00, 0x20], asm("LDA $2000"));
+        assert_eq!(vec![0xbd, 0x00, 0x20], asm("LDA $2000,X"));
+        assert_eq!(vec![0xb9, 0x00, 0x20], asm("LDA $2000, y"));
+        assert_eq!(vec![0xb9, 0x10, 0x00], asm("LDA $10,Y"));
+        assert_eq!(vec![0x6c, 0xfc, 0xff], asm("JMP ($FFFC)"));
+        assert_eq!(vec![0xa1, 0x10], asm("LDA ($10,X)"));
+        assert_eq!(vec![0xb1, 0x10], asm("LDA ($10),Y"));
+        assert_eq!(vec![0xf0, 0xfe], asm("BEQ $8000"));
+        assert_eq!(vec![0x00, 0x00], asm("BRK"));
+    }
+
+    #[test]
+    fn test_assemble_labels_and_directives() {
+        let source = "
+            PPUCTRL = $2000
+            start:
+                LDX #<table       ; low byte
+                LDY #>table
+            loop: DEX
+                BNE loop
+                BEQ done
+                STA PPUCTRL
+            done:
+                JMP start
+            table:
+                .byte 1, %10, $03
+                .word done, *+2
+                .org $8020
+                .byte $FF
+        ";
+
+        assert_eq!(
+            vec![
+                0xa2, 0x0f, 0xa0, 0x80, 0xca, 0xd0, 0xfd, 0xf0, 0x03, 0x8d, 0x00, 0x20, 0x4c, 0x00,
+                0x80, 0x01, 0x02, 0x03, 0x0c, 0x80, 0x16, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0xff
+            ],
+            asm(source)
+        );
+    }
+
+    #[test]
+    fn test_assemble_forward_reference_is_absolute() {
+        assert_eq!(vec![0xad, 0x03, 0x80], asm("LDA zp\nzp:"));
+        assert_eq!(vec![0xa5, 0x10], asm("zp = $10\nLDA zp"));
+    }
+
+    #[test]
+    fn test_assemble_undocumented_opcodes() {
+        assert_eq!(vec![0xa7, 0x10], asm("LAX $10"));
+        assert_eq!(vec![0xcb, 0x05], asm("SBX #$05"));
+        assert_eq!(vec![0x02], asm("JAM"));
+        assert_eq!(vec![0xe9, 0x01], asm("SBC #1"));
+    }
+
+    #[test]
+    fn test_assemble_65c02() {
+        let source = "loop: STZ $10\nBRA loop\nBBS7 $10,loop\nJMP ($1234,X)\nLDA ($10)";
+
+        assert_eq!(
+            vec![0x64, 0x10, 0x80, 0xfc, 0xff, 0x10, 0xf9, 0x7c, 0x34, 0x12, 0xb2, 0x10],
+            assemble(source, 0x8000, CpuVariant::Wdc65C02).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let error = |source| assemble(source, 0x8000, CpuVariant::Ricoh2A03).unwrap_err();
+
+        assert_eq!(
+            AsmError {
+                line: 2,
+                message: "unknown mnemonic FOO".to_owned()
+            },
+            error("NOP\nFOO")
+        );
+        assert_eq!(
+            "line 1: invalid addressing mode for JSR",
+            error("JSR #$10").to_string()
+        );
+        assert_eq!(
+            "line 1: undefined label in 'nowhere'",
+            error("JMP nowhere").to_string()
+        );
+        assert_eq!(
+            "line 1: $0100 does not fit in a byte",
+            error("LDA #$100").to_string()
+        );
+        assert_eq!(
+            "line 2: label a is already defined",
+            error("a:\na:").to_string()
+        );
+        assert_eq!(
+            "line 2: branch to $8100 is out of range",
+            error("NOP\nBNE $8100").to_string()
+        );
+        assert_eq!(
+            "line 1: unknown mnemonic BBR0",
+            error("BBR0 $10,$8000").to_string()
+        );
+    }
+
+    #[test]
+    fn test_assemble_round_trips_disassembly() {
+        for variant in [
+            CpuVariant::Ricoh2A03,
+            CpuVariant::Nmos6502,
+            CpuVariant::Wdc65C02,
+        ] {
+            for opcode in 0x0..=0xff {
+                let line = &disassemble(&[opcode, 0x12, 0x34], 0x8000, variant)[0];
+                let source = format!("{} {}", line.mnemonic, line.operand);
+                let bytes = assemble(&source, 0x8000, variant).unwrap();
+
+                let reassembled = &disassemble(&bytes, 0x8000, variant)[0];
+                assert_eq!(
+                    (line.mnemonic, &line.operand, &line.bytes[1..]),
+                    (
+                        reassembled.mnemonic,
+                        &reassembled.operand,
+                        &reassembled.bytes[1..]
+                    ),
+                    "{:?} opcode ${:02X}",
+                    variant,
+                    opcode
+                );
+            }
+        }
+    }
+
+    struct RamBus {
+        ram: Vec<u8>,
+    }
+
+    impl Bus for RamBus {
+        fn read(&self, addr: u16) -> u8 {
+            self.ram[addr as usize]
+        }
+
+        fn write(&mut self, addr: u16, data: u8) {
+            self.ram[addr as usize] = data;
+        }
+
+        fn peek(&self, addr: u16) -> u8 {
+            self.ram[addr as usize]
+        }
+    }
+
+    #[test]
+    fn test_assembled_program_runs() {
+        let program = asm("
+                LDX #5
+                LDA #0
+                CLC
+            loop:
+                ADC #3
+                DEX
+                BNE loop
+                STA $10
+                JAM
+        ");
+
+        let mut bus = RamBus {
+            ram: vec![0x0; 0x10000],
+        };
+        bus.ram[0x8000..0x8000 + program.len()].copy_from_slice(&program);
+        bus.ram[0xfffc] = 0x00;
+        bus.ram[0xfffd] = 0x80;
+
+        let mut cpu = NESCPU::new();
+        while cpu.get_jam().is_none() {
+            cpu.clock(&mut bus);
+        }
+
+        assert_eq!(15, bus.ram[0x10]);
+    }
+}
@@ -0,0 +1,1818 @@
+/*
+    Cycle-by-cycle execution
+
+    Every clock performs exactly one bus access, the same one the
+    6502 makes on that cycle, including its dummy accesses:
+
+    - Implied/accumulator instructions read the byte after the opcode
+    - Zero page indexed modes read the unindexed zero page address
+    - Absolute indexed and (indirect),Y modes read the address with
+      the unfixed high byte when the index crosses a page (always for
+      stores and read-modify-write instructions)
+    - Read-modify-write instructions write the unmodified value back
+      before writing the result
+    - Stack instructions and interrupts read the stack or the next
+      byte while the stack pointer is adjusted
+
+    Once an instruction's last bus access has been made, its execute
+    function runs against a ReplayBus, which answers reads with the
+    values fetched on earlier cycles and only lets through the writes
+    that belong to the final cycle.
+
+    The 65C02 differs in a few of these: it reads the last instruction
+    byte instead of the unfixed address, reads rather than writes the
+    unmodified value of read-modify-write instructions, and spends an
+    extra cycle on decimal ADC/SBC and on JMP ($xxxx).
+*/
+
+use crate::bus::Bus;
+
+use super::{
+    addr::{AddrModeResult, AddrModeType},
+    CpuVariant, CurrentInstruction, Instruction, InstructionType, LookupTable, Trace, NESCPU,
+};
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(in crate::cpu) enum Access {
+    Read,
+    Write,
+    ReadModifyWrite,
+    Implied,
+    Branch,
+    Jump,
+    Jsr,
+    Rts,
+    Rti,
+    Brk,
+    Push,
+    Pull,
+    Jam,
+    BitBranch,
+    Wait,
+    Stop,
+}
+
+impl Access {
+    //Mnemonics are matched as bytes so the tables below can be built at
+    //compile time
+    const fn of(mnemonic: &str, mode: AddrModeType) -> Access {
+        match (mnemonic.as_bytes(), mode) {
+            (b"JAM", _) => Access::Jam,
+            (b"WAI", _) => Access::Wait,
+            (b"STP", _) => Access::Stop,
+            (b"BRK", _) => Access::Brk,
+            (b"JSR", _) => Access::Jsr,
+            (b"RTS", _) => Access::Rts,
+            (b"RTI", _) => Access::Rti,
+            (b"JMP", _) => Access::Jump,
+            (b"PHA" | b"PHP" | b"PHX" | b"PHY", _) => Access::Push,
+            (b"PLA" | b"PLP" | b"PLX" | b"PLY", _) => Access::Pull,
+            (_, AddrModeType::Rel) => Access::Branch,
+            (_, AddrModeType::Zprel) => Access::BitBranch,
+            (_, AddrModeType::Imp | AddrModeType::Acc) => Access::Implied,
+            (b"STA" | b"STX" | b"STY" | b"STZ" | b"SAX" | b"SHA" | b"SHX" | b"SHY" | b"SHS", _) => {
+                Access::Write
+            }
+            (
+                b"ASL"
+                | b"LSR"
+                | b"ROL"
+                | b"ROR"
+                | b"INC"
+                | b"DEC"
+                | b"SLO"
+                | b"SRE"
+                | b"RLA"
+                | b"RRA"
+                | b"DCP"
+                | b"ISC"
+                | b"TRB"
+                | b"TSB"
+                | [b'R' | b'S', b'M', b'B', _],
+                _,
+            ) => Access::ReadModifyWrite,
+            _ => Access::Read,
+        }
+    }
+}
+
+//How an opcode accesses memory, and whether its page fixup cycle is skipped
+//when no page is crossed
+type OpcodeAccess = (Access, bool);
+
+impl NESCPU {
+    const ACCESS_TABLE: [OpcodeAccess; 256] = NESCPU::access_table(
+        &<NESCPU as LookupTable>::LOOKUP_TABLE,
+        CpuVariant::Ricoh2A03,
+    );
+    const ACCESS_TABLE_65C02: [OpcodeAccess; 256] = NESCPU::access_table(
+        &<NESCPU as LookupTable>::LOOKUP_TABLE_65C02,
+        CpuVariant::Wdc65C02,
+    );
+
+    const fn access_table(
+        lookup_table: &[Instruction; 256],
+        variant: CpuVariant,
+    ) -> [OpcodeAccess; 256] {
+        let mut table = [(Access::Read, false); 256];
+        let mut opcode = 0;
+        while opcode < 256 {
+            let (mnemonic, mode, _, _) = lookup_table[opcode];
+            let access = Access::of(mnemonic, mode);
+            let fixup_optional = match access {
+                Access::Read => true,
+                //The 65C02 only skips it for its shifts and rotates
+                Access::ReadModifyWrite => {
+                    matches!(variant, CpuVariant::Wdc65C02)
+                        && matches!(mnemonic.as_bytes(), b"ASL" | b"LSR" | b"ROL" | b"ROR")
+                }
+                _ => false,
+            };
+            table[opcode] = (access, fixup_optional);
+            opcode += 1;
+        }
+
+        table
+    }
+
+    fn decode_access(&self, opcode: u8) -> OpcodeAccess {
+        match self.variant {
+            CpuVariant::Wdc65C02 => NESCPU::ACCESS_TABLE_65C02[opcode as usize],
+            CpuVariant::Ricoh2A03 | CpuVariant::Nmos6502 => NESCPU::ACCESS_TABLE[opcode as usize],
+        }
+    }
+}
+
+struct ReplayBus<'a, B: Bus> {
+    bus: &'a mut B,
+    reads: &'a [(u16, u8)],
+    forward_writes: bool,
+}
+
+impl<B: Bus> Bus for ReplayBus<'_, B> {
+    fn read(&self, addr: u16) -> u8 {
+        match self
+            .reads
+            .iter()
+            .rev()
+            .find(|(read_addr, _)| *read_addr == addr)
+        {
+            Some((_, data)) => *data,
+            //A real read here would repeat its side effects
+            None => unreachable!("{:04X} was not read on an earlier cycle", addr),
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        if self.forward_writes {
+            self.bus.write(addr, data);
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        match self
+            .reads
+            .iter()
+            .rev()
+            .find(|(read_addr, _)| *read_addr == addr)
+        {
+            Some((_, data)) => *data,
+            None => self.bus.peek(addr),
+        }
+    }
+}
+
+//Reads through peek, so tracing does not disturb the machine
+struct PeekBus<'a, B: Bus>(&'a B);
+
+impl<B: Bus> Bus for PeekBus<'_, B> {
+    fn read(&self, addr: u16) -> u8 {
+        self.0.peek(addr)
+    }
+
+    fn write(&mut self, _addr: u16, _data: u8) {
+        unreachable!("Addressing modes never write");
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.0.peek(addr)
+    }
+}
+
+impl NESCPU {
+    //Returns true once the last cycle of the current operation has run
+    pub(in crate::cpu) fn execute_cycle<B: Bus>(&mut self, bus: &mut B) -> bool {
+        let cycle = self.current_instruction.cycle;
+        match self.current_instruction.instruction_type {
+            InstructionType::Fetch => self.fetch_opcode(bus),
+            InstructionType::Jam => {
+                self.current_instruction.remaining_cycles = 0xff;
+                false
+            }
+            InstructionType::Wait => {
+                //Any interrupt ends the wait, even an IRQ masked by the I flag
+                if self.pending_nmi || self.pending_irq {
+                    self.interrupt_polled = self.pending_nmi || !self.i;
+                    return true;
+                }
+                self.current_instruction.remaining_cycles = 0xff;
+                false
+            }
+            InstructionType::Reset => self.reset_cycle(cycle, bus),
+            InstructionType::Nmi => self.interrupt_cycle(cycle, NESCPU::NMI_VECTOR, bus),
+            InstructionType::Irq => self.interrupt_cycle(cycle, NESCPU::IRQ_VECTOR, bus),
+            InstructionType::Instruction { opcode, access } => match access {
+                Access::Implied => {
+                    bus.read(self.pc);
+                    self.execute(opcode, bus, true);
+                    true
+                }
+                Access::Read if self.decode(opcode).1 == AddrModeType::Imm => {
+                    if cycle == 2 {
+                        self.operand = self.fetch_byte(bus) as u16;
+                        self.execute(opcode, bus, true);
+                    } else {
+                        bus.read(self.pc);
+                    }
+                    self.current_instruction.remaining_cycles == 0
+                }
+                Access::Read | Access::Write | Access::ReadModifyWrite => {
+                    self.memory_cycle(opcode, access, cycle, bus)
+                }
+                Access::Branch => self.branch_cycle(opcode, cycle, bus),
+                Access::Jump => self.jump_cycle(opcode, cycle, bus),
+                Access::Jsr => self.jsr_cycle(opcode, cycle, bus),
+                Access::Rts => self.rts_cycle(opcode, cycle, bus),
+                Access::Rti => self.rti_cycle(opcode, cycle, bus),
+                Access::Brk => self.brk_cycle(opcode, cycle, bus),
+                Access::Push => self.push_cycle(opcode, cycle, bus),
+                Access::Pull => self.pull_cycle(opcode, cycle, bus),
+                Access::BitBranch => self.bit_branch_cycle(opcode, cycle, bus),
+                Access::Wait | Access::Stop => self.stop_cycle(opcode, access, cycle, bus),
+                Access::Jam => unreachable!(),
+            },
+        }
+    }
+
+    pub(in crate::cpu) fn trace_next_instruction<B: Bus>(&mut self, bus: &B) {
+        let bus = &PeekBus(bus);
+        let pc = self.pc;
+        let opcode = bus.read(pc);
+        let (_, mode, cycles_fn, _) = self.decode(opcode);
+
+        let bytes = AddrModeResult::unresolved(mode).bytes;
+        let operand = match bytes {
+            1 => 0,
+            2 => bus.read(pc.wrapping_add(1)) as u16,
+            _ => (bus.read(pc.wrapping_add(2)) as u16) << 8 | bus.read(pc.wrapping_add(1)) as u16,
+        };
+
+        //Relative targets are computed from the address of the next instruction
+        self.pc = pc.wrapping_add(bytes as u16);
+        let addr_mode = self.addr_mode(mode, operand, bus);
+        self.pc = pc;
+
+        self.current_instruction.remaining_cycles = cycles_fn(self, &addr_mode);
+        self.trace = Some(Trace {
+            pc,
+            opcode,
+            addr_mode,
+            elapsed_cycles: self.elapsed_cycles,
+        });
+    }
+
+    fn fetch_opcode<B: Bus>(&mut self, bus: &mut B) -> bool {
+        let opcode = self.fetch_byte(bus);
+        let (_, mode, cycles_fn, _) = self.decode(opcode);
+        let (access, _) = self.decode_access(opcode);
+
+        self.reads_len = 0;
+        self.page_crossed = false;
+
+        if access == Access::Jam {
+            self.jam(opcode, self.pc.wrapping_sub(1), 1);
+            return false;
+        }
+
+        let cycles = cycles_fn(self, &AddrModeResult::unresolved(mode));
+        self.current_instruction = CurrentInstruction {
+            cycle: 1,
+            remaining_cycles: cycles - 1,
+            instruction_type: InstructionType::Instruction { opcode, access },
+        };
+
+        //Single-cycle 65C02 NOPs complete with the opcode fetch, and
+        //cannot be interrupted as there is no penultimate cycle to poll on
+        if cycles == 1 {
+            self.interrupt_polled = false;
+            self.execute(opcode, bus, true);
+            return true;
+        }
+
+        false
+    }
+
+    fn read_latched<B: Bus>(&mut self, bus: &B, addr: u16) -> u8 {
+        let data = bus.read(addr);
+        self.reads[self.reads_len] = (addr, data);
+        self.reads_len += 1;
+
+        data
+    }
+
+    pub(in crate::cpu) fn addr_mode<B: Bus>(
+        &self,
+        mode: AddrModeType,
+        operand: u16,
+        bus: &B,
+    ) -> AddrModeResult {
+        match mode {
+            AddrModeType::Acc => self._acc(),
+            AddrModeType::Imp => self._imp(),
+            AddrModeType::Imm => self._imm(operand as u8),
+            AddrModeType::Zp => self._zp(operand as u8, bus),
+            AddrModeType::Zpx => self._zpx(operand as u8, bus),
+            AddrModeType::Zpy => self._zpy(operand as u8, bus),
+            AddrModeType::Abs => self._abs(operand, bus),
+            AddrModeType::Absx => self._absx(operand, bus),
+            AddrModeType::Absy => self._absy(operand, bus),
+            AddrModeType::Ind => self._ind(operand, bus),
+            AddrModeType::Indx => self._indx(operand as u8, bus),
+            AddrModeType::Indy => self._indy(operand as u8, bus),
+            AddrModeType::Indzp => self._indzp(operand as u8, bus),
+            AddrModeType::Indabsx => self._indabsx(operand, bus),
+            AddrModeType::Rel => self._rel(operand as u8),
+            AddrModeType::Zprel => self._zprel(operand as u8, (operand >> 8) as u8, bus),
+        }
+    }
+
+    fn execute<B: Bus>(&mut self, opcode: u8, bus: &mut B, forward_writes: bool) {
+        let (_, mode, _, execute_fn) = NESCPU::lookup_for_bus(self.variant, opcode);
+        let reads = self.reads;
+        let mut bus = ReplayBus {
+            bus,
+            reads: &reads[..self.reads_len],
+            forward_writes,
+        };
+
+        let addr_mode = self.addr_mode(mode, self.operand, &bus);
+        execute_fn(self, &addr_mode, &mut bus);
+    }
+
+    fn index_effective_addr(&mut self, base: u16, index: u8, fixup_optional: bool) {
+        self.effective_addr = base.wrapping_add(index as u16);
+        self.page_crossed = (base & 0xff00) != (self.effective_addr & 0xff00);
+
+        //Only spend the extra cycle when the high byte needs fixing
+        if self.page_crossed && fixup_optional {
+            self.current_instruction.remaining_cycles += 1;
+        }
+    }
+
+    fn memory_cycle<B: Bus>(&mut self, opcode: u8, access: Access, cycle: u8, bus: &mut B) -> bool {
+        let mode = self.decode(opcode).1;
+        let (_, fixup_optional) = self.decode_access(opcode);
+        let skip_fixup = fixup_optional && !self.page_crossed;
+        let resolved_at = match mode {
+            AddrModeType::Zp => 2,
+            AddrModeType::Zpx | AddrModeType::Zpy | AddrModeType::Abs => 3,
+            AddrModeType::Absx | AddrModeType::Absy if skip_fixup => 3,
+            AddrModeType::Absx | AddrModeType::Absy => 4,
+            AddrModeType::Indy if skip_fixup => 4,
+            AddrModeType::Indzp => 4,
+            AddrModeType::Indy | AddrModeType::Indx => 5,
+            mode => panic!("Addressing mode {:?} does not access memory", mode),
+        };
+
+        if cycle <= resolved_at {
+            self.address_cycle(mode, fixup_optional, cycle, bus);
+            return false;
+        }
+
+        match (access, cycle - resolved_at) {
+            (Access::Read, 1) => {
+                self.read_latched(bus, self.effective_addr);
+                self.execute(opcode, bus, true);
+                return self.current_instruction.remaining_cycles == 0;
+            }
+            (Access::Read, _) => {
+                //Extra cycles after the operand has been read
+                bus.read(self.effective_addr);
+                return self.current_instruction.remaining_cycles == 0;
+            }
+            (Access::ReadModifyWrite, 1) => {
+                self.read_latched(bus, self.effective_addr);
+                return false;
+            }
+            (Access::ReadModifyWrite, 2) if self.variant == CpuVariant::Wdc65C02 => {
+                bus.read(self.effective_addr);
+                return false;
+            }
+            (Access::ReadModifyWrite, 2) => {
+                let data = self.reads[self.reads_len - 1].1;
+                bus.write(self.effective_addr, data);
+                return false;
+            }
+            _ => {}
+        }
+
+        self.execute(opcode, bus, true);
+        true
+    }
+
+    fn address_cycle<B: Bus>(
+        &mut self,
+        mode: AddrModeType,
+        fixup_optional: bool,
+        cycle: u8,
+        bus: &mut B,
+    ) {
+        match (mode, cycle) {
+            (_, 2) => {
+                self.operand = self.fetch_byte(bus) as u16;
+                self.effective_addr = self.operand;
+            }
+            (AddrModeType::Abs, 3) => {
+                self.operand |= (self.fetch_byte(bus) as u16) << 8;
+                self.effective_addr = self.operand;
+            }
+            (AddrModeType::Absx, 3) => {
+                self.operand |= (self.fetch_byte(bus) as u16) << 8;
+                self.index_effective_addr(self.operand, self.x, fixup_optional);
+            }
+            (AddrModeType::Absy, 3) => {
+                self.operand |= (self.fetch_byte(bus) as u16) << 8;
+                self.index_effective_addr(self.operand, self.y, fixup_optional);
+            }
+            (AddrModeType::Zpx, 3) => {
+                bus.read(self.operand);
+                self.effective_addr = (self.operand as u8).wrapping_add(self.x) as u16;
+            }
+            (AddrModeType::Zpy, 3) => {
+                bus.read(self.operand);
+                self.effective_addr = (self.operand as u8).wrapping_add(self.y) as u16;
+            }
+            (AddrModeType::Indx, 3) => {
+                bus.read(self.operand);
+            }
+            (AddrModeType::Indx, 4) => {
+                let ptr = (self.operand as u8).wrapping_add(self.x);
+                self.effective_addr = self.read_latched(bus, ptr as u16) as u16;
+            }
+            (AddrModeType::Indx, 5) => {
+                let ptr = (self.operand as u8).wrapping_add(self.x).wrapping_add(1);
+                self.effective_addr |= (self.read_latched(bus, ptr as u16) as u16) << 8;
+            }
+            (AddrModeType::Indy, 3) => {
+                self.effective_addr = self.read_latched(bus, self.operand) as u16;
+            }
+            (AddrModeType::Indy, 4) => {
+                let ptr = (self.operand as u8).wrapping_add(1);
+                let base = self.effective_addr | (self.read_latched(bus, ptr as u16) as u16) << 8;
+                self.index_effective_addr(base, self.y, fixup_optional);
+            }
+            (AddrModeType::Indzp, 3) => {
+                self.effective_addr = self.read_latched(bus, self.operand) as u16;
+            }
+            (AddrModeType::Indzp, 4) => {
+                let ptr = (self.operand as u8).wrapping_add(1);
+                self.effective_addr |= (self.read_latched(bus, ptr as u16) as u16) << 8;
+            }
+            _ if self.variant == CpuVariant::Wdc65C02 && self.page_crossed => {
+                //Read the last instruction byte while the page is fixed
+                bus.read(self.pc.wrapping_sub(1));
+            }
+            _ => {
+                //Read from the address before the page crossing is fixed
+                let unfixed = self
+                    .effective_addr
+                    .wrapping_sub(0x100 * self.page_crossed as u16);
+                bus.read(unfixed);
+            }
+        }
+    }
+
+    fn branch_cycle<B: Bus>(&mut self, opcode: u8, cycle: u8, bus: &mut B) -> bool {
+        match cycle {
+            2 => {
+                self.operand = self.fetch_byte(bus) as u16;
+                let cycles_fn = self.decode(opcode).2;
+                let cycles = cycles_fn(self, &self._rel(self.operand as u8));
+                self.current_instruction.remaining_cycles = cycles - 2;
+            }
+            3 => {
+                bus.read(self.pc);
+            }
+            _ => {
+                let target = self._rel(self.operand as u8).addr.unwrap();
+                bus.read((self.pc & 0xff00) | (target & 0xff));
+            }
+        }
+
+        if self.current_instruction.remaining_cycles > 0 {
+            return false;
+        }
+
+        self.execute(opcode, bus, true);
+        true
+    }
+
+    fn jump_cycle<B: Bus>(&mut self, opcode: u8, cycle: u8, bus: &mut B) -> bool {
+        let mode = self.decode(opcode).1;
+        let nmos = self.variant != CpuVariant::Wdc65C02;
+        let ptr = match mode {
+            AddrModeType::Indabsx => self.operand.wrapping_add(self.x as u16),
+            _ => self.operand,
+        };
+
+        match (mode, cycle) {
+            (_, 2) => {
+                self.operand = self.fetch_byte(bus) as u16;
+            }
+            (_, 3) => {
+                self.operand |= (self.fetch_byte(bus) as u16) << 8;
+                if mode == AddrModeType::Abs {
+                    self.execute(opcode, bus, true);
+                    return true;
+                }
+            }
+            (AddrModeType::Ind, 4) if nmos => {
+                self.read_latched(bus, self.operand);
+            }
+            (AddrModeType::Ind, _) if nmos => {
+                //The high byte is read without carrying into the page
+                let high_byte_addr =
+                    (self.operand & 0xff00) | (self.operand as u8).wrapping_add(1) as u16;
+                self.read_latched(bus, high_byte_addr);
+                self.execute(opcode, bus, true);
+                return true;
+            }
+            (_, 4) => {
+                //Read the last instruction byte while the pointer is formed
+                bus.read(self.pc.wrapping_sub(1));
+            }
+            (_, 5) => {
+                self.read_latched(bus, ptr);
+            }
+            _ => {
+                self.read_latched(bus, ptr.wrapping_add(1));
+                self.execute(opcode, bus, true);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn jsr_cycle<B: Bus>(&mut self, opcode: u8, cycle: u8, bus: &mut B) -> bool {
+        match cycle {
+            2 => self.operand = self.fetch_byte(bus) as u16,
+            3 => {
+                bus.read(0x100 + self.sp as u16);
+            }
+            4 => bus.write(0x100 + self.sp as u16, (self.pc >> 8) as u8),
+            5 => bus.write(0x100 + self.sp.wrapping_sub(1) as u16, self.pc as u8),
+            _ => {
+                self.operand |= (self.fetch_byte(bus) as u16) << 8;
+                self.execute(opcode, bus, false);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn rts_cycle<B: Bus>(&mut self, opcode: u8, cycle: u8, bus: &mut B) -> bool {
+        match cycle {
+            2 => {
+                bus.read(self.pc);
+            }
+            3 => {
+                bus.read(0x100 + self.sp as u16);
+            }
+            4 | 5 => {
+                let offset = cycle - 3;
+                self.read_latched(bus, 0x100 + self.sp.wrapping_add(offset) as u16);
+            }
+            _ => {
+                self.execute(opcode, bus, false);
+                //Read the last byte of the JSR while the PC is incremented
+                bus.read(self.pc.wrapping_sub(1));
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn rti_cycle<B: Bus>(&mut self, opcode: u8, cycle: u8, bus: &mut B) -> bool {
+        match cycle {
+            2 => {
+                bus.read(self.pc);
+            }
+            3 => {
+                bus.read(0x100 + self.sp as u16);
+            }
+            4 => {
+                //The flags are restored in time for the interrupt poll
+                let status = self.read_latched(bus, 0x100 + self.sp.wrapping_add(1) as u16);
+                self.set_status_byte(status);
+            }
+            5 => {
+                self.read_latched(bus, 0x100 + self.sp.wrapping_add(2) as u16);
+            }
+            _ => {
+                self.read_latched(bus, 0x100 + self.sp.wrapping_add(3) as u16);
+                self.execute(opcode, bus, false);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn brk_cycle<B: Bus>(&mut self, opcode: u8, cycle: u8, bus: &mut B) -> bool {
+        match cycle {
+            2 => self.operand = self.fetch_byte(bus) as u16,
+            3 => bus.write(0x100 + self.sp as u16, (self.pc >> 8) as u8),
+            4 => bus.write(0x100 + self.sp.wrapping_sub(1) as u16, self.pc as u8),
+            5 => {
+                bus.write(
+                    0x100 + self.sp.wrapping_sub(2) as u16,
+                    self.get_status_byte(true),
+                );
+                self.hijack_by_nmi();
+            }
+            6 => {
+                self.read_latched(bus, NESCPU::IRQ_VECTOR);
+            }
+            _ => {
+                self.read_latched(bus, NESCPU::IRQ_VECTOR + 1);
+                self.execute(opcode, bus, false);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn push_cycle<B: Bus>(&mut self, opcode: u8, cycle: u8, bus: &mut B) -> bool {
+        if cycle == 2 {
+            bus.read(self.pc);
+            return false;
+        }
+
+        self.execute(opcode, bus, true);
+        true
+    }
+
+    fn pull_cycle<B: Bus>(&mut self, opcode: u8, cycle: u8, bus: &mut B) -> bool {
+        match cycle {
+            2 => {
+                bus.read(self.pc);
+            }
+            3 => {
+                bus.read(0x100 + self.sp as u16);
+            }
+            _ => {
+                self.read_latched(bus, 0x100 + self.sp.wrapping_add(1) as u16);
+                self.execute(opcode, bus, false);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn bit_branch_cycle<B: Bus>(&mut self, opcode: u8, cycle: u8, bus: &mut B) -> bool {
+        match cycle {
+            2 => self.operand = self.fetch_byte(bus) as u16,
+            3 => {
+                self.read_latched(bus, self.operand);
+            }
+            4 => {
+                bus.read(self.operand);
+            }
+            5 => {
+                self.operand |= (self.fetch_byte(bus) as u16) << 8;
+
+                let (_, mode, cycles_fn, _) = self.decode(opcode);
+                let reads = self.reads;
+                let replay = ReplayBus {
+                    bus,
+                    reads: &reads[..self.reads_len],
+                    forward_writes: false,
+                };
+                let cycles = cycles_fn(self, &self.addr_mode(mode, self.operand, &replay));
+                self.current_instruction.remaining_cycles = cycles - 5;
+            }
+            6 => {
+                bus.read(self.pc);
+            }
+            _ => {
+                let target = self._rel((self.operand >> 8) as u8).addr.unwrap();
+                bus.read((self.pc & 0xff00) | (target & 0xff));
+            }
+        }
+
+        if self.current_instruction.remaining_cycles > 0 {
+            return false;
+        }
+
+        self.execute(opcode, bus, true);
+        true
+    }
+
+    //WAI and STP, which leave the CPU waiting for an interrupt or reset
+    fn stop_cycle<B: Bus>(&mut self, opcode: u8, access: Access, cycle: u8, bus: &mut B) -> bool {
+        bus.read(self.pc);
+        if cycle < 3 {
+            return false;
+        }
+
+        self.execute(opcode, bus, true);
+        if access == Access::Stop {
+            self.jam(opcode, self.pc.wrapping_sub(1), cycle);
+        } else {
+            self.current_instruction = CurrentInstruction {
+                cycle,
+                remaining_cycles: 0xff,
+                instruction_type: InstructionType::Wait,
+            };
+        }
+        false
+    }
+
+    fn interrupt_cycle<B: Bus>(&mut self, cycle: u8, vector: u16, bus: &mut B) -> bool {
+        match cycle {
+            1 => {
+                self.reads_len = 0;
+                bus.read(self.pc);
+            }
+            2 => {
+                bus.read(self.pc);
+            }
+            3 => bus.write(0x100 + self.sp as u16, (self.pc >> 8) as u8),
+            4 => bus.write(0x100 + self.sp.wrapping_sub(1) as u16, self.pc as u8),
+            5 => {
+                bus.write(
+                    0x100 + self.sp.wrapping_sub(2) as u16,
+                    self.get_status_byte(false),
+                );
+                if vector == NESCPU::IRQ_VECTOR {
+                    self.hijack_by_nmi();
+                }
+            }
+            6 => {
+                self.read_latched(bus, vector);
+            }
+            _ => {
+                self.read_latched(bus, vector + 1);
+
+                let reads = self.reads;
+                let mut bus = ReplayBus {
+                    bus,
+                    reads: &reads[..self.reads_len],
+                    forward_writes: false,
+                };
+                match vector {
+                    NESCPU::NMI_VECTOR => self.nmi(&mut bus),
+                    _ => self.irq(&mut bus),
+                }
+                return true;
+            }
+        }
+
+        false
+    }
+
+    //An NMI that arrives before BRK or an IRQ fetches its vector takes
+    //over the rest of the sequence, which then loads the NMI vector. The
+    //status already pushed (with B set for BRK) is left as it is.
+    fn hijack_by_nmi(&mut self) {
+        if self.pending_nmi {
+            self.current_instruction.instruction_type = InstructionType::Nmi;
+        }
+    }
+
+    //A taken branch that stays on the same page does not poll on its
+    //second cycle, so an interrupt is only seen after the next instruction
+    pub(in crate::cpu) fn polls_interrupts(&self) -> bool {
+        !matches!(
+            self.current_instruction,
+            CurrentInstruction {
+                cycle: 2,
+                remaining_cycles: 1,
+                instruction_type: InstructionType::Instruction {
+                    access: Access::Branch,
+                    ..
+                },
+            }
+        )
+    }
+
+    fn reset_cycle<B: Bus>(&mut self, cycle: u8, bus: &mut B) -> bool {
+        match cycle {
+            1 => {
+                self.reads_len = 0;
+                bus.read(self.pc);
+            }
+            2 => {
+                bus.read(self.pc);
+            }
+            //Pushes are turned into reads while reset is held
+            3..=5 => {
+                bus.read(0x100 + self.sp.wrapping_sub(cycle - 3) as u16);
+            }
+            6 => {
+                self.read_latched(bus, NESCPU::RESET_VECTOR);
+            }
+            _ => {
+                self.read_latched(bus, NESCPU::RESET_VECTOR + 1);
+
+                let reads = self.reads;
+                let bus = ReplayBus {
+                    bus,
+                    reads: &reads[..self.reads_len],
+                    forward_writes: false,
+                };
+                self.reset(&bus);
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod cycles_tests {
+    use std::cell::RefCell;
+
+    use crate::cpu::CPU;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    enum BusAccess {
+        Read(u16),
+        Write(u16, u8),
+    }
+
+    struct RecordingBus {
+        memory: Vec<u8>,
+        accesses: RefCell<Vec<BusAccess>>,
+    }
+
+    impl RecordingBus {
+        fn new(program: &[u8]) -> RecordingBus {
+            let mut memory = vec![0x0; 0x10000];
+            memory[0x8000..0x8000 + program.len()].copy_from_slice(program);
+
+            RecordingBus {
+                memory,
+                accesses: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl Bus for RecordingBus {
+        fn read(&self, addr: u16) -> u8 {
+            self.accesses.borrow_mut().push(BusAccess::Read(addr));
+            self.memory[addr as usize]
+        }
+
+        fn write(&mut self, addr: u16, data: u8) {
+            self.accesses
+                .borrow_mut()
+                .push(BusAccess::Write(addr, data));
+            self.memory[addr as usize] = data;
+        }
+
+        fn peek(&self, addr: u16) -> u8 {
+            self.memory[addr as usize]
+        }
+    }
+
+    fn cpu_at_instruction_boundary() -> NESCPU {
+        let mut cpu = NESCPU::new();
+        cpu.pc = 0x8000;
+        cpu.sp = 0xfd;
+        cpu.current_instruction = CurrentInstruction {
+            cycle: 0,
+            remaining_cycles: 0,
+            instruction_type: InstructionType::Fetch,
+        };
+
+        cpu
+    }
+
+    //Runs one instruction, checking every cycle made exactly one access
+    fn run_instruction(cpu: &mut NESCPU, bus: &mut RecordingBus) -> Vec<BusAccess> {
+        bus.accesses.borrow_mut().clear();
+        let mut cycles = 0;
+
+        loop {
+            cpu.clock(bus);
+            cycles += 1;
+            assert_eq!(cycles, bus.accesses.borrow().len());
+
+            if cpu.current_instruction.cycle == 0 {
+                return bus.accesses.borrow().clone();
+            }
+        }
+    }
+
+    #[test]
+    fn test_implied_dummy_read() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xe8]); //INX
+
+        assert_eq!(
+            vec![BusAccess::Read(0x8000), BusAccess::Read(0x8001)],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x1, cpu.x);
+    }
+
+    #[test]
+    fn test_zpx_dummy_read_of_base_address() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xb5, 0xff]); //LDA $FF,X
+        bus.memory[0x4] = 0x42;
+        cpu.x = 0x5;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x00ff),
+                BusAccess::Read(0x0004)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x42, cpu.a);
+    }
+
+    #[test]
+    fn test_absx_read_without_page_cross() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xbd, 0x00, 0x20]); //LDA $2000,X
+        cpu.x = 0x10;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x2010)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+    }
+
+    #[test]
+    fn test_absx_read_page_cross_dummy_read() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xbd, 0xf0, 0x20]); //LDA $20F0,X
+        bus.memory[0x2110] = 0x42;
+        cpu.x = 0x20;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x2010),
+                BusAccess::Read(0x2110)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x42, cpu.a);
+    }
+
+    #[test]
+    fn test_absx_write_always_dummy_reads() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x9d, 0x00, 0x20]); //STA $2000,X
+        cpu.a = 0x42;
+        cpu.x = 0x1;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x2001),
+                BusAccess::Write(0x2001, 0x42)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+    }
+
+    #[test]
+    fn test_indy_read_page_cross_dummy_read() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xb1, 0x10]); //LDA ($10),Y
+        bus.memory[0x10] = 0xff;
+        bus.memory[0x11] = 0x20;
+        bus.memory[0x2100] = 0x42;
+        cpu.y = 0x1;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x0010),
+                BusAccess::Read(0x0011),
+                BusAccess::Read(0x2000),
+                BusAccess::Read(0x2100)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x42, cpu.a);
+    }
+
+    #[test]
+    fn test_indx_dummy_read_of_pointer() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xa1, 0x10]); //LDA ($10,X)
+        bus.memory[0x12] = 0x34;
+        bus.memory[0x13] = 0x12;
+        bus.memory[0x1234] = 0x42;
+        cpu.x = 0x2;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x0010),
+                BusAccess::Read(0x0012),
+                BusAccess::Read(0x0013),
+                BusAccess::Read(0x1234)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x42, cpu.a);
+    }
+
+    #[test]
+    fn test_read_modify_write_dummy_write() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xe6, 0x10]); //INC $10
+        bus.memory[0x10] = 0x5;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x0010),
+                BusAccess::Write(0x0010, 0x5),
+                BusAccess::Write(0x0010, 0x6)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+    }
+
+    #[test]
+    fn test_read_modify_write_absx() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x1e, 0x00, 0x20]); //ASL $2000,X
+        bus.memory[0x2001] = 0x41;
+        cpu.x = 0x1;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x2001),
+                BusAccess::Read(0x2001),
+                BusAccess::Write(0x2001, 0x41),
+                BusAccess::Write(0x2001, 0x82)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+    }
+
+    #[test]
+    fn test_branch_taken_page_cross() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xd0, 0x80]); //BNE -128
+        cpu.z = false;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x8082)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x7f82, cpu.pc);
+    }
+
+    #[test]
+    fn test_branch_not_taken() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xd0, 0x80]); //BNE -128
+        cpu.z = true;
+
+        assert_eq!(
+            vec![BusAccess::Read(0x8000), BusAccess::Read(0x8001)],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x8002, cpu.pc);
+    }
+
+    #[test]
+    fn test_jsr_and_rts() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x20, 0x00, 0x90]); //JSR $9000
+        bus.memory[0x9000] = 0x60; //RTS
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x01fd),
+                BusAccess::Write(0x01fd, 0x80),
+                BusAccess::Write(0x01fc, 0x02),
+                BusAccess::Read(0x8002)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x9000, cpu.pc);
+        assert_eq!(0xfb, cpu.sp);
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x9000),
+                BusAccess::Read(0x9001),
+                BusAccess::Read(0x01fb),
+                BusAccess::Read(0x01fc),
+                BusAccess::Read(0x01fd),
+                BusAccess::Read(0x8002)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x8003, cpu.pc);
+        assert_eq!(0xfd, cpu.sp);
+    }
+
+    #[test]
+    fn test_brk_and_rti() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x00, 0x00]); //BRK
+        bus.memory[0xfffe] = 0x00;
+        bus.memory[0xffff] = 0x90;
+        bus.memory[0x9000] = 0x40; //RTI
+        cpu.i = false;
+        cpu.c = true;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Write(0x01fd, 0x80),
+                BusAccess::Write(0x01fc, 0x02),
+                BusAccess::Write(0x01fb, 0x31),
+                BusAccess::Read(0xfffe),
+                BusAccess::Read(0xffff)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x9000, cpu.pc);
+        assert_eq!(true, cpu.i);
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x9000),
+                BusAccess::Read(0x9001),
+                BusAccess::Read(0x01fa),
+                BusAccess::Read(0x01fb),
+                BusAccess::Read(0x01fc),
+                BusAccess::Read(0x01fd)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x8002, cpu.pc);
+        assert_eq!(0xfd, cpu.sp);
+        assert_eq!(false, cpu.i);
+        assert_eq!(true, cpu.c);
+    }
+
+    #[test]
+    fn test_pha_and_pla() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x48, 0x68]); //PHA, PLA
+        cpu.a = 0x42;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Write(0x01fd, 0x42)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+
+        cpu.a = 0x0;
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x01fc),
+                BusAccess::Read(0x01fd)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x42, cpu.a);
+    }
+
+    #[test]
+    fn test_jmp_indirect_page_wrap() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x6c, 0xff, 0x20]); //JMP ($20FF)
+        bus.memory[0x20ff] = 0x34;
+        bus.memory[0x2000] = 0x12;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x20ff),
+                BusAccess::Read(0x2000)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x1234, cpu.pc);
+    }
+
+    #[test]
+    fn test_nmi_sequence() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xea]); //NOP
+        bus.memory[0xfffa] = 0x00;
+        bus.memory[0xfffb] = 0x90;
+
+        cpu.cpu_nmi();
+        run_instruction(&mut cpu, &mut bus);
+        assert_eq!(
+            InstructionType::Nmi,
+            cpu.current_instruction.instruction_type
+        );
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8001),
+                BusAccess::Write(0x01fd, 0x80),
+                BusAccess::Write(0x01fc, 0x01),
+                BusAccess::Write(0x01fb, 0x24),
+                BusAccess::Read(0xfffa),
+                BusAccess::Read(0xfffb)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x9000, cpu.pc);
+        assert_eq!(false, cpu.pending_nmi);
+    }
+
+    #[test]
+    fn test_taken_branch_without_page_cross_delays_irq() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xf0, 0x00, 0xea]); //BEQ $8002, NOP
+        cpu.i = false;
+        cpu.z = true;
+
+        //Asserted in time for the poll before the operand fetch
+        cpu.cpu_irq(true);
+        run_instruction(&mut cpu, &mut bus);
+        assert_eq!(
+            InstructionType::Irq,
+            cpu.current_instruction.instruction_type
+        );
+
+        //Asserted after it, when a three cycle instruction would still poll
+        let mut cpu = cpu_at_instruction_boundary();
+        cpu.i = false;
+        cpu.z = true;
+
+        cpu.clock(&mut bus);
+        cpu.cpu_irq(true);
+        cpu.clock(&mut bus);
+        cpu.clock(&mut bus);
+        assert_eq!(
+            InstructionType::Fetch,
+            cpu.current_instruction.instruction_type
+        );
+
+        run_instruction(&mut cpu, &mut bus);
+        assert_eq!(
+            InstructionType::Irq,
+            cpu.current_instruction.instruction_type
+        );
+    }
+
+    #[test]
+    fn test_cli_takes_irq_after_next_instruction() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x58, 0xea]); //CLI, NOP
+        cpu.i = true;
+        cpu.cpu_irq(true);
+
+        run_instruction(&mut cpu, &mut bus);
+        assert_eq!(
+            InstructionType::Fetch,
+            cpu.current_instruction.instruction_type
+        );
+
+        run_instruction(&mut cpu, &mut bus);
+        assert_eq!(
+            InstructionType::Irq,
+            cpu.current_instruction.instruction_type
+        );
+    }
+
+    #[test]
+    fn test_sei_still_takes_pending_irq() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x78]); //SEI
+        cpu.i = false;
+        cpu.cpu_irq(true);
+
+        run_instruction(&mut cpu, &mut bus);
+        assert_eq!(
+            InstructionType::Irq,
+            cpu.current_instruction.instruction_type
+        );
+    }
+
+    #[test]
+    fn test_nmi_hijacks_brk() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x00, 0x00]); //BRK
+        bus.memory[0xfffa] = 0x00;
+        bus.memory[0xfffb] = 0xa0;
+        bus.memory[0xfffe] = 0x00;
+        bus.memory[0xffff] = 0x90;
+
+        for _ in 0..4 {
+            cpu.clock(&mut bus);
+        }
+        cpu.cpu_nmi();
+        for _ in 0..3 {
+            cpu.clock(&mut bus);
+        }
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Write(0x01fd, 0x80),
+                BusAccess::Write(0x01fc, 0x02),
+                BusAccess::Write(0x01fb, 0x34),
+                BusAccess::Read(0xfffa),
+                BusAccess::Read(0xfffb)
+            ],
+            *bus.accesses.borrow()
+        );
+        assert_eq!(0xa000, cpu.pc);
+        assert_eq!(false, cpu.pending_nmi);
+        assert_eq!(true, cpu.i);
+    }
+
+    #[test]
+    fn test_nmi_late_in_brk_waits_for_handler_instruction() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x00, 0x00]); //BRK
+        bus.memory[0xfffe] = 0x00;
+        bus.memory[0xffff] = 0x90;
+        bus.memory[0x9000] = 0xea; //NOP
+
+        for _ in 0..5 {
+            cpu.clock(&mut bus);
+        }
+        cpu.cpu_nmi();
+        for _ in 0..2 {
+            cpu.clock(&mut bus);
+        }
+
+        assert_eq!(0x9000, cpu.pc);
+        assert_eq!(
+            InstructionType::Fetch,
+            cpu.current_instruction.instruction_type
+        );
+
+        run_instruction(&mut cpu, &mut bus);
+        assert_eq!(
+            InstructionType::Nmi,
+            cpu.current_instruction.instruction_type
+        );
+    }
+
+    #[test]
+    fn test_nmi_hijacks_irq() {
+        let mut cpu = cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xea]); //NOP
+        bus.memory[0xfffa] = 0x00;
+        bus.memory[0xfffb] = 0xa0;
+        cpu.i = false;
+
+        cpu.cpu_irq(true);
+        run_instruction(&mut cpu, &mut bus);
+        bus.accesses.borrow_mut().clear();
+
+        for _ in 0..3 {
+            cpu.clock(&mut bus);
+        }
+        cpu.cpu_nmi();
+        for _ in 0..4 {
+            cpu.clock(&mut bus);
+        }
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8001),
+                BusAccess::Write(0x01fd, 0x80),
+                BusAccess::Write(0x01fc, 0x01),
+                BusAccess::Write(0x01fb, 0x20),
+                BusAccess::Read(0xfffa),
+                BusAccess::Read(0xfffb)
+            ],
+            *bus.accesses.borrow()
+        );
+        assert_eq!(0xa000, cpu.pc);
+        assert_eq!(false, cpu.pending_nmi);
+    }
+
+    #[test]
+    fn test_reset_sequence_does_not_write() {
+        let mut cpu = NESCPU::new();
+        let mut bus = RecordingBus::new(&[]);
+        bus.memory[0xfffc] = 0x00;
+        bus.memory[0xfffd] = 0x80;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x0000),
+                BusAccess::Read(0x0000),
+                BusAccess::Read(0x01ff),
+                BusAccess::Read(0x01fe),
+                BusAccess::Read(0x01fd),
+                BusAccess::Read(0xfffc),
+                BusAccess::Read(0xfffd)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x8000, cpu.pc);
+    }
+
+    #[test]
+    fn test_cycle_counts_match_lookup_table() {
+        for opcode in 0x0..=0xff_u8 {
+            if <NESCPU as LookupTable>::LOOKUP_TABLE[opcode as usize].0 == "JAM" {
+                continue;
+            }
+
+            for (index, flags) in [(0x00, 0x00), (0xff, 0xff), (0x80, 0x55), (0x7f, 0xaa)] {
+                let mut cpu = cpu_at_instruction_boundary();
+                let mut bus = RecordingBus::new(&[opcode, 0xf0, 0x20]);
+                bus.memory[0xf0] = 0x80;
+                bus.memory[0xf1] = 0x20;
+                cpu.x = index;
+                cpu.y = index;
+                cpu.set_status_byte(flags);
+
+                cpu.trace_next_instruction(&bus);
+                let expected = cpu.cycles_remaining() as usize;
+
+                assert_eq!(
+                    expected,
+                    run_instruction(&mut cpu, &mut bus).len(),
+                    "opcode {:02X}",
+                    opcode
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_replay_bus_panics_on_unrecorded_read() {
+        let mut bus = RecordingBus::new(&[]);
+        let replay = ReplayBus {
+            bus: &mut bus,
+            reads: &[(0x2002, 0x80)],
+            forward_writes: true,
+        };
+
+        replay.read(0x2007);
+    }
+
+    #[test]
+    fn test_execute_only_reads_recorded_addresses() {
+        for variant in [CpuVariant::Ricoh2A03, CpuVariant::Wdc65C02] {
+            for opcode in 0x0..=0xff_u8 {
+                let mut cpu = cpu_at_instruction_boundary();
+                cpu.variant = variant;
+                if matches!(cpu.decode(opcode).0, "JAM" | "STP" | "WAI") {
+                    continue;
+                }
+
+                for (index, flags) in [(0x00, 0x00), (0xff, 0xff), (0x80, 0x55), (0x7f, 0xaa)] {
+                    let mut cpu = cpu_at_instruction_boundary();
+                    cpu.variant = variant;
+                    let mut bus = RecordingBus::new(&[opcode, 0xf0, 0x20]);
+                    bus.memory[0xf0] = 0x80;
+                    bus.memory[0xf1] = 0x20;
+                    cpu.x = index;
+                    cpu.y = index;
+                    cpu.set_status_byte(flags);
+
+                    //Any read the execute function makes that wasn't made
+                    //on an earlier cycle panics, or shows up as an extra
+                    //access on the cycle it ran
+                    run_instruction(&mut cpu, &mut bus);
+                }
+            }
+        }
+    }
+
+    fn cmos_cpu_at_instruction_boundary() -> NESCPU {
+        let mut cpu = cpu_at_instruction_boundary();
+        cpu.variant = CpuVariant::Wdc65C02;
+
+        cpu
+    }
+
+    #[test]
+    fn test_65c02_jmp_indirect_no_page_wrap() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x6c, 0xff, 0x20]); //JMP ($20FF)
+        bus.memory[0x20ff] = 0x34;
+        bus.memory[0x2100] = 0x12;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x20ff),
+                BusAccess::Read(0x2100)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x1234, cpu.pc);
+    }
+
+    #[test]
+    fn test_65c02_jmp_absolute_indexed_indirect() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x7c, 0x00, 0x20]); //JMP ($2000,X)
+        bus.memory[0x2004] = 0x34;
+        bus.memory[0x2005] = 0x12;
+        cpu.x = 0x4;
+
+        assert_eq!(6, run_instruction(&mut cpu, &mut bus).len());
+        assert_eq!(0x1234, cpu.pc);
+    }
+
+    #[test]
+    fn test_65c02_zero_page_indirect() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xb2, 0x10]); //LDA ($10)
+        bus.memory[0x10] = 0x34;
+        bus.memory[0x11] = 0x12;
+        bus.memory[0x1234] = 0x42;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x0010),
+                BusAccess::Read(0x0011),
+                BusAccess::Read(0x1234)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x42, cpu.a);
+    }
+
+    #[test]
+    fn test_65c02_read_modify_write_dummy_read() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xe6, 0x10]); //INC $10
+        bus.memory[0x10] = 0x5;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x0010),
+                BusAccess::Read(0x0010),
+                BusAccess::Write(0x0010, 0x6)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+    }
+
+    #[test]
+    fn test_65c02_page_cross_reads_last_instruction_byte() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xbd, 0xf0, 0x20]); //LDA $20F0,X
+        cpu.x = 0x20;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x2110)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+    }
+
+    #[test]
+    fn test_65c02_single_cycle_nop() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x03, 0xe8]); //NOP, INX
+
+        assert_eq!(
+            vec![BusAccess::Read(0x8000)],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x8001, cpu.pc);
+    }
+
+    #[test]
+    fn test_65c02_decimal_adc_extra_cycle() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x69, 0x01]); //ADC #$01
+        cpu.d = true;
+        cpu.a = 0x09;
+
+        assert_eq!(3, run_instruction(&mut cpu, &mut bus).len());
+        assert_eq!(0x10, cpu.a);
+    }
+
+    #[test]
+    fn test_nmos_decimal_adc() {
+        let mut cpu = cpu_at_instruction_boundary();
+        cpu.variant = CpuVariant::Nmos6502;
+        let mut bus = RecordingBus::new(&[0x69, 0x01]); //ADC #$01
+        cpu.d = true;
+        cpu.a = 0x09;
+
+        assert_eq!(2, run_instruction(&mut cpu, &mut bus).len());
+        assert_eq!(0x10, cpu.a);
+    }
+
+    #[test]
+    fn test_65c02_bit_branch_taken() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0x8f, 0x10, 0x10]); //BBS0 $10,$8013
+        bus.memory[0x10] = 0x01;
+
+        assert_eq!(
+            vec![
+                BusAccess::Read(0x8000),
+                BusAccess::Read(0x8001),
+                BusAccess::Read(0x0010),
+                BusAccess::Read(0x0010),
+                BusAccess::Read(0x8002),
+                BusAccess::Read(0x8003)
+            ],
+            run_instruction(&mut cpu, &mut bus)
+        );
+        assert_eq!(0x8013, cpu.pc);
+    }
+
+    #[test]
+    fn test_65c02_wai_resumes_on_masked_irq() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xcb, 0xe8]); //WAI, INX
+        cpu.i = true;
+
+        for _ in 0..10 {
+            cpu.clock(&mut bus);
+        }
+        assert_eq!(
+            InstructionType::Wait,
+            cpu.current_instruction.instruction_type
+        );
+        assert_eq!("WAIT", cpu.to_string());
+
+        cpu.cpu_irq(true);
+        cpu.clock(&mut bus);
+        assert_eq!(
+            InstructionType::Fetch,
+            cpu.current_instruction.instruction_type
+        );
+
+        run_instruction(&mut cpu, &mut bus);
+        assert_eq!(0x1, cpu.x);
+    }
+
+    #[test]
+    fn test_65c02_wai_services_irq() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xcb]); //WAI
+        cpu.i = false;
+
+        for _ in 0..5 {
+            cpu.clock(&mut bus);
+        }
+
+        cpu.cpu_irq(true);
+        cpu.clock(&mut bus);
+        assert_eq!(
+            InstructionType::Irq,
+            cpu.current_instruction.instruction_type
+        );
+    }
+
+    #[test]
+    fn test_65c02_stp_halts_until_reset() {
+        let mut cpu = cmos_cpu_at_instruction_boundary();
+        let mut bus = RecordingBus::new(&[0xdb]); //STP
+
+        cpu.cpu_nmi();
+        for _ in 0..10 {
+            cpu.clock(&mut bus);
+        }
+        assert_eq!(
+            InstructionType::Jam,
+            cpu.current_instruction.instruction_type
+        );
+        assert_eq!(0xdb, cpu.get_jam().unwrap().opcode);
+        assert_eq!(0x8000, cpu.get_jam().unwrap().pc);
+
+        cpu.cpu_reset();
+        assert_eq!(
+            InstructionType::Reset,
+            cpu.current_instruction.instruction_type
+        );
+    }
+
+    #[test]
+    fn test_65c02_replaces_every_undocumented_opcode() {
+        let documented = [
+            "ADC", "AND", "ASL", "BCC", "BCS", "BEQ", "BIT", "BMI", "BNE", "BPL", "BRK", "BVC",
+            "BVS", "CLC", "CLD", "CLI", "CLV", "CMP", "CPX", "CPY", "DEC", "DEX", "DEY", "EOR",
+            "INC", "INX", "INY", "JMP", "JSR", "LDA", "LDX", "LDY", "LSR", "NOP", "ORA", "PHA",
+            "PHP", "PLA", "PLP", "ROL", "ROR", "RTI", "RTS", "SBC", "SEC", "SED", "SEI", "STA",
+            "STX", "STY", "TAX", "TAY", "TSX", "TXA", "TXS", "TYA", "BRA", "PHX", "PHY", "PLX",
+            "PLY", "STZ", "TRB", "TSB", "WAI", "STP",
+        ];
+        let cpu = cmos_cpu_at_instruction_boundary();
+
+        for opcode in 0x0..=0xff_u8 {
+            let mnemonic = cpu.decode(opcode).0;
+            assert!(
+                documented.contains(&mnemonic)
+                    || ["RMB", "SMB", "BBR", "BBS"].contains(&&mnemonic[..3]),
+                "opcode {:02X} is {}",
+                opcode,
+                mnemonic
+            );
+        }
+    }
+
+    #[test]
+    fn test_65c02_cycle_counts_match_lookup_table() {
+        for opcode in 0x0..=0xff_u8 {
+            let mut cpu = cmos_cpu_at_instruction_boundary();
+            if matches!(cpu.decode(opcode).0, "WAI" | "STP") {
+                continue;
+            }
+
+            for (index, flags) in [(0x00, 0x00), (0xff, 0xff), (0x80, 0x55), (0x7f, 0xaa)] {
+                let mut bus = RecordingBus::new(&[opcode, 0xf0, 0x20]);
+                bus.memory[0xf0] = 0x80;
+                bus.memory[0xf1] = 0x20;
+                cpu = cmos_cpu_at_instruction_boundary();
+                cpu.x = index;
+                cpu.y = index;
+                cpu.set_status_byte(flags);
+
+                cpu.trace_next_instruction(&bus);
+                let expected = cpu.cycles_remaining() as usize;
+
+                assert_eq!(
+                    expected,
+                    run_instruction(&mut cpu, &mut bus).len(),
+                    "opcode {:02X}",
+                    opcode
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+/*
+    Static disassembler
+
+    Decodes a block of memory (such as a PRG ROM bank) without running
+    it, using the same lookup table and addressing mode functions as
+    the CPU. Operands are formatted as in the trace log.
+
+    Undocumented opcodes are marked with a '*' before the mnemonic, as
+    nestest does. Bytes at the end of the block that do not form a
+    whole instruction are listed as a .byte directive.
+*/
+
+use std::fmt;
+
+use crate::bus::Bus;
+
+use super::{addr::AddrModeResult, CpuVariant, NESCPU};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DisasmLine {
+    pub addr: u16,
+    pub bytes: Vec<u8>,
+    pub mnemonic: &'static str,
+    pub operand: String,
+    pub undocumented: bool,
+}
+
+impl fmt::Display for DisasmLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes: Vec<String> = self.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        let line = format!(
+            "{:04X}  {: <8} {}{} {}",
+            self.addr,
+            bytes.join(" "),
+            if self.undocumented { "*" } else { " " },
+            self.mnemonic,
+            self.operand
+        );
+
+        write!(f, "{}", line.trim_end())
+    }
+}
+
+//Operands are only formatted, so pointers are never followed
+struct NullBus;
+
+impl Bus for NullBus {
+    fn read(&self, _addr: u16) -> u8 {
+        0x0
+    }
+
+    fn write(&mut self, _addr: u16, _data: u8) {}
+
+    fn peek(&self, _addr: u16) -> u8 {
+        0x0
+    }
+}
+
+pub fn disassemble(bytes: &[u8], base_addr: u16, variant: CpuVariant) -> Vec<DisasmLine> {
+    let mut cpu = NESCPU::with_variant(variant);
+    let mut lines = vec![];
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        let addr = base_addr.wrapping_add(offset as u16);
+        let opcode = bytes[offset];
+        let (mnemonic, mode, _, _) = cpu.decode(opcode);
+        let length = AddrModeResult::unresolved(mode).bytes as usize;
+
+        if offset + length > bytes.len() {
+            let operands: Vec<String> = bytes[offset..]
+                .iter()
+                .map(|b| format!("${:02X}", b))
+                .collect();
+            lines.push(DisasmLine {
+                addr,
+                bytes: bytes[offset..].to_vec(),
+                mnemonic: ".byte",
+                operand: operands.join(","),
+                undocumented: false,
+            });
+            break;
+        }
+
+        let operand = match length {
+            1 => 0,
+            2 => bytes[offset + 1] as u16,
+            _ => (bytes[offset + 2] as u16) << 8 | bytes[offset + 1] as u16,
+        };
+
+        //Relative targets are computed from the address of the next instruction
+        cpu.pc = addr.wrapping_add(length as u16);
+        lines.push(DisasmLine {
+            addr,
+            bytes: bytes[offset..offset + length].to_vec(),
+            mnemonic,
+            operand: cpu.addr_mode(mode, operand, &NullBus).repr(),
+            undocumented: is_undocumented(variant, opcode),
+        });
+
+        offset += length;
+    }
+
+    lines
+}
+
+pub fn is_undocumented(variant: CpuVariant, opcode: u8) -> bool {
+    match variant {
+        //Every 65C02 opcode is defined, but only 0xEA is documented as a NOP
+        CpuVariant::Wdc65C02 => NESCPU::lookup(variant, opcode).0 == "NOP" && opcode != 0xea,
+        CpuVariant::Ricoh2A03 | CpuVariant::Nmos6502 => {
+            matches!(
+                NESCPU::lookup(variant, opcode).0,
+                "ANC"
+                    | "ARR"
+                    | "ASR"
+                    | "DCP"
+                    | "ISC"
+                    | "JAM"
+                    | "LAS"
+                    | "LAX"
+                    | "RLA"
+                    | "RRA"
+                    | "SAX"
+                    | "SBX"
+                    | "SHA"
+                    | "SHS"
+                    | "SHX"
+                    | "SHY"
+                    | "SLO"
+                    | "SRE"
+                    | "XAA"
+            ) || (NESCPU::lookup(variant, opcode).0 == "NOP" && opcode != 0xea)
+                || opcode == 0xeb
+        }
+    }
+}
+
+#[cfg(test)]
+mod disasm_tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble_addressing_modes() {
+        let bytes = [
+            0x78, //SEI
+            0xa9, 0x10, //LDA #$10
+            0x8d, 0x00, 0x20, //STA $2000
+            0xb5, 0x33, //LDA $33,X
+            0xb1, 0x40, //LDA ($40),Y
+            0x6c, 0xfc, 0xff, //JMP ($FFFC)
+            0x10, 0xfe, //BPL $800F
+        ];
+
+        let lines: Vec<String> = disassemble(&bytes, 0x8000, CpuVariant::Ricoh2A03)
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+
+        assert_eq!(
+            vec![
+                "8000  78        SEI",
+                "8001  A9 10     LDA #$10",
+                "8003  8D 00 20  STA $2000",
+                "8006  B5 33     LDA $33,X",
+                "8008  B1 40     LDA ($40),Y",
+                "800A  6C FC FF  JMP ($FFFC)",
+                "800D  10 FE     BPL $800D",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn test_disassemble_line_fields() {
+        assert_eq!(
+            vec![DisasmLine {
+                addr: 0xc000,
+                bytes: vec![0x4c, 0xf5, 0xc5],
+                mnemonic: "JMP",
+                operand: "$C5F5".to_owned(),
+                undocumented: false
+            }],
+            disassemble(&[0x4c, 0xf5, 0xc5], 0xc000, CpuVariant::Ricoh2A03)
+        );
+    }
+
+    #[test]
+    fn test_disassemble_marks_undocumented_opcodes() {
+        let lines = disassemble(
+            &[0xa7, 0x10, 0xea, 0x1a, 0xeb, 0x00],
+            0x8000,
+            CpuVariant::Ricoh2A03,
+        );
+
+        assert_eq!("8000  A7 10    *LAX $10", lines[0].to_string());
+        assert_eq!(false, lines[1].undocumented);
+        assert_eq!(true, lines[2].undocumented);
+        assert_eq!("8004  EB 00    *SBC #$00", lines[3].to_string());
+    }
+
+    #[test]
+    fn test_disassemble_65c02() {
+        let lines = disassemble(&[0x1a, 0x0f, 0x10, 0x02], 0x8000, CpuVariant::Wdc65C02);
+
+        assert_eq!("8000  1A        INC A", lines[0].to_string());
+        assert_eq!("8001  0F 10 02  BBR0 $10,$8006", lines[1].to_string());
+    }
+
+    #[test]
+    fn test_disassemble_truncated_instruction() {
+        let lines = disassemble(&[0xea, 0x4c, 0x00], 0xfffd, CpuVariant::Ricoh2A03);
+
+        assert_eq!(2, lines.len());
+        assert_eq!("FFFE  4C 00     .byte $4C,$00", lines[1].to_string());
+    }
+}
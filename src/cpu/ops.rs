@@ -4,6 +4,8 @@ mod and;
 mod arr; //Undocumented
 mod asl;
 mod asr; //Undocumented
+mod bbr; //65C02
+mod bbs; //65C02
 mod bcc;
 mod bcs;
 mod beq;
@@ -11,6 +13,7 @@ mod bit;
 mod bmi;
 mod bne;
 mod bpl;
+mod bra; //65C02
 mod brk;
 mod bvc;
 mod bvs;
@@ -43,9 +46,14 @@ mod nop;
 mod ora;
 mod pha;
 mod php;
+mod phx; //65C02
+mod phy; //65C02
 mod pla;
 mod plp;
+mod plx; //65C02
+mod ply; //65C02
 mod rla; //Undocumented
+mod rmb; //65C02
 mod rol;
 mod ror;
 mod rra; //Undocumented
@@ -62,16 +70,22 @@ mod shs; //Undocumented
 mod shx; //Undocumented
 mod shy; //Undocumented
 mod slo; //Undocumented
+mod smb; //65C02
 mod sre; //Undocumented
 mod sta;
+mod stp; //65C02
 mod stx;
 mod sty;
+mod stz; //65C02
 mod tax;
 mod tay;
+mod trb; //65C02
+mod tsb; //65C02
 mod tsx;
 mod txa;
 mod txs;
 mod tya;
+mod wai; //65C02
 mod xaa; //Undocumented
 
 mod irq;
@@ -16,26 +16,41 @@
     negative flag is reset.
 
     The zero flag is set if the accumulator result is 0, otherwise the zero flag is reset.
+
+    The Ricoh 2A03 has no decimal mode. On the NMOS 6502 the decimal add leaves N, V and Z
+    as they would be for the binary add (N and V use the sum before its high digit is
+    adjusted). The 65C02 sets N and Z from the decimal result and takes one more cycle.
 */
 
-use crate::{bus::Bus, cpu::addr::AddrModeResult};
+use crate::{
+    bus::Bus,
+    cpu::{addr::AddrModeResult, CpuVariant},
+};
 
 use super::super::NESCPU;
 
 impl NESCPU {
     pub(in crate::cpu) fn adcc(&self, mode: &AddrModeResult) -> u8 {
-        2 + mode.cycles
+        2 + mode.cycles + (self.decimal_mode() && self.variant == CpuVariant::Wdc65C02) as u8
     }
 
-    pub(in crate::cpu) fn adc(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
-        let a: u16 = self.a as u16;
+    pub(in crate::cpu) fn adc<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
+        let data = if let Some(addr) = mode.addr {
+            bus.read(addr)
+        } else {
+            mode.data.unwrap()
+        };
 
-        let v: u16;
-        if let Some(addr) = mode.addr {
-            v = bus.read(addr) as u16;
+        if self.decimal_mode() {
+            self.adc_decimal(data);
         } else {
-            v = mode.data.unwrap() as u16;
+            self.adc_binary(data);
         }
+    }
+
+    pub(in crate::cpu) fn adc_binary(&mut self, data: u8) {
+        let a: u16 = self.a as u16;
+        let v: u16 = data as u16;
 
         let s: u16 = a + v + self.c as u16;
 
@@ -45,6 +60,38 @@ impl NESCPU {
         self.n = (self.a & 0b_1000_0000_u8) != 0;
         self.v = ((a ^ s) & (v ^ s) & 0x80) != 0;
     }
+
+    fn adc_decimal(&mut self, data: u8) {
+        let a: u16 = self.a as u16;
+        let v: u16 = data as u16;
+        let binary_sum = (a + v + self.c as u16) as u8;
+
+        let mut low = (a & 0xf) + (v & 0xf) + self.c as u16;
+        if low > 0x9 {
+            low = ((low + 0x6) & 0xf) + 0x10;
+        }
+
+        let mut s = (a & 0xf0) + (v & 0xf0) + low;
+        let n = (s & 0x80) != 0;
+        self.v = ((a ^ s) & (v ^ s) & 0x80) != 0;
+
+        if s > 0x9f {
+            s += 0x60;
+        }
+        self.c = s > 0xff;
+
+        self.a = s as u8;
+        match self.variant {
+            CpuVariant::Wdc65C02 => {
+                self.n = (self.a & 0x80) != 0;
+                self.z = self.a == 0;
+            }
+            _ => {
+                self.n = n;
+                self.z = binary_sum == 0;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -257,4 +304,73 @@ mod adc_tests {
         assert_eq!(false, cpu.n);
         assert_eq!(false, cpu.v);
     }
+
+    #[test]
+    fn test_adc_ignores_decimal_flag_on_2a03() {
+        let mut cpu = NESCPU::new();
+        cpu.d = true;
+        cpu.a = 0x09;
+
+        cpu.adc(&cpu._imm(0x01), &mut MockBus::new());
+
+        assert_eq!(0x0a, cpu.a);
+    }
+
+    #[test]
+    fn test_adc_decimal_nmos() {
+        let mut cpu = NESCPU::with_variant(CpuVariant::Nmos6502);
+        cpu.d = true;
+        cpu.a = 0x58;
+        cpu.c = true;
+
+        assert_eq!(2, cpu.adcc(&cpu._imm(0x46)));
+        cpu.adc(&cpu._imm(0x46), &mut MockBus::new());
+
+        assert_eq!(0x05, cpu.a);
+        assert_eq!(true, cpu.c);
+    }
+
+    #[test]
+    fn test_adc_decimal_nmos_flags_from_binary_sum() {
+        let mut cpu = NESCPU::with_variant(CpuVariant::Nmos6502);
+        cpu.d = true;
+        cpu.a = 0x99;
+
+        cpu.adc(&cpu._imm(0x01), &mut MockBus::new());
+
+        assert_eq!(0x00, cpu.a);
+        assert_eq!(true, cpu.c);
+        assert_eq!(false, cpu.z);
+        assert_eq!(true, cpu.n);
+    }
+
+    #[test]
+    fn test_adc_decimal_nmos_overflow() {
+        let mut cpu = NESCPU::with_variant(CpuVariant::Nmos6502);
+        cpu.d = true;
+        cpu.a = 0x79;
+        cpu.c = true;
+
+        cpu.adc(&cpu._imm(0x00), &mut MockBus::new());
+
+        assert_eq!(0x80, cpu.a);
+        assert_eq!(true, cpu.n);
+        assert_eq!(true, cpu.v);
+        assert_eq!(false, cpu.c);
+    }
+
+    #[test]
+    fn test_adc_decimal_65c02_flags_from_result() {
+        let mut cpu = NESCPU::with_variant(CpuVariant::Wdc65C02);
+        cpu.d = true;
+        cpu.a = 0x99;
+
+        assert_eq!(3, cpu.adcc(&cpu._imm(0x01)));
+        cpu.adc(&cpu._imm(0x01), &mut MockBus::new());
+
+        assert_eq!(0x00, cpu.a);
+        assert_eq!(true, cpu.c);
+        assert_eq!(true, cpu.z);
+        assert_eq!(false, cpu.n);
+    }
 }
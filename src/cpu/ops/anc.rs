@@ -24,7 +24,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn anc(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn anc<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         self.a &= mode.data.unwrap();
 
         self.z = self.a == 0;
@@ -22,7 +22,7 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn and(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn and<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         if let Some(addr) = mode.addr {
             self.a &= bus.read(addr);
         } else {
@@ -33,7 +33,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn arr(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn arr<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         self.and(mode, bus);
         self.ror(&self._acc(), bus);
 
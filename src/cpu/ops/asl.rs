@@ -16,7 +16,10 @@
 
 use crate::{
     bus::Bus,
-    cpu::addr::{AddrModeResult, AddrModeType},
+    cpu::{
+        addr::{AddrModeResult, AddrModeType},
+        CpuVariant,
+    },
 };
 
 use super::super::NESCPU;
@@ -25,12 +28,13 @@ impl NESCPU {
     pub(in crate::cpu) fn aslc(&self, mode: &AddrModeResult) -> u8 {
         match mode.mode {
             AddrModeType::Acc => 2,
-            AddrModeType::Absx => 7,
+            //The 65C02 skips the page fixup cycle when no page is crossed
+            AddrModeType::Absx if self.variant != CpuVariant::Wdc65C02 => 7,
             _ => 4 + mode.cycles,
         }
     }
 
-    pub(in crate::cpu) fn asl(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn asl<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         if let Some(addr) = mode.addr {
             let data: u16 = (bus.read(mode.addr.unwrap()) as u16) << 1;
 
@@ -181,4 +185,14 @@ mod asl_tests {
 
         cpu.asl(&cpu._zp(0x0, &bus), &mut bus);
     }
+
+    #[test]
+    fn test_asl_absx_65c02_correct_number_of_cycles() {
+        let mut cpu = NESCPU::with_variant(CpuVariant::Wdc65C02);
+        let bus = MockBus::new();
+        cpu.x = 0x1;
+
+        assert_eq!(6, cpu.aslc(&cpu._absx(0x1234, &bus)));
+        assert_eq!(7, cpu.aslc(&cpu._absx(0x12ff, &bus)));
+    }
 }
@@ -23,7 +23,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn asr(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn asr<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         self.and(mode, bus);
         self.lsr(&self._acc(), bus)
     }
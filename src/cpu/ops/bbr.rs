@@ -0,0 +1,87 @@
+/*
+    BBR - Branch on Bit Reset (65C02)
+    Operation: Branch on Mb = 0
+
+    BBR0 to BBR7 test bit 0 to 7 of a zero page memory location
+    and take the branch if the bit is reset. Like the other branches,
+    a taken branch adds one cycle, plus one more if it crosses a
+    page boundary.
+
+    BBR does not affect any of the flags or registers other than
+    the program counter.
+*/
+
+use crate::{bus::Bus, cpu::addr::AddrModeResult};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn bbrc<const BIT: u8>(&self, mode: &AddrModeResult) -> u8 {
+        match mode.data {
+            Some(data) if data & (1 << BIT) == 0 => 5 + 1 + mode.cycles,
+            _ => 5,
+        }
+    }
+
+    pub(in crate::cpu) fn bbr<const BIT: u8, B: Bus + ?Sized>(
+        &mut self,
+        mode: &AddrModeResult,
+        _bus: &mut B,
+    ) {
+        if mode.data.unwrap() & (1 << BIT) == 0 {
+            self.pc = mode.addr.unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod bbr_tests {
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_bbr_not_taken_correct_number_of_cycles() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.pc = 0x8003;
+        bus.expect_read().return_const(0x08_u8);
+
+        assert_eq!(5, cpu.bbrc::<3>(&cpu._zprel(0x10, 0x10, &bus)));
+    }
+
+    #[test]
+    fn test_bbr_taken_correct_number_of_cycles() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.pc = 0x8003;
+        bus.expect_read().return_const(0xf7_u8);
+
+        assert_eq!(6, cpu.bbrc::<3>(&cpu._zprel(0x10, 0x10, &bus)));
+        assert_eq!(7, cpu.bbrc::<3>(&cpu._zprel(0x10, 0x80, &bus)));
+    }
+
+    #[test]
+    fn test_bbr_taken() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.pc = 0x8003;
+        bus.expect_read().return_const(0xf7_u8);
+
+        cpu.bbr::<3, _>(&cpu._zprel(0x10, 0x10, &bus), &mut MockBus::new());
+
+        assert_eq!(0x8013, cpu.pc);
+    }
+
+    #[test]
+    fn test_bbr_not_taken() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.pc = 0x8003;
+        bus.expect_read().return_const(0x08_u8);
+
+        cpu.bbr::<3, _>(&cpu._zprel(0x10, 0x10, &bus), &mut MockBus::new());
+
+        assert_eq!(0x8003, cpu.pc);
+    }
+}
@@ -22,7 +22,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn bcc(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn bcc<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         if !self.c {
             self.pc = mode.addr.unwrap();
         }
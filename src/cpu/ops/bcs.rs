@@ -21,7 +21,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn bcs(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn bcs<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         if self.c {
             self.pc = mode.addr.unwrap();
         }
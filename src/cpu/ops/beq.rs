@@ -24,7 +24,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn beq(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn beq<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         if self.z {
             self.pc = mode.addr.unwrap();
         }
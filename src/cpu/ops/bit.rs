@@ -17,7 +17,10 @@
 
 use crate::{
     bus::Bus,
-    cpu::{addr::AddrModeResult, NESCPU},
+    cpu::{
+        addr::{AddrModeResult, AddrModeType},
+        NESCPU,
+    },
 };
 
 impl NESCPU {
@@ -25,11 +28,17 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn bit(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
-        let data = bus.read(mode.addr.unwrap());
-
-        self.n = (data & 0b1000_0000) != 0;
-        self.v = (data & 0b0100_0000) != 0;
+    pub(in crate::cpu) fn bit<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
+        let data = match mode.addr {
+            Some(addr) => bus.read(addr),
+            None => mode.data.unwrap(),
+        };
+
+        //The 65C02 immediate form only affects the Z flag
+        if mode.mode != AddrModeType::Imm {
+            self.n = (data & 0b1000_0000) != 0;
+            self.v = (data & 0b0100_0000) != 0;
+        }
         self.z = (self.a & data) == 0;
     }
 }
@@ -101,4 +110,18 @@ mod bit_tests {
         assert_eq!(false, cpu.v);
         assert_eq!(true, cpu.z);
     }
+
+    #[test]
+    fn test_bit_imm_only_affects_zero_flag() {
+        let mut cpu = NESCPU::new();
+        cpu.a = 0x3f;
+        cpu.n = false;
+        cpu.v = false;
+
+        cpu.bit(&cpu._imm(0xc0), &mut MockBus::new());
+
+        assert_eq!(false, cpu.n);
+        assert_eq!(false, cpu.v);
+        assert_eq!(true, cpu.z);
+    }
 }
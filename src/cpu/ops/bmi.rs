@@ -21,7 +21,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn bmi(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn bmi<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         if self.n {
             self.pc = mode.addr.unwrap();
         }
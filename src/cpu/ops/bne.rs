@@ -23,7 +23,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn bne(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn bne<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         if !self.z {
             self.pc = mode.addr.unwrap();
         }
@@ -28,7 +28,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn bpl(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn bpl<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         if !self.n {
             self.pc = mode.addr.unwrap();
         }
@@ -0,0 +1,58 @@
+/*
+    BRA - Branch Always (65C02)
+    Operation: Branch unconditionally
+
+    This instruction always takes the branch. It takes one
+    more cycle than a taken conditional branch would, plus one
+    more if the branch crosses a page boundary.
+
+    BRA does not affect any of the flags or registers other
+    than the program counter.
+*/
+
+use crate::{bus::Bus, cpu::addr::AddrModeResult};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn brac(&self, mode: &AddrModeResult) -> u8 {
+        3 + mode.cycles
+    }
+
+    pub(in crate::cpu) fn bra<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
+        self.pc = mode.addr.unwrap();
+    }
+}
+
+#[cfg(test)]
+mod bra_tests {
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_bra_correct_number_of_cycles() {
+        let mut cpu = NESCPU::new();
+        cpu.pc = 0x8002;
+
+        assert_eq!(3, cpu.brac(&cpu._rel(0x10)));
+    }
+
+    #[test]
+    fn test_bra_with_page_cross_correct_number_of_cycles() {
+        let mut cpu = NESCPU::new();
+        cpu.pc = 0x8002;
+
+        assert_eq!(4, cpu.brac(&cpu._rel(0x80)));
+    }
+
+    #[test]
+    fn test_bra_always_branches() {
+        let mut cpu = NESCPU::new();
+        cpu.pc = 0x8002;
+
+        cpu.bra(&cpu._rel(0x10), &mut MockBus::new());
+
+        assert_eq!(0x8012, cpu.pc);
+    }
+}
@@ -14,7 +14,10 @@
     changes no values in either the registers or the flags.
 */
 
-use crate::{bus::Bus, cpu::addr::AddrModeResult};
+use crate::{
+    bus::Bus,
+    cpu::{addr::AddrModeResult, CpuVariant},
+};
 
 use super::super::NESCPU;
 
@@ -23,7 +26,7 @@ impl NESCPU {
         7
     }
 
-    pub(in crate::cpu) fn brk(&mut self, _mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn brk<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
         let pc_lsb = (self.pc & 0xff) as u8;
         let pc_msb = (self.pc >> 8) as u8;
 
@@ -35,6 +38,8 @@ impl NESCPU {
         );
 
         self.i = true;
+        //The 65C02 also leaves decimal mode
+        self.d = self.d && self.variant != CpuVariant::Wdc65C02;
         self.sp = self.sp.wrapping_sub(3);
         self.pc =
             (bus.read(NESCPU::IRQ_VECTOR + 1) as u16) << 8 | bus.read(NESCPU::IRQ_VECTOR) as u16;
@@ -165,4 +170,21 @@ mod brk_tests {
 
         assert_eq!(0xfd, cpu.sp);
     }
+
+    #[test]
+    fn test_brk_clears_decimal_flag_on_65c02() {
+        let mut bus = MockBus::new();
+        bus.expect_write().return_const(());
+        bus.expect_read().return_const(0x0);
+
+        let mut cpu = NESCPU::new();
+        cpu.d = true;
+        cpu.brk(&cpu._imp(), &mut bus);
+        assert_eq!(true, cpu.d);
+
+        let mut cpu = NESCPU::with_variant(CpuVariant::Wdc65C02);
+        cpu.d = true;
+        cpu.brk(&cpu._imp(), &mut bus);
+        assert_eq!(false, cpu.d);
+    }
 }
@@ -23,7 +23,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn bvc(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn bvc<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         if !self.v {
             self.pc = mode.addr.unwrap();
         }
@@ -22,7 +22,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn bvs(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn bvs<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         if self.v {
             self.pc = mode.addr.unwrap();
         }
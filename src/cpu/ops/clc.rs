@@ -20,7 +20,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn clc(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn clc<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.c = false;
     }
 }
@@ -20,7 +20,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn cld(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn cld<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.d = false;
     }
 }
@@ -18,7 +18,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn cli(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn cli<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.i = false;
     }
 }
@@ -21,7 +21,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn clv(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn clv<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.v = false;
     }
 }
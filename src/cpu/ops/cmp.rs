@@ -24,7 +24,7 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn cmp(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn cmp<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let data: u8;
         if let Some(addr) = mode.addr {
             data = bus.read(addr);
@@ -27,7 +27,7 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn cpy(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn cpy<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let data = match mode.addr {
             Some(addr) => bus.read(addr),
             None => mode.data.unwrap(),
@@ -33,7 +33,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn dcp(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn dcp<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let data = bus.read(mode.addr.unwrap());
         let data_to_write = data.wrapping_sub(1);
 
@@ -21,14 +21,25 @@ use crate::{
 impl NESCPU {
     pub(in crate::cpu) fn decc(&self, mode: &AddrModeResult) -> u8 {
         match mode.mode {
+            AddrModeType::Acc => 2,
             AddrModeType::Absx => 7,
             _ => 4 + mode.cycles,
         }
     }
 
-    pub(in crate::cpu) fn dec(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
-        let result = bus.read(mode.addr.unwrap()).wrapping_sub(1);
-        bus.write(mode.addr.unwrap(), result);
+    pub(in crate::cpu) fn dec<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
+        let result = match mode.addr {
+            Some(addr) => {
+                let result = bus.read(addr).wrapping_sub(1);
+                bus.write(addr, result);
+                result
+            }
+            //65C02 accumulator form
+            None => {
+                self.a = self.a.wrapping_sub(1);
+                self.a
+            }
+        };
 
         self.n = (result & 0x80) != 0;
         self.z = result == 0;
@@ -131,4 +142,22 @@ mod dec_tests {
         cpu.dec(&cpu._zp(0x0, &bus), &mut bus);
         assert_eq!(true, cpu.z);
     }
+
+    #[test]
+    fn test_dec_acc_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        assert_eq!(2, cpu.decc(&cpu._acc()));
+    }
+
+    #[test]
+    fn test_dec_acc() {
+        let mut cpu = NESCPU::new();
+        cpu.a = 0x1;
+
+        cpu.dec(&cpu._acc(), &mut MockBus::new());
+
+        assert_eq!(0x0, cpu.a);
+        assert_eq!(false, cpu.n);
+        assert_eq!(true, cpu.z);
+    }
 }
@@ -22,7 +22,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn dex(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn dex<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.x = self.x.wrapping_sub(1);
 
         self.n = (self.x & 0x80) > 0;
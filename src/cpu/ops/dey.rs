@@ -26,7 +26,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn dey(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn dey<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.y = self.y.wrapping_sub(1);
 
         self.n = (self.y & 0x80) > 0;
@@ -24,7 +24,7 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn eor(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn eor<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         if let Some(addr) = mode.addr {
             self.a ^= bus.read(addr);
         } else {
@@ -24,14 +24,25 @@ use crate::{
 impl NESCPU {
     pub(in crate::cpu) fn incc(&self, mode: &AddrModeResult) -> u8 {
         match mode.mode {
+            AddrModeType::Acc => 2,
             AddrModeType::Absx => 7,
             _ => 4 + mode.cycles,
         }
     }
 
-    pub(in crate::cpu) fn inc(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
-        let data = bus.read(mode.addr.unwrap()).wrapping_add(1);
-        bus.write(mode.addr.unwrap(), data);
+    pub(in crate::cpu) fn inc<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
+        let data = match mode.addr {
+            Some(addr) => {
+                let data = bus.read(addr).wrapping_add(1);
+                bus.write(addr, data);
+                data
+            }
+            //65C02 accumulator form
+            None => {
+                self.a = self.a.wrapping_add(1);
+                self.a
+            }
+        };
 
         self.n = (data & 0x80) != 0;
         self.z = data == 0;
@@ -127,4 +138,22 @@ mod inc_tests {
 
         assert_eq!(true, cpu.z);
     }
+
+    #[test]
+    fn test_inc_acc_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        assert_eq!(2, cpu.incc(&cpu._acc()));
+    }
+
+    #[test]
+    fn test_inc_acc() {
+        let mut cpu = NESCPU::new();
+        cpu.a = 0x7f;
+
+        cpu.inc(&cpu._acc(), &mut MockBus::new());
+
+        assert_eq!(0x80, cpu.a);
+        assert_eq!(true, cpu.n);
+        assert_eq!(false, cpu.z);
+    }
 }
@@ -25,7 +25,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn inx(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn inx<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.x = self.x.wrapping_add(1);
 
         self.n = (self.x & 0x80) > 0;
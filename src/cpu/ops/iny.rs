@@ -25,7 +25,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn iny(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn iny<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.y = self.y.wrapping_add(1);
 
         self.n = (self.y & 0x80) > 0;
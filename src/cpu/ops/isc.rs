@@ -37,7 +37,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn isc(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn isc<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let data_to_write = bus.read(mode.addr.unwrap()).wrapping_add(1);
         bus.write(mode.addr.unwrap(), data_to_write);
         self.sbc(&self._imm(data_to_write), bus);
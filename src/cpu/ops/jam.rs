@@ -17,7 +17,7 @@ impl NESCPU {
         0
     }
 
-    pub(in crate::cpu) fn _jam(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn _jam<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         panic!("JAM: This should not be called")
     }
 }
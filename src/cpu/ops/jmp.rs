@@ -10,15 +10,22 @@
 
 use crate::{
     bus::Bus,
-    cpu::{addr::AddrModeResult, NESCPU},
+    cpu::{
+        addr::{AddrModeResult, AddrModeType},
+        CpuVariant, NESCPU,
+    },
 };
 
 impl NESCPU {
     pub(in crate::cpu) fn jmpc(&self, mode: &AddrModeResult) -> u8 {
-        1 + mode.cycles
+        match mode.mode {
+            //The 65C02 spends a cycle fixing the page wrap bug
+            AddrModeType::Ind if self.variant == CpuVariant::Wdc65C02 => 6,
+            _ => 1 + mode.cycles,
+        }
     }
 
-    pub(in crate::cpu) fn jmp(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn jmp<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         self.pc = mode.addr.unwrap();
     }
 }
@@ -59,4 +66,16 @@ mod jmp_tests {
         cpu.jmp(&cpu._abs(0x1234, &bus), &mut MockBus::new());
         assert_eq!(0x1234, cpu.pc);
     }
+
+    #[test]
+    fn test_jmp_65c02_correct_number_of_cycles() {
+        let mut cpu = NESCPU::with_variant(CpuVariant::Wdc65C02);
+        let mut bus = MockBus::new();
+        cpu.x = 0x2;
+
+        bus.expect_read().return_const(0x0);
+
+        assert_eq!(6, cpu.jmpc(&cpu._ind(0x0000, &bus)));
+        assert_eq!(6, cpu.jmpc(&cpu._indabsx(0x0000, &bus)));
+    }
 }
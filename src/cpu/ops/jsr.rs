@@ -30,7 +30,7 @@ impl NESCPU {
         6
     }
 
-    pub(in crate::cpu) fn jsr(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn jsr<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         //Set return address to last byte of JSR
         let return_addr = self.pc.wrapping_sub(1);
 
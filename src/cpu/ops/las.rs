@@ -22,7 +22,7 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn las(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn las<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let data = bus.read(mode.addr.unwrap()) & self.sp;
 
         self.a = data;
@@ -8,6 +8,9 @@
     LAX does not affect the C or V flags; sets Z if the value loaded
     was zero, otherwise resets it; sets N if the value loaded in bit 7
     is a 1; otherwise N is reset, and affects only the X register.
+
+    The immediate form is unstable: like XAA, it ORs the accumulator
+    with a magic constant before ANDing it with the operand.
 */
 
 use crate::{
@@ -20,10 +23,10 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn lax(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn lax<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let data = match mode.addr {
             Some(addr) => bus.read(addr),
-            None => mode.data.unwrap(),
+            None => (self.a | self.unstable_magic()) & mode.data.unwrap(),
         };
 
         self.a = data;
@@ -38,7 +41,7 @@ impl NESCPU {
 mod lax_tests {
     use mockall::predicate::eq;
 
-    use crate::bus::MockBus;
+    use crate::{bus::MockBus, cpu::UnstableOpcodes};
 
     use super::*;
 
@@ -148,4 +151,16 @@ mod lax_tests {
         assert_eq!(false, cpu.n);
         assert_eq!(true, cpu.z);
     }
+
+    #[test]
+    fn test_lax_imm_magic_constant() {
+        let mut cpu = NESCPU::new();
+        cpu.set_unstable_opcodes(UnstableOpcodes::Fixed { magic: 0x0 });
+        cpu.a = 0x0f;
+
+        cpu.lax(&cpu._imm(0xff), &mut MockBus::new());
+
+        assert_eq!(0x0f, cpu.a);
+        assert_eq!(0x0f, cpu.x);
+    }
 }
@@ -23,7 +23,7 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn lda(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn lda<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         if let Some(addr) = mode.addr {
             self.a = bus.read(addr);
         } else {
@@ -20,7 +20,7 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn ldx(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn ldx<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         if let Some(addr) = mode.addr {
             self.x = bus.read(addr);
         } else {
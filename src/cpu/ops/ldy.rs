@@ -20,7 +20,7 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn ldy(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn ldy<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         self.y = match mode.addr {
             Some(addr) => bus.read(addr),
             None => mode.data.unwrap(),
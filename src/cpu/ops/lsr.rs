@@ -19,7 +19,10 @@
 
 use crate::{
     bus::Bus,
-    cpu::addr::{AddrModeResult, AddrModeType},
+    cpu::{
+        addr::{AddrModeResult, AddrModeType},
+        CpuVariant,
+    },
 };
 
 use super::super::NESCPU;
@@ -28,12 +31,13 @@ impl NESCPU {
     pub(in crate::cpu) fn lsrc(&self, mode: &AddrModeResult) -> u8 {
         match mode.mode {
             AddrModeType::Acc => 2,
-            AddrModeType::Absx => 7,
+            //The 65C02 skips the page fixup cycle when no page is crossed
+            AddrModeType::Absx if self.variant != CpuVariant::Wdc65C02 => 7,
             _ => 4 + mode.cycles,
         }
     }
 
-    pub(in crate::cpu) fn lsr(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn lsr<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         if let Some(addr) = mode.addr {
             let before_shift = bus.read(mode.addr.unwrap());
             let after_shift = before_shift >> 1;
@@ -1,11 +1,14 @@
-use crate::{bus::Bus, cpu::NESCPU};
+use crate::{
+    bus::Bus,
+    cpu::{CpuVariant, NESCPU},
+};
 
 impl NESCPU {
     pub(in crate::cpu) fn nmic(&self) -> u8 {
         7
     }
 
-    pub(in crate::cpu) fn nmi(&mut self, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn nmi<B: Bus + ?Sized>(&mut self, bus: &mut B) {
         let pc_high: u8 = (self.pc >> 8) as u8;
         let pc_low: u8 = self.pc as u8;
 
@@ -17,6 +20,8 @@ impl NESCPU {
         );
 
         self.i = true;
+        //The 65C02 also leaves decimal mode
+        self.d = self.d && self.variant != CpuVariant::Wdc65C02;
         self.sp = self.sp.wrapping_sub(3);
         self.pc =
             (bus.read(NESCPU::NMI_VECTOR + 1) as u16) << 8 | bus.read(NESCPU::NMI_VECTOR) as u16;
@@ -1,6 +1,10 @@
 /*
     NOP - No Operation
     Operation: No operation
+
+    On the 65C02 every unused opcode is a NOP. Most take the
+    cycles of their addressing mode, but the single byte ones
+    complete in one cycle and $5C takes eight.
 */
 
 use crate::{bus::Bus, cpu::addr::AddrModeResult};
@@ -12,7 +16,15 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn nop(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn nop1c(&self, _mode: &AddrModeResult) -> u8 {
+        1
+    }
+
+    pub(in crate::cpu) fn nop8c(&self, _mode: &AddrModeResult) -> u8 {
+        8
+    }
+
+    pub(in crate::cpu) fn nop<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         //No operation
     }
 }
@@ -81,6 +93,16 @@ mod nop_tests {
         assert_eq!(5, cpu.nopc(&cpu._absx(0x1234, &bus)));
     }
 
+    #[test]
+    fn test_nop_65c02_fixed_number_of_cycles() {
+        let cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        bus.expect_read().return_const(0x0);
+
+        assert_eq!(1, cpu.nop1c(&cpu._imp()));
+        assert_eq!(8, cpu.nop8c(&cpu._abs(0x0, &bus)));
+    }
+
     #[test]
     fn test_nop_does_not_crash() {
         let mut cpu = NESCPU::new();
@@ -21,7 +21,7 @@ impl NESCPU {
         3
     }
 
-    pub(in crate::cpu) fn pha(&mut self, _mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn pha<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
         bus.write(0x100 + (self.sp as u16), self.a);
         self.sp = self.sp.wrapping_sub(1);
     }
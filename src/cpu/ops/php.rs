@@ -19,7 +19,7 @@ impl NESCPU {
         3
     }
 
-    pub(in crate::cpu) fn php(&mut self, _mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn php<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
         bus.write(0x100 + (self.sp as u16), self.get_status_byte(true));
         self.sp = self.sp.wrapping_sub(1);
     }
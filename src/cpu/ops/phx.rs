@@ -0,0 +1,57 @@
+/*
+    PHX - Push Index Register X On Stack (65C02)
+
+    Operation: X↓
+
+    This instruction transfers the current value of the
+    index register X to the next location on the stack,
+    automatically decrementing the stack to point to the
+    next empty location.
+
+    It affects no flags.
+*/
+
+use crate::{bus::Bus, cpu::addr::AddrModeResult};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn phxc(&self, _mode: &AddrModeResult) -> u8 {
+        3
+    }
+
+    pub(in crate::cpu) fn phx<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
+        bus.write(0x100 + (self.sp as u16), self.x);
+        self.sp = self.sp.wrapping_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod phx_tests {
+    use mockall::predicate::eq;
+
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_phx_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        assert_eq!(3, cpu.phxc(&cpu._imp()));
+    }
+
+    #[test]
+    fn test_phx_push_x_onto_stack() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.x = 0xee;
+
+        bus.expect_write()
+            .with(eq(0x1ff), eq(0xee))
+            .times(1)
+            .return_const(());
+
+        cpu.phx(&cpu._imp(), &mut bus);
+        assert_eq!(0xfe, cpu.sp);
+    }
+}
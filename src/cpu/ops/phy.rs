@@ -0,0 +1,57 @@
+/*
+    PHY - Push Index Register Y On Stack (65C02)
+
+    Operation: Y↓
+
+    This instruction transfers the current value of the
+    index register Y to the next location on the stack,
+    automatically decrementing the stack to point to the
+    next empty location.
+
+    It affects no flags.
+*/
+
+use crate::{bus::Bus, cpu::addr::AddrModeResult};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn phyc(&self, _mode: &AddrModeResult) -> u8 {
+        3
+    }
+
+    pub(in crate::cpu) fn phy<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
+        bus.write(0x100 + (self.sp as u16), self.y);
+        self.sp = self.sp.wrapping_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod phy_tests {
+    use mockall::predicate::eq;
+
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_phy_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        assert_eq!(3, cpu.phyc(&cpu._imp()));
+    }
+
+    #[test]
+    fn test_phy_push_y_onto_stack() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.y = 0xee;
+
+        bus.expect_write()
+            .with(eq(0x1ff), eq(0xee))
+            .times(1)
+            .return_const(());
+
+        cpu.phy(&cpu._imp(), &mut bus);
+        assert_eq!(0xfe, cpu.sp);
+    }
+}
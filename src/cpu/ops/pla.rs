@@ -25,7 +25,7 @@ impl NESCPU {
         4
     }
 
-    pub(in crate::cpu) fn pla(&mut self, _mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn pla<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
         self.sp = self.sp.wrapping_add(1);
 
         self.a = bus.read(0x100 + (self.sp as u16));
@@ -22,7 +22,7 @@ impl NESCPU {
         4
     }
 
-    pub(in crate::cpu) fn plp(&mut self, _mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn plp<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
         self.sp = self.sp.wrapping_add(1);
         let data = bus.read(0x100 + (self.sp as u16));
 
@@ -0,0 +1,78 @@
+/*
+    PLY - Pull Index Register Y From Stack (65C02)
+
+    Operation: Y↑
+
+    This instruction adds 1 to the current value of the
+    stack pointer and uses it to address the stack and
+    loads the contents of the stack into the Y register.
+
+    It sets N if bit 7 of Y is on, otherwise it is reset.
+    If Y is zero as a result, the Z flag is set, otherwise
+    it is reset.
+*/
+
+use crate::{bus::Bus, cpu::addr::AddrModeResult};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn plyc(&self, _mode: &AddrModeResult) -> u8 {
+        4
+    }
+
+    pub(in crate::cpu) fn ply<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
+        self.sp = self.sp.wrapping_add(1);
+        self.y = bus.read(0x100 + (self.sp as u16));
+        self.n = (self.y & 0x80) > 0;
+        self.z = self.y == 0;
+    }
+}
+
+#[cfg(test)]
+mod ply_tests {
+    use mockall::predicate::eq;
+
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_ply_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        assert_eq!(4, cpu.plyc(&cpu._imp()));
+    }
+
+    #[test]
+    fn test_ply_fetches_y() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.sp = 0xfe;
+
+        bus.expect_read()
+            .with(eq(0x1ff))
+            .times(1)
+            .return_const(0xcc);
+
+        cpu.ply(&cpu._imp(), &mut bus);
+
+        assert_eq!(0xcc, cpu.y);
+        assert_eq!(0xff, cpu.sp);
+        assert_eq!(true, cpu.n);
+        assert_eq!(false, cpu.z);
+    }
+
+    #[test]
+    fn test_ply_sets_zero_flag() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.sp = 0xfe;
+
+        bus.expect_read().with(eq(0x1ff)).times(1).return_const(0x0);
+
+        cpu.ply(&cpu._imp(), &mut bus);
+
+        assert_eq!(false, cpu.n);
+        assert_eq!(true, cpu.z);
+    }
+}
@@ -1,15 +1,20 @@
-use crate::{bus::Bus, cpu::NESCPU};
+use crate::{
+    bus::Bus,
+    cpu::{CpuVariant, NESCPU},
+};
 
 impl NESCPU {
     pub(in crate::cpu) fn resetc(&self) -> u8 {
         7
     }
 
-    pub(in crate::cpu) fn reset(&mut self, bus: &dyn Bus) {
+    pub(in crate::cpu) fn reset<B: Bus + ?Sized>(&mut self, bus: &B) {
         let low_byte = bus.read(NESCPU::RESET_VECTOR) as u16;
         let high_byte = bus.read(NESCPU::RESET_VECTOR + 1) as u16;
 
         self.i = true;
+        //The 65C02 also leaves decimal mode
+        self.d = self.d && self.variant != CpuVariant::Wdc65C02;
         self.pc = high_byte << 8 | low_byte;
         self.sp = 0xFD;
     }
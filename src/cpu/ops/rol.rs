@@ -17,7 +17,10 @@
 
 use crate::{
     bus::Bus,
-    cpu::addr::{AddrModeResult, AddrModeType},
+    cpu::{
+        addr::{AddrModeResult, AddrModeType},
+        CpuVariant,
+    },
 };
 
 use super::super::NESCPU;
@@ -26,12 +29,13 @@ impl NESCPU {
     pub(in crate::cpu) fn rolc(&self, mode: &AddrModeResult) -> u8 {
         match mode.mode {
             AddrModeType::Acc => 2,
-            AddrModeType::Absx => 7,
+            //The 65C02 skips the page fixup cycle when no page is crossed
+            AddrModeType::Absx if self.variant != CpuVariant::Wdc65C02 => 7,
             _ => 4 + mode.cycles,
         }
     }
 
-    pub(in crate::cpu) fn rol(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn rol<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         if let Some(addr) = mode.addr {
             let data: u16 = ((bus.read(mode.addr.unwrap()) as u16) << 1) | (self.c as u16);
 
@@ -16,7 +16,10 @@
 
 use crate::{
     bus::Bus,
-    cpu::addr::{AddrModeResult, AddrModeType},
+    cpu::{
+        addr::{AddrModeResult, AddrModeType},
+        CpuVariant,
+    },
 };
 
 use super::super::NESCPU;
@@ -25,12 +28,13 @@ impl NESCPU {
     pub(in crate::cpu) fn rorc(&self, mode: &AddrModeResult) -> u8 {
         match mode.mode {
             AddrModeType::Acc => 2,
-            AddrModeType::Absx => 7,
+            //The 65C02 skips the page fixup cycle when no page is crossed
+            AddrModeType::Absx if self.variant != CpuVariant::Wdc65C02 => 7,
             _ => 4 + mode.cycles,
         }
     }
 
-    pub(in crate::cpu) fn ror(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn ror<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         if let Some(addr) = mode.addr {
             let before = bus.read(mode.addr.unwrap());
             let after: u8 = ((self.c as u8) << 7) | before >> 1;
@@ -35,7 +35,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn rra(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn rra<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let data = bus.read(mode.addr.unwrap());
         let data_to_write = (self.c as u8) << 7 | data >> 1;
         self.c = (data & 0x1) != 0;
@@ -29,7 +29,7 @@ impl NESCPU {
         6
     }
 
-    pub(in crate::cpu) fn rti(&mut self, _mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn rti<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
         let reg = bus.read(0x100 + (self.sp.wrapping_add(1) as u16));
         let pc_low = bus.read(0x100 + (self.sp.wrapping_add(2) as u16)) as u16;
         let pc_high = bus.read(0x100 + (self.sp.wrapping_add(3) as u16)) as u16;
@@ -23,7 +23,7 @@ impl NESCPU {
         6
     }
 
-    pub(in crate::cpu) fn rts(&mut self, _mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn rts<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, bus: &mut B) {
         let pc_low = bus.read(0x100 + (self.sp.wrapping_add(1) as u16)) as u16;
         let pc_high = bus.read(0x100 + (self.sp.wrapping_add(2) as u16)) as u16;
 
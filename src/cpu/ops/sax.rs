@@ -20,7 +20,7 @@ impl NESCPU {
         2 + mode.cycles
     }
 
-    pub(in crate::cpu) fn sax(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn sax<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         bus.write(mode.addr.unwrap(), self.a & self.x);
     }
 }
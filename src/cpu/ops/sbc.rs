@@ -16,18 +16,25 @@
     in the accumulator has bit 7 on, otherwise it is reset. The Z
     flag is set if the result in the accumulator is 0, otherwise
     it is reset.
+
+    The Ricoh 2A03 has no decimal mode. On the NMOS 6502 the decimal subtract sets every
+    flag as the binary subtract would. The 65C02 sets N and Z from the decimal result and
+    takes one more cycle.
 */
 
-use crate::{bus::Bus, cpu::addr::AddrModeResult};
+use crate::{
+    bus::Bus,
+    cpu::{addr::AddrModeResult, CpuVariant},
+};
 
 use super::super::NESCPU;
 
 impl NESCPU {
     pub(in crate::cpu) fn sbcc(&self, mode: &AddrModeResult) -> u8 {
-        2 + mode.cycles
+        2 + mode.cycles + (self.decimal_mode() && self.variant == CpuVariant::Wdc65C02) as u8
     }
 
-    pub(in crate::cpu) fn sbc(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn sbc<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let data: u8;
         if let Some(addr) = mode.addr {
             data = bus.read(addr);
@@ -35,7 +42,52 @@ impl NESCPU {
             data = mode.data.unwrap();
         }
 
-        self.adc(&self._imm(!data), bus)
+        if self.decimal_mode() {
+            self.sbc_decimal(data);
+        } else {
+            self.adc_binary(!data);
+        }
+    }
+
+    fn sbc_decimal(&mut self, data: u8) {
+        let a = self.a as i16;
+        let v = data as i16;
+        let borrow = !self.c as i16;
+
+        let low = (a & 0xf) - (v & 0xf) - borrow;
+        let result = match self.variant {
+            CpuVariant::Wdc65C02 => {
+                let mut s = a - v - borrow;
+                if s < 0 {
+                    s -= 0x60;
+                }
+                if low < 0 {
+                    s -= 0x6;
+                }
+                s
+            }
+            _ => {
+                let low = if low < 0 {
+                    ((low - 0x6) & 0xf) - 0x10
+                } else {
+                    low
+                };
+                let s = (a & 0xf0) - (v & 0xf0) + low;
+                if s < 0 {
+                    s - 0x60
+                } else {
+                    s
+                }
+            }
+        } as u8;
+
+        //C and V (and on the NMOS 6502, N and Z) come from the binary subtract
+        self.adc_binary(!data);
+        self.a = result;
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.n = (result & 0x80) != 0;
+            self.z = result == 0;
+        }
     }
 }
 
@@ -250,4 +302,60 @@ mod sbc_tests {
         cpu.sbc(&cpu._imm(0x1), &mut MockBus::new());
         assert_eq!(false, cpu.v);
     }
+
+    #[test]
+    fn test_sbc_ignores_decimal_flag_on_2a03() {
+        let mut cpu = NESCPU::new();
+        cpu.d = true;
+        cpu.c = true;
+        cpu.a = 0x10;
+
+        cpu.sbc(&cpu._imm(0x01), &mut MockBus::new());
+
+        assert_eq!(0x0f, cpu.a);
+    }
+
+    #[test]
+    fn test_sbc_decimal_nmos() {
+        let mut cpu = NESCPU::with_variant(CpuVariant::Nmos6502);
+        cpu.d = true;
+        cpu.c = true;
+        cpu.a = 0x46;
+
+        assert_eq!(2, cpu.sbcc(&cpu._imm(0x12)));
+        cpu.sbc(&cpu._imm(0x12), &mut MockBus::new());
+
+        assert_eq!(0x34, cpu.a);
+        assert_eq!(true, cpu.c);
+    }
+
+    #[test]
+    fn test_sbc_decimal_nmos_borrow() {
+        let mut cpu = NESCPU::with_variant(CpuVariant::Nmos6502);
+        cpu.d = true;
+        cpu.c = true;
+        cpu.a = 0x00;
+
+        cpu.sbc(&cpu._imm(0x01), &mut MockBus::new());
+
+        assert_eq!(0x99, cpu.a);
+        assert_eq!(false, cpu.c);
+        assert_eq!(true, cpu.n);
+    }
+
+    #[test]
+    fn test_sbc_decimal_65c02() {
+        let mut cpu = NESCPU::with_variant(CpuVariant::Wdc65C02);
+        cpu.d = true;
+        cpu.c = false;
+        cpu.a = 0x21;
+
+        assert_eq!(3, cpu.sbcc(&cpu._imm(0x12)));
+        cpu.sbc(&cpu._imm(0x12), &mut MockBus::new());
+
+        assert_eq!(0x08, cpu.a);
+        assert_eq!(true, cpu.c);
+        assert_eq!(false, cpu.z);
+        assert_eq!(false, cpu.n);
+    }
 }
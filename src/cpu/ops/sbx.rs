@@ -26,7 +26,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn sbx(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn sbx<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
         self.x = (self.a & self.x).wrapping_sub(mode.data.unwrap());
 
         self.n = (self.x & 0x80) != 0;
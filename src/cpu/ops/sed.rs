@@ -19,7 +19,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn sed(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn sed<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.d = true;
     }
 }
@@ -24,11 +24,12 @@ impl NESCPU {
         5
     }
 
-    pub(in crate::cpu) fn shs(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn shs<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         self.sp = self.a & self.x;
         let write_addr = mode.addr.unwrap();
         let h = ((write_addr.wrapping_sub(self.y as u16)) >> 8) as u8;
-        bus.write(write_addr, self.sp & h.wrapping_add(1));
+        let v = self.unstable_high_byte(h);
+        bus.write(write_addr, self.sp & v);
     }
 }
 
@@ -21,10 +21,11 @@ impl NESCPU {
         5
     }
 
-    pub(in crate::cpu) fn shx(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn shx<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let write_addr = mode.addr.unwrap();
         let h = (write_addr.wrapping_sub(self.y as u16) >> 8) as u8;
-        bus.write(write_addr, self.x & h.wrapping_add(1));
+        let v = self.unstable_high_byte(h);
+        bus.write(write_addr, self.x & v);
     }
 }
 
@@ -32,7 +33,7 @@ impl NESCPU {
 mod shx_tests {
     use mockall::predicate::eq;
 
-    use crate::bus::MockBus;
+    use crate::{bus::MockBus, cpu::UnstableOpcodes};
 
     use super::*;
 
@@ -54,10 +55,29 @@ mod shx_tests {
         let mut bus = MockBus::new();
         bus.expect_read().return_const(0x0);
         bus.expect_write()
-            .with(eq(0x1333), eq(0xff & 0x13))
+            .with(eq(0x1333), eq(0x13))
             .once()
             .return_const(());
 
         cpu.shx(&cpu._absy(0x1234, &bus), &mut bus);
     }
+
+    #[test]
+    fn test_shx_seeded_high_byte() {
+        let mut cpu = NESCPU::new();
+        cpu.set_unstable_opcodes(UnstableOpcodes::Seeded(1));
+        cpu.x = 0xff;
+        cpu.y = 0xff;
+
+        let mut bus = MockBus::new();
+        bus.expect_read().return_const(0x0);
+        bus.expect_write()
+            .withf(|addr, data| *addr == 0x1333 && [0x13, 0xff].contains(data))
+            .times(100)
+            .return_const(());
+
+        for _ in 0..100 {
+            cpu.shx(&cpu._absy(0x1234, &bus), &mut bus);
+        }
+    }
 }
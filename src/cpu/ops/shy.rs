@@ -21,10 +21,11 @@ impl NESCPU {
         5
     }
 
-    pub(in crate::cpu) fn shy(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn shy<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let write_addr = mode.addr.unwrap();
         let h = (write_addr.wrapping_sub(self.x as u16) >> 8) as u8;
-        bus.write(write_addr, self.y & h.wrapping_add(1));
+        let v = self.unstable_high_byte(h);
+        bus.write(write_addr, self.y & v);
     }
 }
 
@@ -54,7 +55,7 @@ mod shy_tests {
         let mut bus = MockBus::new();
         bus.expect_read().return_const(0x0);
         bus.expect_write()
-            .with(eq(0x1333), eq(0xff & 0x13))
+            .with(eq(0x1333), eq(0x13))
             .once()
             .return_const(());
 
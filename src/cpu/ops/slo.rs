@@ -32,7 +32,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn slo(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn slo<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         let data = bus.read(mode.addr.unwrap());
         let data_to_write = data << 1;
         bus.write(mode.addr.unwrap(), data_to_write);
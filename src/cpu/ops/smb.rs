@@ -0,0 +1,73 @@
+/*
+    SMB - Set Memory Bit (65C02)
+    Operation: 1 → Mb
+
+    SMB0 to SMB7 set bit 0 to 7 of a zero page memory
+    location, leaving the other bits unchanged.
+
+    SMB does not affect any flags or registers.
+*/
+
+use crate::{bus::Bus, cpu::addr::AddrModeResult};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn smbc(&self, _mode: &AddrModeResult) -> u8 {
+        5
+    }
+
+    pub(in crate::cpu) fn smb<const BIT: u8, B: Bus + ?Sized>(
+        &mut self,
+        mode: &AddrModeResult,
+        bus: &mut B,
+    ) {
+        let data = bus.read(mode.addr.unwrap());
+        bus.write(mode.addr.unwrap(), data | (1 << BIT));
+    }
+}
+
+#[cfg(test)]
+mod smb_tests {
+    use mockall::predicate::eq;
+
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_smb_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        let bus = MockBus::new();
+
+        assert_eq!(5, cpu.smbc(&cpu._zp(0x0, &bus)));
+    }
+
+    #[test]
+    fn test_smb0_sets_bit_0() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+
+        bus.expect_read().with(eq(0x10)).return_const(0x0);
+        bus.expect_write()
+            .with(eq(0x10), eq(0x01))
+            .times(1)
+            .return_const(());
+
+        cpu.smb::<0, _>(&cpu._zp(0x10, &bus), &mut bus);
+    }
+
+    #[test]
+    fn test_smb7_sets_bit_7() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+
+        bus.expect_read().with(eq(0x10)).return_const(0x0);
+        bus.expect_write()
+            .with(eq(0x10), eq(0x80))
+            .times(1)
+            .return_const(());
+
+        cpu.smb::<7, _>(&cpu._zp(0x10, &bus), &mut bus);
+    }
+}
@@ -24,7 +24,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn sta(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn sta<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         bus.write(mode.addr.unwrap(), self.a);
     }
 }
@@ -0,0 +1,34 @@
+/*
+    STP - Stop the Clock (65C02)
+    Operation: Stop execution
+
+    This instruction stops the microprocessor until it is reset.
+    Neither IRQs nor NMIs are handled while stopped.
+
+    STP does not affect any flags or registers.
+*/
+
+use crate::{bus::Bus, cpu::addr::AddrModeResult};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn stpc(&self, _mode: &AddrModeResult) -> u8 {
+        3
+    }
+
+    pub(in crate::cpu) fn stp<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
+        //The stop itself is a CPU state, entered once this completes
+    }
+}
+
+#[cfg(test)]
+mod stp_tests {
+    use super::*;
+
+    #[test]
+    fn test_stp_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        assert_eq!(3, cpu.stpc(&cpu._imp()));
+    }
+}
@@ -23,7 +23,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn stx(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn stx<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         bus.write(mode.addr.unwrap(), self.x);
     }
 }
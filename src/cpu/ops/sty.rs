@@ -22,7 +22,7 @@ impl NESCPU {
         }
     }
 
-    pub(in crate::cpu) fn sty(&mut self, mode: &AddrModeResult, bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn sty<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
         bus.write(mode.addr.unwrap(), self.y);
     }
 }
@@ -0,0 +1,86 @@
+/*
+    STZ - Store Zero in Memory (65C02)
+    Operation: 0 → M
+
+    This instruction transfers zero into memory.
+
+    STZ does not affect any flags or registers in the
+    microprocessor.
+*/
+
+use crate::{
+    bus::Bus,
+    cpu::addr::{AddrModeResult, AddrModeType},
+};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn stzc(&self, mode: &AddrModeResult) -> u8 {
+        match mode.mode {
+            AddrModeType::Absx => 5,
+            _ => 2 + mode.cycles,
+        }
+    }
+
+    pub(in crate::cpu) fn stz<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
+        bus.write(mode.addr.unwrap(), 0x0);
+    }
+}
+
+#[cfg(test)]
+mod stz_tests {
+    use mockall::predicate::eq;
+
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_stz_zp_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        let bus = MockBus::new();
+
+        assert_eq!(3, cpu.stzc(&cpu._zp(0x0, &bus)));
+    }
+
+    #[test]
+    fn test_stz_zpx_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        let bus = MockBus::new();
+
+        assert_eq!(4, cpu.stzc(&cpu._zpx(0x0, &bus)));
+    }
+
+    #[test]
+    fn test_stz_abs_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        let bus = MockBus::new();
+
+        assert_eq!(4, cpu.stzc(&cpu._abs(0x0, &bus)));
+    }
+
+    #[test]
+    fn test_stz_absx_correct_number_of_cycles() {
+        let mut cpu = NESCPU::new();
+        let bus = MockBus::new();
+        cpu.x = 0xff;
+
+        assert_eq!(5, cpu.stzc(&cpu._absx(0x0, &bus)));
+        assert_eq!(5, cpu.stzc(&cpu._absx(0x1234, &bus)));
+    }
+
+    #[test]
+    fn test_stz_stores_zero() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.a = 0xff;
+
+        bus.expect_write()
+            .with(eq(0x1234), eq(0x0))
+            .times(1)
+            .return_const(());
+
+        cpu.stz(&cpu._abs(0x1234, &bus), &mut bus);
+    }
+}
@@ -22,7 +22,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn tax(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn tax<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.x = self.a;
 
         self.n = (self.x & 0x80) > 0;
@@ -21,7 +21,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn tay(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn tay<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.y = self.a;
 
         self.n = (self.y & 0x80) > 0;
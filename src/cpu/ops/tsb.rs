@@ -0,0 +1,85 @@
+/*
+    TSB - Test and Set Memory Bits (65C02)
+    Operation: M ∨ A → M, A ∧ M → Z
+
+    The Z flag is set if the logical AND of the accumulator and
+    the memory value (before it is modified) is zero, otherwise
+    it is reset. The bits that are set in the accumulator are
+    then set in memory.
+
+    TSB does not affect any other flags or registers.
+*/
+
+use crate::{bus::Bus, cpu::addr::AddrModeResult};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn tsbc(&self, mode: &AddrModeResult) -> u8 {
+        4 + mode.cycles
+    }
+
+    pub(in crate::cpu) fn tsb<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, bus: &mut B) {
+        let data = bus.read(mode.addr.unwrap());
+
+        self.z = (self.a & data) == 0;
+        bus.write(mode.addr.unwrap(), data | self.a);
+    }
+}
+
+#[cfg(test)]
+mod tsb_tests {
+    use mockall::predicate::eq;
+
+    use crate::bus::MockBus;
+
+    use super::*;
+
+    #[test]
+    fn test_tsb_zp_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        let bus = MockBus::new();
+
+        assert_eq!(5, cpu.tsbc(&cpu._zp(0x0, &bus)));
+    }
+
+    #[test]
+    fn test_tsb_abs_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        let bus = MockBus::new();
+
+        assert_eq!(6, cpu.tsbc(&cpu._abs(0x0, &bus)));
+    }
+
+    #[test]
+    fn test_tsb_modifies_memory() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.a = 0b0011_0011;
+
+        bus.expect_read().with(eq(0x10)).return_const(0b0101_0101);
+        bus.expect_write()
+            .with(eq(0x10), eq(0b0111_0111))
+            .times(1)
+            .return_const(());
+
+        cpu.tsb(&cpu._zp(0x10, &bus), &mut bus);
+
+        assert_eq!(false, cpu.z);
+        assert_eq!(0b0011_0011, cpu.a);
+    }
+
+    #[test]
+    fn test_tsb_zero_flag() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.a = 0b1010_1010;
+
+        bus.expect_read().with(eq(0x10)).return_const(0b0101_0101);
+        bus.expect_write().return_const(());
+
+        cpu.tsb(&cpu._zp(0x10, &bus), &mut bus);
+
+        assert_eq!(true, cpu.z);
+    }
+}
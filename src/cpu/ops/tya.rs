@@ -7,7 +7,7 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn tya(&mut self, _mode: &AddrModeResult, _bus: &mut dyn Bus) {
+    pub(in crate::cpu) fn tya<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
         self.a = self.y;
 
         self.n = (self.a & 0x80) > 0;
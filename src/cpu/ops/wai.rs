@@ -0,0 +1,37 @@
+/*
+    WAI - Wait for Interrupt (65C02)
+    Operation: Stop until an interrupt
+
+    This instruction halts the microprocessor until an NMI or
+    IRQ is signalled. An NMI, or an IRQ when the interrupt
+    disable flag is reset, is then serviced as usual. An IRQ
+    while interrupts are disabled simply resumes execution with
+    the next instruction.
+
+    WAI does not affect any flags or registers.
+*/
+
+use crate::{bus::Bus, cpu::addr::AddrModeResult};
+
+use super::super::NESCPU;
+
+impl NESCPU {
+    pub(in crate::cpu) fn waic(&self, _mode: &AddrModeResult) -> u8 {
+        3
+    }
+
+    pub(in crate::cpu) fn wai<B: Bus + ?Sized>(&mut self, _mode: &AddrModeResult, _bus: &mut B) {
+        //The wait itself is a CPU state, entered once this completes
+    }
+}
+
+#[cfg(test)]
+mod wai_tests {
+    use super::*;
+
+    #[test]
+    fn test_wai_correct_number_of_cycles() {
+        let cpu = NESCPU::new();
+        assert_eq!(3, cpu.waic(&cpu._imp()));
+    }
+}
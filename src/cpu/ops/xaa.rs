@@ -13,6 +13,7 @@
     individual microprocessor and is usually one of $00, $EE, $EF, $FE and $FF,
     and may be influenced by the RDY pin, leftover contents of the data bus,
     the temperature of the microprocessor, the supplied voltage, and other factors.
+    The constant used here is configured through UnstableOpcodes.
 
     On some machines, additional bits of the result may be set or reset depending
     on non-deterministic factors.
@@ -24,8 +25,6 @@
     reset.
 */
 
-use rand::seq::SliceRandom;
-
 use crate::{
     bus::Bus,
     cpu::{addr::AddrModeResult, NESCPU},
@@ -36,10 +35,8 @@ impl NESCPU {
         2
     }
 
-    pub(in crate::cpu) fn xaa(&mut self, mode: &AddrModeResult, _bus: &mut dyn Bus) {
-        let magic_constant = *vec![0x00, 0xee, 0xef, 0xfe, 0xff]
-            .choose(&mut rand::thread_rng())
-            .unwrap() as u8;
+    pub(in crate::cpu) fn xaa<B: Bus + ?Sized>(&mut self, mode: &AddrModeResult, _bus: &mut B) {
+        let magic_constant = self.unstable_magic();
         self.a = (self.a | magic_constant) & self.x & mode.data.unwrap();
 
         self.n = (self.a & 0x80) != 0;
@@ -49,7 +46,7 @@ impl NESCPU {
 
 #[cfg(test)]
 mod xaa_tests {
-    use crate::bus::MockBus;
+    use crate::{bus::MockBus, cpu::UnstableOpcodes};
 
     use super::*;
 
@@ -66,6 +63,20 @@ mod xaa_tests {
         cpu.x = 0xff;
         cpu.xaa(&cpu._imm(0xff), &mut bus);
 
-        assert_eq!(true, vec![0x00, 0xee, 0xef, 0xfe, 0xff].contains(&cpu.a));
+        assert_eq!(0xee, cpu.a);
+        assert_eq!(true, cpu.n);
+        assert_eq!(false, cpu.z);
+    }
+
+    #[test]
+    fn test_xaa_fixed_magic_constant() {
+        let mut cpu = NESCPU::new();
+        let mut bus = MockBus::new();
+        cpu.set_unstable_opcodes(UnstableOpcodes::Fixed { magic: 0x0 });
+        cpu.a = 0x0f;
+        cpu.x = 0xff;
+        cpu.xaa(&cpu._imm(0x3c), &mut bus);
+
+        assert_eq!(0x0c, cpu.a);
     }
 }
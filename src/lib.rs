@@ -1,3 +1,5 @@
+#![cfg_attr(test, allow(clippy::bool_assert_comparison))]
+
 pub mod bus;
 pub mod cartridge;
 pub mod controller;
@@ -1,31 +1,28 @@
-use minifb::{Icon, Key, Window, WindowOptions};
+#[cfg(target_os = "windows")]
+use minifb::Icon;
+use minifb::{Key, KeyRepeat, Scale, Window, WindowOptions};
 
 use nes_emu::{
-    bus::{cpu_bus::CPUBus, ppu_bus::PPUBus},
-    cartridge::NESCartridge,
+    bus::{cpu_bus::CPUBus, ppu_bus::PPUBus, Bus},
+    cartridge::{Cartridge, NESCartridge},
     controller::NESController,
     cpu::NESCPU,
-    mapper::mapper_factory,
-    ppu::NESPPU,
-    util::{extract_chr_rom, extract_header, extract_prg_rom, read_bytes_from_file},
+    mapper::submapper_factory,
+    ppu::{debug::DebugImage, palette::frame_to_argb, PowerOnState, NESPPU, PPU},
+    util::{extract_chr_rom, extract_header, extract_prg_rom, read_bytes_from_file, Region},
 };
-use std::{
-    cell::RefCell,
-    rc::Rc,
-    time::{Duration, Instant},
-};
-
-#[rustfmt::skip]
-const SCREEN_COLORS: [(u8, u8, u8); 0x40] = [
-    ( 84,  84,  84), (  0,  30, 116), (  8,  16, 144), ( 48,   0, 136), ( 68,   0, 100), ( 92,   0,  48), ( 84,   4,   0), ( 60,  24,   0),
-    ( 32,  42,   0), (  8,  58,   0), (  0,  64,   0), (  0,  60,   0), (  0,  50,  60), (  0,   0,   0), (  0,   0,   0), (  0,   0,   0),
-    (152, 150, 152), (  8,  76, 196), ( 48,  50, 236), ( 92,  30, 228), (136,  20, 176), (160,  20, 100), (152,  34,  32), (120,  60,   0),
-    ( 84,  90,   0), ( 40, 114,   0), (  8, 124,   0), (  0, 118,  40), (  0, 102, 120), (  0,   0,   0), (  0,   0,   0), (  0,   0,   0),
-    (236, 238, 236), ( 76, 154, 236), (120, 124, 236), (176,  98, 236), (228,  84, 236), (236,  88, 180), (236, 106, 100), (212, 136,  32),
-    (160, 170,   0), (116, 196,   0), ( 76, 208,  32), ( 56, 204, 108), ( 56, 180, 204), ( 60,  60,  60), (  0,   0,   0), (  0,   0,   0),
-    (236, 238, 236), (168, 204, 236), (188, 188, 236), (212, 178, 236), (236, 174, 236), (236, 174, 212), (236, 180, 176), (228, 196, 144),
-    (204, 210, 120), (180, 222, 120), (168, 226, 144), (152, 226, 180), (160, 214, 228), (160, 162, 160), (  0,   0,   0), (  0,   0,   0),
-];
+use std::{cell::RefCell, rc::Rc, time::Instant};
+
+//Debug views, toggled with F1-F4 from the main window. F5 cycles the
+//palette the pattern tables are drawn with
+#[derive(Default)]
+struct DebugWindows {
+    nametables: Option<Window>,
+    pattern_tables: Option<Window>,
+    oam: Option<Window>,
+    palettes: Option<Window>,
+    pattern_palette: u8,
+}
 
 fn main() {
     let bytes = read_bytes_from_file("roms/dk.nes".to_owned());
@@ -34,12 +31,22 @@ fn main() {
     let prg_rom = extract_prg_rom(&header, &bytes);
     let chr_rom = extract_chr_rom(&header, &bytes);
 
-    let mapper = mapper_factory(header.mapper_num);
+    let mapper = submapper_factory(header.mapper_num, header.submapper);
+
+    //The header's timing can be overridden for dumps that don't record it
+    let region = match std::env::args().nth(1).as_deref() {
+        Some("ntsc") => Region::NTSC,
+        Some("pal") => Region::PAL,
+        Some("dendy") => Region::DENDY,
+        Some(arg) => panic!("Unknown region {}, expected ntsc, pal or dendy", arg),
+        None => header.region,
+    };
 
     let cartridge_cpu = Rc::new(NESCartridge::new(
         prg_rom,
         chr_rom,
-        Box::new(mapper),
+        header.prg_ram_bytes,
+        mapper,
         header.mirroring,
     ));
     let cartridge_ppu = Rc::clone(&cartridge_cpu);
@@ -51,13 +58,15 @@ fn main() {
     let controller_2_clone = Rc::clone(&controller_2);
 
     let mut cpu = NESCPU::new();
-    let ppu = NESPPU::new(Box::new(PPUBus::new(cartridge_ppu)));
+    let mut ppu = NESPPU::new(Box::new(PPUBus::new(cartridge_ppu)));
+    ppu.power_on(&PowerOnState::default());
     let mut main_bus = CPUBus::new(
         Box::new(ppu),
         cartridge_cpu,
         controller_1_clone,
         controller_2_clone,
     );
+    main_bus.set_region(region);
 
     let mut window = Window::new("NES Emulator", 512, 480, WindowOptions::default())
         .unwrap_or_else(|e| {
@@ -67,18 +76,22 @@ fn main() {
     #[cfg(target_os = "windows")]
     window.set_icon(Icon::from_str("res/icon.ico").unwrap());
 
-    let frame_duration = Duration::new(0, 16_666_600);
+    let frame_duration = region.frame_duration();
+    let mut screen_buffer = vec![0x0; 256 * 240];
+    let mut debug_windows = DebugWindows::default();
     while window.is_open() && !window.is_key_down(Key::Escape) {
         let start = Instant::now();
         //Update screen
-        update_screen_buffer(&main_bus, &mut window);
+        update_screen_buffer(&main_bus, &mut window, &mut screen_buffer);
+        update_debug_windows(&mut debug_windows, &window, main_bus.get_ppu());
 
         //Update controller input
         update_controller_input(&window, &controller_1);
 
         //Emulate one frame's worth of cycles
-        while !main_bus.is_frame_completed() {
-            main_bus.clock(&mut cpu);
+        if let Err(jam) = main_bus.run_frame(&mut cpu) {
+            eprintln!("{}", jam);
+            break;
         }
 
         while start.elapsed() < frame_duration {}
@@ -101,16 +114,82 @@ fn update_controller_input(window: &Window, controller_1: &Rc<RefCell<NESControl
 }
 
 #[inline]
-fn update_screen_buffer(main_bus: &CPUBus<'_>, window: &mut Window) {
-    let buffer: Vec<u32> = main_bus
-        .get_frame_from_ppu()
-        .iter()
-        .flatten()
-        .map(|&value| {
-            let rgb = SCREEN_COLORS[value as usize];
-            (rgb.0 as u32) << 16 | (rgb.1 as u32) << 8 | rgb.2 as u32
-        })
-        .collect();
-
-    window.update_with_buffer(&buffer, 256, 240).unwrap();
+fn update_screen_buffer<P: PPU + ?Sized, C: Cartridge + ?Sized>(
+    main_bus: &CPUBus<'_, P, C>,
+    window: &mut Window,
+    buffer: &mut [u32],
+) {
+    frame_to_argb(main_bus.get_frame_from_ppu(), buffer);
+    window.update_with_buffer(buffer, 256, 240).unwrap();
+}
+
+fn toggle_window(window: &mut Option<Window>, title: &str, size: (usize, usize), scale: Scale) {
+    *window = match window.take() {
+        Some(_) => None,
+        None => {
+            let options = WindowOptions {
+                scale,
+                ..WindowOptions::default()
+            };
+            Some(
+                Window::new(title, size.0, size.1, options).unwrap_or_else(|e| {
+                    panic!("{}", e);
+                }),
+            )
+        }
+    };
+}
+
+//Only builds the image while its window is open
+fn show_debug_image(window: &mut Option<Window>, image: impl FnOnce() -> DebugImage) {
+    if window.as_ref().is_some_and(|window| !window.is_open()) {
+        *window = None;
+    }
+
+    if let Some(window) = window {
+        let image = image();
+        window
+            .update_with_buffer(&image.pixels, image.width, image.height)
+            .unwrap();
+    }
+}
+
+fn update_debug_windows<B: Bus + ?Sized>(
+    debug_windows: &mut DebugWindows,
+    window: &Window,
+    ppu: &NESPPU<B>,
+) {
+    if window.is_key_pressed(Key::F1, KeyRepeat::No) {
+        toggle_window(
+            &mut debug_windows.nametables,
+            "Nametables",
+            (512, 480),
+            Scale::X1,
+        );
+    }
+    if window.is_key_pressed(Key::F2, KeyRepeat::No) {
+        toggle_window(
+            &mut debug_windows.pattern_tables,
+            "Pattern tables",
+            (256, 128),
+            Scale::X2,
+        );
+    }
+    if window.is_key_pressed(Key::F3, KeyRepeat::No) {
+        toggle_window(&mut debug_windows.oam, "OAM", (64, 128), Scale::X4);
+    }
+    if window.is_key_pressed(Key::F4, KeyRepeat::No) {
+        toggle_window(&mut debug_windows.palettes, "Palettes", (16, 2), Scale::X16);
+    }
+    if window.is_key_pressed(Key::F5, KeyRepeat::No) {
+        debug_windows.pattern_palette = (debug_windows.pattern_palette + 1) % 8;
+    }
+
+    let pattern_palette = debug_windows.pattern_palette;
+    show_debug_image(&mut debug_windows.nametables, || ppu.render_nametables());
+    show_debug_image(&mut debug_windows.pattern_tables, || {
+        ppu.render_pattern_tables(pattern_palette)
+    });
+    show_debug_image(&mut debug_windows.oam, || ppu.render_oam());
+    show_debug_image(&mut debug_windows.palettes, || ppu.render_palettes());
 }
@@ -1,21 +1,56 @@
-use self::mapper_0::Mapper0;
 use mockall::automock;
 
+use crate::util::Mirroring;
+
 mod mapper_0;
+mod mapper_15;
+mod mapper_225;
+mod mapper_227;
+mod mapper_228;
+mod mapper_60;
+pub mod registry;
 
 #[automock]
 pub trait Mapper {
-    fn read_prg(&self, addr: u16, prg_banks: u8) -> u16;
+    fn read_prg(&self, addr: u16, prg_banks: u8) -> u32;
     fn write_prg(&self, addr: u16, data: u8, prg_banks: u8);
-    fn read_chr(&self, addr: u16, chr_banks: u8) -> u16;
+    fn read_chr(&self, addr: u16, chr_banks: u8) -> u32;
     fn write_chr(&self, addr: u16, data: u8, chr_banks: u8);
-}
 
-pub fn mapper_factory(mapper: u8) -> impl Mapper {
-    match mapper {
-        0 => Mapper0::new(),
-        mapper => panic!("Mapper {mapper} has not been implemented"),
+    //Offset into the work RAM for $6000-$7FFF, used for reads and writes;
+    //None leaves the bus undriven, as boards without RAM do
+    fn map_prg_ram(&self, addr: u16, prg_ram_bytes: u32) -> Option<u32> {
+        (prg_ram_bytes > 0).then(|| (addr as u32 & 0x1fff) % prg_ram_bytes)
+    }
+
+    //Registers or memory at $4020-$5FFF; None leaves the bus undriven
+    fn read_expansion(&self, _addr: u16) -> Option<u8> {
+        None
+    }
+    fn write_expansion(&self, _addr: u16, _data: u8) {}
+    //Reads of expansion registers may have side effects, so mappers opt in
+    //to being peeked
+    fn peek_expansion(&self, _addr: u16) -> Option<u8> {
+        None
     }
+
+    //None if the mirroring is hardwired on the board (taken from the header)
+    fn get_mirroring(&self) -> Option<Mirroring>;
+    fn reset(&self);
+}
+
+pub fn mapper_factory(mapper: u8) -> Box<dyn Mapper> {
+    submapper_factory(mapper as u16, 0)
+}
+
+pub fn submapper_factory(mapper: u16, submapper: u8) -> Box<dyn Mapper> {
+    registry::create_mapper(mapper, submapper)
+        .unwrap_or_else(|| panic!("Mapper {mapper}.{submapper} has not been implemented"))
+}
+
+pub fn board_factory(board: &str) -> Box<dyn Mapper> {
+    registry::create_board(board)
+        .unwrap_or_else(|| panic!("Board {board} has not been implemented"))
 }
 
 #[cfg(test)]
@@ -32,4 +67,15 @@ mod mapper_tests {
     fn test_mapper_factory_with_unimplemented_mapper() {
         mapper_factory(0xff);
     }
+
+    #[test]
+    fn test_board_factory_with_nrom() {
+        board_factory("NES-NROM-256");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_board_factory_with_unimplemented_board() {
+        board_factory("UNL-UNKNOWN");
+    }
 }
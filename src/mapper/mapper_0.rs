@@ -1,4 +1,5 @@
 use super::Mapper;
+use crate::util::Mirroring;
 
 pub struct Mapper0;
 
@@ -9,10 +10,10 @@ impl Mapper0 {
 }
 
 impl Mapper for Mapper0 {
-    fn read_prg(&self, addr: u16, prg_banks: u8) -> u16 {
+    fn read_prg(&self, addr: u16, prg_banks: u8) -> u32 {
         assert!((0x8000..=0xffff).contains(&addr));
         assert!((1..=2).contains(&prg_banks));
-        let offset = addr - 0x8000;
+        let offset = (addr - 0x8000) as u32;
 
         if prg_banks == 1 {
             offset & 0x3fff
@@ -26,17 +27,23 @@ impl Mapper for Mapper0 {
         assert!((1..=2).contains(&prg_banks));
     }
 
-    fn read_chr(&self, addr: u16, chr_banks: u8) -> u16 {
+    fn read_chr(&self, addr: u16, chr_banks: u8) -> u32 {
         assert!((..=0x1fff).contains(&addr));
         assert!(chr_banks == 1);
 
-        addr
+        addr as u32
     }
 
     fn write_chr(&self, addr: u16, _data: u8, chr_banks: u8) {
         assert!((..=0x1fff).contains(&addr));
         assert!(chr_banks == 1);
     }
+
+    fn get_mirroring(&self) -> Option<Mirroring> {
+        None
+    }
+
+    fn reset(&self) {}
 }
 
 #[cfg(test)]
@@ -45,10 +52,6 @@ mod mapper0_tests {
 
     #[test]
     fn test_mapper0_one_prg_rom_bank() {
-        let mut prg_rom: [u8; 16384] = [0; 16384];
-
-        prg_rom[0] = 0xff;
-
         let mapper = Mapper0::new();
 
         //Memory is mirrored
@@ -58,11 +61,6 @@ mod mapper0_tests {
 
     #[test]
     fn test_mapper0_two_prg_rom_banks() {
-        let mut prg_rom: [u8; 2 * 16384] = [0; 2 * 16384];
-
-        prg_rom[0] = 0xff;
-        prg_rom[0x4000] = 0xee;
-
         let mapper = Mapper0::new();
 
         //Memory is not mirrored
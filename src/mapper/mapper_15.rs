@@ -0,0 +1,149 @@
+/*
+    Mapper 15 - 100-in-1 Contra Function 16
+
+    A single register at $8000-$FFFF. The bank mode is taken from
+    address lines A1-A0 and the data byte holds the bank number:
+
+    D~[BMPP PPPP]
+       |||| ||||
+       ||++-++++- 16 KiB PRG bank
+       |+-------- Mirroring (0: vertical, 1: horizontal)
+       +--------- 8 KiB half of the bank (NROM-64 mode only)
+
+    Modes:
+    0 - NROM-256: P at $8000, P|1 at $C000
+    1 - UNROM:    P at $8000, P|7 at $C000
+    2 - NROM-64:  8 KiB bank (P*2+B) mirrored across $8000-$FFFF
+    3 - NROM-128: P at both $8000 and $C000
+
+    CHR is 8 KiB of unbanked RAM.
+*/
+
+use std::cell::Cell;
+
+use super::Mapper;
+use crate::util::Mirroring;
+
+pub struct Mapper15 {
+    mode: Cell<u8>,
+    data: Cell<u8>,
+}
+
+impl Mapper15 {
+    pub fn new() -> Mapper15 {
+        Mapper15 {
+            mode: Cell::new(0),
+            data: Cell::new(0),
+        }
+    }
+}
+
+impl Mapper for Mapper15 {
+    fn read_prg(&self, addr: u16, prg_banks: u8) -> u32 {
+        assert!((0x8000..=0xffff).contains(&addr));
+        let bank = (self.data.get() & 0x3f) as u32;
+        let upper = addr >= 0xc000;
+
+        let offset = match self.mode.get() {
+            0 => (if upper { bank | 1 } else { bank }) * 0x4000 + (addr & 0x3fff) as u32,
+            1 => (if upper { bank | 7 } else { bank }) * 0x4000 + (addr & 0x3fff) as u32,
+            2 => {
+                let half = (self.data.get() >> 7) as u32;
+                (bank * 2 + half) * 0x2000 + (addr & 0x1fff) as u32
+            }
+            _ => bank * 0x4000 + (addr & 0x3fff) as u32,
+        };
+
+        offset % (prg_banks as u32 * 0x4000)
+    }
+
+    fn write_prg(&self, addr: u16, data: u8, _prg_banks: u8) {
+        assert!((0x8000..=0xffff).contains(&addr));
+        self.mode.set((addr & 0x3) as u8);
+        self.data.set(data);
+    }
+
+    fn read_chr(&self, addr: u16, _chr_banks: u8) -> u32 {
+        assert!((..=0x1fff).contains(&addr));
+        addr as u32
+    }
+
+    fn write_chr(&self, addr: u16, _data: u8, _chr_banks: u8) {
+        assert!((..=0x1fff).contains(&addr));
+    }
+
+    fn get_mirroring(&self) -> Option<Mirroring> {
+        if (self.data.get() & 0x40) != 0 {
+            Some(Mirroring::HORIZONTAL)
+        } else {
+            Some(Mirroring::VERTICAL)
+        }
+    }
+
+    fn reset(&self) {
+        self.mode.set(0);
+        self.data.set(0);
+    }
+}
+
+#[cfg(test)]
+mod mapper15_tests {
+    use super::*;
+
+    #[test]
+    fn test_mapper15_nrom_256_mode() {
+        let mapper = Mapper15::new();
+        mapper.write_prg(0x8000, 0x04, 64);
+
+        assert_eq!(4 * 0x4000, mapper.read_prg(0x8000, 64));
+        assert_eq!(5 * 0x4000 + 0x10, mapper.read_prg(0xc010, 64));
+    }
+
+    #[test]
+    fn test_mapper15_unrom_mode() {
+        let mapper = Mapper15::new();
+        mapper.write_prg(0x8001, 0x08, 64);
+
+        assert_eq!(8 * 0x4000, mapper.read_prg(0x8000, 64));
+        assert_eq!(15 * 0x4000, mapper.read_prg(0xc000, 64));
+    }
+
+    #[test]
+    fn test_mapper15_nrom_64_mode() {
+        let mapper = Mapper15::new();
+        mapper.write_prg(0x8002, 0x83, 64);
+
+        assert_eq!(7 * 0x2000, mapper.read_prg(0x8000, 64));
+        assert_eq!(7 * 0x2000, mapper.read_prg(0xa000, 64));
+        assert_eq!(7 * 0x2000 + 0x1fff, mapper.read_prg(0xffff, 64));
+    }
+
+    #[test]
+    fn test_mapper15_nrom_128_mode() {
+        let mapper = Mapper15::new();
+        mapper.write_prg(0x8003, 0x02, 64);
+
+        assert_eq!(2 * 0x4000, mapper.read_prg(0x8000, 64));
+        assert_eq!(2 * 0x4000, mapper.read_prg(0xc000, 64));
+    }
+
+    #[test]
+    fn test_mapper15_mirroring() {
+        let mapper = Mapper15::new();
+        assert_eq!(Some(Mirroring::VERTICAL), mapper.get_mirroring());
+
+        mapper.write_prg(0x8000, 0x40, 64);
+        assert_eq!(Some(Mirroring::HORIZONTAL), mapper.get_mirroring());
+    }
+
+    #[test]
+    fn test_mapper15_reset() {
+        let mapper = Mapper15::new();
+        mapper.write_prg(0x8003, 0x42, 64);
+        mapper.reset();
+
+        assert_eq!(0x0, mapper.read_prg(0x8000, 64));
+        assert_eq!(0x4000, mapper.read_prg(0xc000, 64));
+        assert_eq!(Some(Mirroring::VERTICAL), mapper.get_mirroring());
+    }
+}
@@ -0,0 +1,139 @@
+/*
+    Mapper 225 - 52 Games / 58-in-1 / 64-in-1
+
+    The register is latched from the address lines of any
+    write to $8000-$FFFF (the data byte is ignored):
+
+    A~[1HMO PPPP PPCC CCCC]
+        |||  |||| ||||||||
+        |||  ++++-++++++++- CHR bank (8 KiB) / PRG bank (16 KiB)
+        ||+---------------- PRG mode (0: 32 KiB, 1: 16 KiB mirrored)
+        |+----------------- Mirroring (0: vertical, 1: horizontal)
+        +------------------ Outer bank (selects the second ROM chip)
+
+    The latch is cleared on reset, returning to the menu.
+*/
+
+use std::cell::Cell;
+
+use super::Mapper;
+use crate::util::Mirroring;
+
+pub struct Mapper225 {
+    latch: Cell<u16>,
+}
+
+impl Mapper225 {
+    pub fn new() -> Mapper225 {
+        Mapper225 {
+            latch: Cell::new(0),
+        }
+    }
+
+    fn outer_bank(&self) -> u32 {
+        ((self.latch.get() >> 14) & 0x1) as u32
+    }
+}
+
+impl Mapper for Mapper225 {
+    fn read_prg(&self, addr: u16, prg_banks: u8) -> u32 {
+        assert!((0x8000..=0xffff).contains(&addr));
+        let latch = self.latch.get();
+        let bank = (self.outer_bank() << 6) | ((latch >> 6) & 0x3f) as u32;
+
+        let offset = if (latch & 0x1000) != 0 {
+            bank * 0x4000 + (addr & 0x3fff) as u32
+        } else {
+            (bank >> 1) * 0x8000 + (addr & 0x7fff) as u32
+        };
+
+        offset % (prg_banks as u32 * 0x4000)
+    }
+
+    fn write_prg(&self, addr: u16, _data: u8, _prg_banks: u8) {
+        assert!((0x8000..=0xffff).contains(&addr));
+        self.latch.set(addr);
+    }
+
+    fn read_chr(&self, addr: u16, chr_banks: u8) -> u32 {
+        assert!((..=0x1fff).contains(&addr));
+        let bank = (self.outer_bank() << 6) | (self.latch.get() & 0x3f) as u32;
+
+        (bank * 0x2000 + addr as u32) % (chr_banks as u32 * 0x2000)
+    }
+
+    fn write_chr(&self, addr: u16, _data: u8, _chr_banks: u8) {
+        assert!((..=0x1fff).contains(&addr));
+    }
+
+    fn get_mirroring(&self) -> Option<Mirroring> {
+        if (self.latch.get() & 0x2000) != 0 {
+            Some(Mirroring::HORIZONTAL)
+        } else {
+            Some(Mirroring::VERTICAL)
+        }
+    }
+
+    fn reset(&self) {
+        self.latch.set(0);
+    }
+}
+
+#[cfg(test)]
+mod mapper225_tests {
+    use super::*;
+
+    #[test]
+    fn test_mapper225_32k_prg_mode() {
+        let mapper = Mapper225::new();
+        mapper.write_prg(0x8000 | (0x05 << 6), 0x0, 64);
+
+        assert_eq!(2 * 0x8000, mapper.read_prg(0x8000, 64));
+        assert_eq!(2 * 0x8000 + 0x7fff, mapper.read_prg(0xffff, 64));
+    }
+
+    #[test]
+    fn test_mapper225_16k_prg_mode() {
+        let mapper = Mapper225::new();
+        mapper.write_prg(0x9000 | (0x05 << 6), 0x0, 64);
+
+        assert_eq!(5 * 0x4000, mapper.read_prg(0x8000, 64));
+        assert_eq!(5 * 0x4000, mapper.read_prg(0xc000, 64));
+    }
+
+    #[test]
+    fn test_mapper225_outer_bank() {
+        let mapper = Mapper225::new();
+        mapper.write_prg(0xd000 | (0x01 << 6) | 0x02, 0x0, 128);
+
+        assert_eq!(65 * 0x4000, mapper.read_prg(0x8000, 128));
+        assert_eq!(66 * 0x2000 + 0x10, mapper.read_chr(0x10, 128));
+    }
+
+    #[test]
+    fn test_mapper225_chr_bank() {
+        let mapper = Mapper225::new();
+        mapper.write_prg(0x8000 | 0x3, 0x0, 64);
+
+        assert_eq!(3 * 0x2000 + 0x1234, mapper.read_chr(0x1234, 64));
+    }
+
+    #[test]
+    fn test_mapper225_mirroring() {
+        let mapper = Mapper225::new();
+        assert_eq!(Some(Mirroring::VERTICAL), mapper.get_mirroring());
+
+        mapper.write_prg(0xa000, 0x0, 64);
+        assert_eq!(Some(Mirroring::HORIZONTAL), mapper.get_mirroring());
+    }
+
+    #[test]
+    fn test_mapper225_reset_returns_to_menu() {
+        let mapper = Mapper225::new();
+        mapper.write_prg(0xbfff, 0x0, 64);
+        mapper.reset();
+
+        assert_eq!(0x0, mapper.read_prg(0x8000, 64));
+        assert_eq!(0x0, mapper.read_chr(0x0, 64));
+    }
+}
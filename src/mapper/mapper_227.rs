@@ -0,0 +1,146 @@
+/*
+    Mapper 227 - 1200-in-1 / 600-in-1
+
+    The register is latched from the address lines of any
+    write to $8000-$FFFF (the data byte is ignored):
+
+    A~[1... ..LP OPPP PPMS]
+              || |||| ||||
+              || |||| |||+- PRG size (0: 16 KiB, 1: 32 KiB)
+              || |||| ||+-- Mirroring (0: vertical, 1: horizontal)
+              |+-|+++-++--- PRG bank (16 KiB)
+              |  +--------- Mode (0: UNROM-like, 1: NROM)
+              +------------ Last bank (UNROM mode only)
+
+    In UNROM mode $C000 is fixed to either the first (L=0) or
+    last (L=1) bank of the current 128 KiB block.
+
+    CHR is 8 KiB of unbanked RAM. The latch is cleared on reset.
+*/
+
+use std::cell::Cell;
+
+use super::Mapper;
+use crate::util::Mirroring;
+
+pub struct Mapper227 {
+    latch: Cell<u16>,
+}
+
+impl Mapper227 {
+    pub fn new() -> Mapper227 {
+        Mapper227 {
+            latch: Cell::new(0),
+        }
+    }
+}
+
+impl Mapper for Mapper227 {
+    fn read_prg(&self, addr: u16, prg_banks: u8) -> u32 {
+        assert!((0x8000..=0xffff).contains(&addr));
+        let latch = self.latch.get();
+
+        let bank = (((latch >> 2) & 0x1f) | ((latch & 0x100) >> 3)) as u32;
+        let size_32k = (latch & 0x1) != 0;
+        let nrom = (latch & 0x80) != 0;
+        let last_bank = (latch & 0x200) != 0;
+        let upper = addr >= 0xc000;
+
+        let bank = match (nrom, size_32k, upper) {
+            (true, true, _) => (bank & !0x1) | upper as u32,
+            (true, false, _) => bank,
+            (false, true, false) => bank & 0x3e,
+            (false, false, false) => bank,
+            (false, _, true) if last_bank => bank | 0x7,
+            (false, _, true) => bank & 0x38,
+        };
+
+        (bank * 0x4000 + (addr & 0x3fff) as u32) % (prg_banks as u32 * 0x4000)
+    }
+
+    fn write_prg(&self, addr: u16, _data: u8, _prg_banks: u8) {
+        assert!((0x8000..=0xffff).contains(&addr));
+        self.latch.set(addr);
+    }
+
+    fn read_chr(&self, addr: u16, _chr_banks: u8) -> u32 {
+        assert!((..=0x1fff).contains(&addr));
+        addr as u32
+    }
+
+    fn write_chr(&self, addr: u16, _data: u8, _chr_banks: u8) {
+        assert!((..=0x1fff).contains(&addr));
+    }
+
+    fn get_mirroring(&self) -> Option<Mirroring> {
+        if (self.latch.get() & 0x2) != 0 {
+            Some(Mirroring::HORIZONTAL)
+        } else {
+            Some(Mirroring::VERTICAL)
+        }
+    }
+
+    fn reset(&self) {
+        self.latch.set(0);
+    }
+}
+
+#[cfg(test)]
+mod mapper227_tests {
+    use super::*;
+
+    #[test]
+    fn test_mapper227_nrom_32k() {
+        let mapper = Mapper227::new();
+        mapper.write_prg(0x8000 | 0x80 | (0x5 << 2) | 0x1, 0x0, 64);
+
+        assert_eq!(4 * 0x4000, mapper.read_prg(0x8000, 64));
+        assert_eq!(5 * 0x4000, mapper.read_prg(0xc000, 64));
+    }
+
+    #[test]
+    fn test_mapper227_nrom_16k() {
+        let mapper = Mapper227::new();
+        mapper.write_prg(0x8000 | 0x80 | (0x5 << 2), 0x0, 64);
+
+        assert_eq!(5 * 0x4000, mapper.read_prg(0x8000, 64));
+        assert_eq!(5 * 0x4000, mapper.read_prg(0xc000, 64));
+    }
+
+    #[test]
+    fn test_mapper227_unrom_first_bank_fixed() {
+        let mapper = Mapper227::new();
+        mapper.write_prg(0x8000 | (0xb << 2), 0x0, 64);
+
+        assert_eq!(11 * 0x4000, mapper.read_prg(0x8000, 64));
+        assert_eq!(8 * 0x4000, mapper.read_prg(0xc000, 64));
+    }
+
+    #[test]
+    fn test_mapper227_unrom_last_bank_fixed() {
+        let mapper = Mapper227::new();
+        mapper.write_prg(0x8000 | 0x200 | (0xb << 2), 0x0, 64);
+
+        assert_eq!(11 * 0x4000, mapper.read_prg(0x8000, 64));
+        assert_eq!(15 * 0x4000, mapper.read_prg(0xc000, 64));
+    }
+
+    #[test]
+    fn test_mapper227_high_prg_bit() {
+        let mapper = Mapper227::new();
+        mapper.write_prg(0x8000 | 0x100 | 0x80 | (0x1 << 2), 0x0, 64);
+
+        assert_eq!(33 * 0x4000, mapper.read_prg(0x8000, 64));
+    }
+
+    #[test]
+    fn test_mapper227_mirroring_and_reset() {
+        let mapper = Mapper227::new();
+        mapper.write_prg(0x8002, 0x0, 64);
+        assert_eq!(Some(Mirroring::HORIZONTAL), mapper.get_mirroring());
+
+        mapper.reset();
+        assert_eq!(Some(Mirroring::VERTICAL), mapper.get_mirroring());
+        assert_eq!(0x0, mapper.read_prg(0x8000, 64));
+    }
+}
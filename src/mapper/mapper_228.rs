@@ -0,0 +1,140 @@
+/*
+    Mapper 228 - Active Enterprises (Action 52, Cheetahmen II)
+
+    The register is latched from both the address and data
+    lines of any write to $8000-$FFFF:
+
+    A~[1.MH HPPP PPO. CCCC]   D~[.... ..cc]
+         ||| |||| |   ||||           ||
+         ||| |||| |   ++++-----------++- CHR bank (8 KiB)
+         ||| |||| +-------------------- PRG mode (0: 32 KiB, 1: 16 KiB)
+         ||| ++++---------------------- PRG bank (16 KiB) within chip
+         |++--------------------------- PRG chip select (512 KiB each)
+         +----------------------------- Mirroring (0: vertical, 1: horizontal)
+
+    Action 52 has no chip 2; chip select 3 addresses the third
+    512 KiB of the ROM. The latch is cleared on reset.
+*/
+
+use std::cell::Cell;
+
+use super::Mapper;
+use crate::util::Mirroring;
+
+pub struct Mapper228 {
+    latch: Cell<u16>,
+    data: Cell<u8>,
+}
+
+impl Mapper228 {
+    pub fn new() -> Mapper228 {
+        Mapper228 {
+            latch: Cell::new(0),
+            data: Cell::new(0),
+        }
+    }
+}
+
+impl Mapper for Mapper228 {
+    fn read_prg(&self, addr: u16, prg_banks: u8) -> u32 {
+        assert!((0x8000..=0xffff).contains(&addr));
+        let latch = self.latch.get();
+
+        let chip = match (latch >> 11) & 0x3 {
+            3 => 2,
+            chip => chip as u32,
+        };
+        let bank = (chip << 5) | ((latch >> 6) & 0x1f) as u32;
+
+        let offset = if (latch & 0x20) != 0 {
+            bank * 0x4000 + (addr & 0x3fff) as u32
+        } else {
+            (bank >> 1) * 0x8000 + (addr & 0x7fff) as u32
+        };
+
+        offset % (prg_banks as u32 * 0x4000)
+    }
+
+    fn write_prg(&self, addr: u16, data: u8, _prg_banks: u8) {
+        assert!((0x8000..=0xffff).contains(&addr));
+        self.latch.set(addr);
+        self.data.set(data);
+    }
+
+    fn read_chr(&self, addr: u16, chr_banks: u8) -> u32 {
+        assert!((..=0x1fff).contains(&addr));
+        let bank = (((self.latch.get() & 0xf) << 2) as u32) | (self.data.get() & 0x3) as u32;
+
+        (bank * 0x2000 + addr as u32) % (chr_banks as u32 * 0x2000)
+    }
+
+    fn write_chr(&self, addr: u16, _data: u8, _chr_banks: u8) {
+        assert!((..=0x1fff).contains(&addr));
+    }
+
+    fn get_mirroring(&self) -> Option<Mirroring> {
+        if (self.latch.get() & 0x2000) != 0 {
+            Some(Mirroring::HORIZONTAL)
+        } else {
+            Some(Mirroring::VERTICAL)
+        }
+    }
+
+    fn reset(&self) {
+        self.latch.set(0);
+        self.data.set(0);
+    }
+}
+
+#[cfg(test)]
+mod mapper228_tests {
+    use super::*;
+
+    #[test]
+    fn test_mapper228_32k_prg_mode() {
+        let mapper = Mapper228::new();
+        mapper.write_prg(0x8000 | (0x6 << 6), 0x0, 96);
+
+        assert_eq!(3 * 0x8000, mapper.read_prg(0x8000, 96));
+        assert_eq!(3 * 0x8000 + 0x7fff, mapper.read_prg(0xffff, 96));
+    }
+
+    #[test]
+    fn test_mapper228_16k_prg_mode() {
+        let mapper = Mapper228::new();
+        mapper.write_prg(0x8000 | (0x7 << 6) | 0x20, 0x0, 96);
+
+        assert_eq!(7 * 0x4000, mapper.read_prg(0x8000, 96));
+        assert_eq!(7 * 0x4000, mapper.read_prg(0xc000, 96));
+    }
+
+    #[test]
+    fn test_mapper228_chip_select() {
+        let mapper = Mapper228::new();
+
+        mapper.write_prg(0x8000 | (0x1 << 11) | 0x20, 0x0, 96);
+        assert_eq!(32 * 0x4000, mapper.read_prg(0x8000, 96));
+
+        mapper.write_prg(0x8000 | (0x3 << 11) | 0x20, 0x0, 96);
+        assert_eq!(64 * 0x4000, mapper.read_prg(0x8000, 96));
+    }
+
+    #[test]
+    fn test_mapper228_chr_bank() {
+        let mapper = Mapper228::new();
+        mapper.write_prg(0x8005, 0x2, 64);
+
+        assert_eq!(22 * 0x2000 + 0x10, mapper.read_chr(0x10, 64));
+    }
+
+    #[test]
+    fn test_mapper228_mirroring_and_reset() {
+        let mapper = Mapper228::new();
+        mapper.write_prg(0xa000, 0x3, 96);
+        assert_eq!(Some(Mirroring::HORIZONTAL), mapper.get_mirroring());
+
+        mapper.reset();
+        assert_eq!(Some(Mirroring::VERTICAL), mapper.get_mirroring());
+        assert_eq!(0x0, mapper.read_chr(0x0, 64));
+    }
+}
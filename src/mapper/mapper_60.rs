@@ -0,0 +1,89 @@
+/*
+    Mapper 60 - Reset-based NROM-128 4-in-1
+
+    There is no register. A counter on the board advances on
+    every soft reset and selects which 16 KiB PRG bank (mirrored
+    at $8000 and $C000) and 8 KiB CHR bank are visible, so each
+    press of the reset button starts the next game.
+*/
+
+use std::cell::Cell;
+
+use super::Mapper;
+use crate::util::Mirroring;
+
+pub struct Mapper60 {
+    game: Cell<u8>,
+}
+
+impl Mapper60 {
+    pub fn new() -> Mapper60 {
+        Mapper60 { game: Cell::new(0) }
+    }
+}
+
+impl Mapper for Mapper60 {
+    fn read_prg(&self, addr: u16, prg_banks: u8) -> u32 {
+        assert!((0x8000..=0xffff).contains(&addr));
+        let offset = self.game.get() as u32 * 0x4000 + (addr & 0x3fff) as u32;
+
+        offset % (prg_banks as u32 * 0x4000)
+    }
+
+    fn write_prg(&self, addr: u16, _data: u8, _prg_banks: u8) {
+        assert!((0x8000..=0xffff).contains(&addr));
+    }
+
+    fn read_chr(&self, addr: u16, chr_banks: u8) -> u32 {
+        assert!((..=0x1fff).contains(&addr));
+        let offset = self.game.get() as u32 * 0x2000 + addr as u32;
+
+        offset % (chr_banks as u32 * 0x2000)
+    }
+
+    fn write_chr(&self, addr: u16, _data: u8, _chr_banks: u8) {
+        assert!((..=0x1fff).contains(&addr));
+    }
+
+    fn get_mirroring(&self) -> Option<Mirroring> {
+        None
+    }
+
+    fn reset(&self) {
+        self.game.set((self.game.get() + 1) & 0x3);
+    }
+}
+
+#[cfg(test)]
+mod mapper60_tests {
+    use super::*;
+
+    #[test]
+    fn test_mapper60_first_game_on_power_on() {
+        let mapper = Mapper60::new();
+
+        assert_eq!(0x10, mapper.read_prg(0x8010, 4));
+        assert_eq!(0x10, mapper.read_prg(0xc010, 4));
+        assert_eq!(0x10, mapper.read_chr(0x10, 4));
+    }
+
+    #[test]
+    fn test_mapper60_writes_are_ignored() {
+        let mapper = Mapper60::new();
+        mapper.write_prg(0x8000, 0xff, 4);
+
+        assert_eq!(0x0, mapper.read_prg(0x8000, 4));
+    }
+
+    #[test]
+    fn test_mapper60_reset_cycles_through_games() {
+        let mapper = Mapper60::new();
+
+        for game in [1, 2, 3, 0] {
+            mapper.reset();
+            assert_eq!(game * 0x4000, mapper.read_prg(0x8000, 4));
+            assert_eq!(game * 0x4000, mapper.read_prg(0xc000, 4));
+            assert_eq!(game * 0x2000, mapper.read_chr(0x0, 4));
+        }
+    }
+}
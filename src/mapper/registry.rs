@@ -0,0 +1,166 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use super::{
+    mapper_0::Mapper0, mapper_15::Mapper15, mapper_225::Mapper225, mapper_227::Mapper227,
+    mapper_228::Mapper228, mapper_60::Mapper60, Mapper,
+};
+
+pub type MapperConstructor = fn() -> Box<dyn Mapper>;
+
+#[derive(Default)]
+pub struct MapperRegistry {
+    by_number: HashMap<(u16, u8), MapperConstructor>,
+    by_board: HashMap<String, MapperConstructor>,
+}
+
+impl MapperRegistry {
+    pub fn new() -> Self {
+        MapperRegistry {
+            by_number: HashMap::new(),
+            by_board: HashMap::new(),
+        }
+    }
+
+    pub fn with_builtin_mappers() -> Self {
+        let mut registry = MapperRegistry::new();
+
+        registry.register(0, 0, || Box::new(Mapper0::new()));
+        registry.register_board("NES-NROM-128", || Box::new(Mapper0::new()));
+        registry.register_board("NES-NROM-256", || Box::new(Mapper0::new()));
+
+        //Multicarts
+        registry.register(15, 0, || Box::new(Mapper15::new()));
+        registry.register(60, 0, || Box::new(Mapper60::new()));
+        registry.register(225, 0, || Box::new(Mapper225::new()));
+        registry.register(227, 0, || Box::new(Mapper227::new()));
+        registry.register(228, 0, || Box::new(Mapper228::new()));
+        registry.register_board("BMC-Reset4in1", || Box::new(Mapper60::new()));
+        registry.register_board("BMC-810544-C-A1", || Box::new(Mapper227::new()));
+        registry.register_board("MLT-ACTION52", || Box::new(Mapper228::new()));
+
+        registry
+    }
+
+    pub fn register(&mut self, mapper: u16, submapper: u8, constructor: MapperConstructor) {
+        self.by_number.insert((mapper, submapper), constructor);
+    }
+
+    pub fn register_board(&mut self, board: &str, constructor: MapperConstructor) {
+        self.by_board.insert(board.to_owned(), constructor);
+    }
+
+    pub fn create(&self, mapper: u16, submapper: u8) -> Option<Box<dyn Mapper>> {
+        //Submapper 0 is the default behaviour for a given mapper
+        self.by_number
+            .get(&(mapper, submapper))
+            .or_else(|| self.by_number.get(&(mapper, 0)))
+            .map(|constructor| constructor())
+    }
+
+    pub fn create_board(&self, board: &str) -> Option<Box<dyn Mapper>> {
+        self.by_board.get(board).map(|constructor| constructor())
+    }
+}
+
+fn global_registry() -> &'static Mutex<MapperRegistry> {
+    static REGISTRY: OnceLock<Mutex<MapperRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(MapperRegistry::with_builtin_mappers()))
+}
+
+pub fn register_mapper(mapper: u16, submapper: u8, constructor: MapperConstructor) {
+    global_registry()
+        .lock()
+        .unwrap()
+        .register(mapper, submapper, constructor);
+}
+
+pub fn register_board(board: &str, constructor: MapperConstructor) {
+    global_registry()
+        .lock()
+        .unwrap()
+        .register_board(board, constructor);
+}
+
+pub fn create_mapper(mapper: u16, submapper: u8) -> Option<Box<dyn Mapper>> {
+    global_registry().lock().unwrap().create(mapper, submapper)
+}
+
+pub fn create_board(board: &str) -> Option<Box<dyn Mapper>> {
+    global_registry().lock().unwrap().create_board(board)
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use crate::mapper::MockMapper;
+
+    use super::*;
+
+    fn mock_mapper() -> Box<dyn Mapper> {
+        let mut mapper = MockMapper::new();
+        mapper.expect_read_prg().return_const(0x1234u32);
+
+        Box::new(mapper)
+    }
+
+    #[test]
+    fn test_empty_registry_has_no_mappers() {
+        let registry = MapperRegistry::new();
+
+        assert!(registry.create(0, 0).is_none());
+        assert!(registry.create_board("NES-NROM-128").is_none());
+    }
+
+    #[test]
+    fn test_builtin_mappers_are_registered() {
+        let registry = MapperRegistry::with_builtin_mappers();
+
+        assert!(registry.create(0, 0).is_some());
+        assert!(registry.create_board("NES-NROM-128").is_some());
+        assert!(registry.create_board("NES-NROM-256").is_some());
+
+        for mapper in [15, 60, 225, 227, 228] {
+            assert!(registry.create(mapper, 0).is_some());
+        }
+        assert!(registry.create_board("MLT-ACTION52").is_some());
+    }
+
+    #[test]
+    fn test_register_by_number() {
+        let mut registry = MapperRegistry::new();
+        registry.register(0x123, 0, mock_mapper);
+
+        let mapper = registry.create(0x123, 0).unwrap();
+        assert_eq!(0x1234, mapper.read_prg(0x8000, 1));
+    }
+
+    #[test]
+    fn test_unknown_submapper_falls_back_to_submapper_0() {
+        let mut registry = MapperRegistry::new();
+        registry.register(0x123, 0, mock_mapper);
+
+        assert!(registry.create(0x123, 5).is_some());
+        assert!(registry.create(0x124, 5).is_none());
+    }
+
+    #[test]
+    fn test_register_by_board_name() {
+        let mut registry = MapperRegistry::new();
+        registry.register_board("UNL-HOMEBREW", mock_mapper);
+
+        let mapper = registry.create_board("UNL-HOMEBREW").unwrap();
+        assert_eq!(0x1234, mapper.read_prg(0x8000, 1));
+    }
+
+    #[test]
+    fn test_register_with_global_registry() {
+        register_mapper(0xfff, 0xf, mock_mapper);
+        register_board("UNL-GLOBAL-TEST", mock_mapper);
+
+        assert!(create_mapper(0xfff, 0xf).is_some());
+        assert!(create_board("UNL-GLOBAL-TEST").is_some());
+        assert!(create_mapper(0, 0).is_some());
+    }
+}
@@ -4,33 +4,66 @@ use self::{
 };
 use crate::{
     bus::Bus,
-    cpu::CPU,
     ppu::registers::{PPUCtrl, PPUMask},
+    util::Region,
 };
 use mockall::automock;
 use std::{borrow::BorrowMut, cell::RefCell};
 
+pub mod debug;
+pub mod palette;
 mod registers;
 mod render;
 
-pub type Frame = [[u8; 256]; 240];
+//Each pixel holds a palette index in bits 0-5 and the PPUMASK colour
+//emphasis bits (red, green, blue) in bits 6-8
+pub type Frame = [[u16; 256]; 240];
 
 #[automock]
 pub trait PPU {
-    fn clock(&mut self, cpu: &mut dyn CPU);
+    //True on the dot the CPU should be sent an NMI
+    fn clock(&mut self) -> bool;
     fn read(&self, addr: u16, rd_only: bool) -> u8;
     fn write(&mut self, addr: u16, data: u8);
+    //What read would return, without clearing flags or moving v
+    fn peek(&self, addr: u16) -> u8;
+    //Power-on clears everything, nametable VRAM included, and starts the
+    //warm-up; reset keeps OAM, VRAM and v
+    fn power_on(&mut self, state: &PowerOnState);
     fn reset(&mut self);
     fn is_frame_completed(&self) -> bool;
-    fn get_frame(&self) -> Frame;
+    fn get_frame(&self) -> &Frame;
+    fn set_region(&mut self, region: Region);
+}
+
+//Memory contents the PPU powers on with, which vary between consoles
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowerOnState {
+    pub oam: [u8; 0x100],
+    //Entries mirrored at $3F10/$3F14/$3F18/$3F1C take the later value
+    pub palette: [u8; 0x20],
+}
+
+impl Default for PowerOnState {
+    //The palette one console powered on with, from blargg's power_up_palette test
+    fn default() -> Self {
+        PowerOnState {
+            oam: [0x0; 0x100],
+            palette: [
+                0x09, 0x01, 0x00, 0x01, 0x00, 0x02, 0x02, 0x0d, 0x08, 0x10, 0x08, 0x24, 0x00, 0x00,
+                0x04, 0x2c, 0x09, 0x01, 0x34, 0x03, 0x00, 0x04, 0x00, 0x14, 0x08, 0x3a, 0x00, 0x02,
+                0x00, 0x20, 0x2c, 0x08,
+            ],
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
-struct OAMSprite {
-    y_pos: u8,
-    tile_index: u8,
-    attr: u8,
-    x_pos: u8,
+pub struct OAMSprite {
+    pub y_pos: u8,
+    pub tile_index: u8,
+    pub attr: u8,
+    pub x_pos: u8,
 }
 
 impl OAMSprite {
@@ -44,26 +77,43 @@ impl OAMSprite {
     }
 }
 
-pub struct NESPPU<'a> {
+//Generic over its bus so the hot paths are statically dispatched; the
+//default keeps the trait object form
+pub struct NESPPU<B: Bus + ?Sized = dyn Bus> {
     registers: Registers,
     render_args: RenderArgs,
 
     oam: [OAMSprite; 64],
     oam_addr: u8,
 
-    ppu_bus: Box<dyn Bus + 'a>,
+    ppu_bus: Box<B>,
 
+    region: Region,
     scanline: i16,
     cycle: u16,
 
-    front_buffer: Frame,
-    back_buffer: Frame,
+    //Last three samples of the NMI output (VBlank and NMI enable), newest in bit 0
+    nmi_output: u8,
+    //Set by a PPUSTATUS read just before VBlank, which stops it being set
+    vblank_suppressed: RefCell<bool>,
+
+    //Value left on the PPU's data lines by the last register access, read
+    //back from write-only registers; each bit decays to 0 unless refreshed
+    io_latch: RefCell<u8>,
+    io_latch_refreshed: RefCell<[u64; 8]>,
+    dots: u64,
+    //Dot the last power-on happened on, if the PPU has been powered on
+    powered_on_at: Option<u64>,
+
+    //Boxed so finishing a frame swaps pointers instead of copying pixels
+    front_buffer: Box<Frame>,
+    back_buffer: Box<Frame>,
 
     completed_frame: RefCell<bool>,
 }
 
-impl<'a> NESPPU<'a> {
-    pub fn new(ppu_bus: Box<dyn Bus + 'a>) -> Self {
+impl<B: Bus + ?Sized> NESPPU<B> {
+    pub fn new(ppu_bus: Box<B>) -> Self {
         NESPPU {
             registers: Registers::new(),
             render_args: RenderArgs::new(),
@@ -73,18 +123,93 @@ impl<'a> NESPPU<'a> {
 
             ppu_bus,
 
+            region: Region::NTSC,
             scanline: -1,
             cycle: 0,
 
-            front_buffer: [[0x0; 256]; 240],
-            back_buffer: [[0x0; 256]; 240],
+            nmi_output: 0b000,
+            vblank_suppressed: RefCell::new(false),
+
+            io_latch: RefCell::new(0x0),
+            io_latch_refreshed: RefCell::new([0; 8]),
+            dots: 0,
+            powered_on_at: None,
+
+            front_buffer: Box::new([[0x0; 256]; 240]),
+            back_buffer: Box::new([[0x0; 256]; 240]),
 
             completed_frame: RefCell::new(false),
         }
     }
 }
 
-impl PPU for NESPPU<'_> {
+impl<B: Bus + ?Sized> NESPPU<B> {
+    pub fn get_oam(&self) -> &[OAMSprite; 64] {
+        &self.oam
+    }
+}
+
+impl<B: Bus + ?Sized> NESPPU<B> {
+    //About 600ms at the NTSC dot rate
+    const IO_LATCH_DECAY_DOTS: u64 = 3_221_591;
+    //A second PPUADDR write reaches v this many dots later
+    const V_UPDATE_DELAY_DOTS: u8 = 3;
+    //How long after power-on PPUCTRL, PPUMASK, PPUSCROLL and PPUADDR
+    //ignore writes
+    const WARM_UP_CPU_CYCLES: u64 = 29658;
+
+    fn is_warming_up(&self) -> bool {
+        let (dots, cycles) = self.region.clock_ratio();
+        let warm_up_dots = Self::WARM_UP_CPU_CYCLES * dots as u64 / cycles as u64;
+
+        self.powered_on_at
+            .is_some_and(|at| self.dots - at < warm_up_dots)
+    }
+
+    fn get_io_latch(&self) -> u8 {
+        let io_latch = self.peek_io_latch();
+        *self.io_latch.borrow_mut() = io_latch;
+        io_latch
+    }
+
+    //The latch with decay applied, leaving the stored value alone
+    fn peek_io_latch(&self) -> u8 {
+        let refreshed = self.io_latch_refreshed.borrow();
+        let mut io_latch = *self.io_latch.borrow();
+        for (bit, &at) in refreshed.iter().enumerate() {
+            if self.dots - at >= Self::IO_LATCH_DECAY_DOTS {
+                io_latch &= !(1 << bit);
+            }
+        }
+
+        io_latch
+    }
+
+    fn get_oam_data(&self) -> u8 {
+        let sprite = &self.oam[(self.oam_addr / 4) as usize];
+        match self.oam_addr % 4 {
+            0 => sprite.y_pos,
+            1 => sprite.tile_index,
+            2 => sprite.attr,
+            _ => sprite.x_pos,
+        }
+    }
+
+    //Drives the bits in mask onto the data lines
+    fn set_io_latch(&self, data: u8, mask: u8) {
+        let io_latch = self.get_io_latch();
+        *self.io_latch.borrow_mut() = (io_latch & !mask) | (data & mask);
+
+        let mut refreshed = self.io_latch_refreshed.borrow_mut();
+        for (bit, at) in refreshed.iter_mut().enumerate() {
+            if mask & (1 << bit) != 0 {
+                *at = self.dots;
+            }
+        }
+    }
+}
+
+impl<B: Bus + ?Sized> PPU for NESPPU<B> {
     fn read(&self, addr: u16, rd_only: bool) -> u8 {
         assert!((0x2000..=0x3fff).contains(&addr) || addr == 0x4014);
 
@@ -94,10 +219,19 @@ impl PPU for NESPPU<'_> {
 
         let offset = (addr - 0x2000) & 0x7;
         match offset {
-            0x0 => 0x0, //PPUCTRL is write-only
-            0x1 => 0x0, //PPUMASK is write-only
+            0x0 => self.get_io_latch(), //PPUCTRL is write-only
+            0x1 => self.get_io_latch(), //PPUMASK is write-only
             0x2 => {
-                let status_to_return = self.registers.ppu_status.borrow().into_bytes()[0];
+                //Only the top 3 bits are driven, the rest come from the latch
+                let status = self.registers.ppu_status.borrow().into_bytes()[0];
+                self.set_io_latch(status, 0xe0);
+                let status_to_return = self.get_io_latch();
+
+                //Reading one dot before VBlank is set reads it clear and
+                //the flag is not set, nor an NMI sent, for this frame
+                if self.scanline == self.region.vblank_scanline() && self.cycle == 1 {
+                    *self.vblank_suppressed.borrow_mut() = true;
+                }
 
                 let mut ppu_status = self.registers.ppu_status.borrow_mut();
                 let mut write_latch = self.registers.write_latch.borrow_mut();
@@ -107,48 +241,36 @@ impl PPU for NESPPU<'_> {
 
                 status_to_return
             }
-            0x3 => 0x0,
+            0x3 => self.get_io_latch(), //OAMADDR is write-only
             0x4 => {
-                let index = (self.oam_addr / 4) as usize;
-                match self.oam_addr % 4 {
-                    0 => self.oam[index].y_pos,
-                    1 => self.oam[index].tile_index,
-                    2 => self.oam[index].attr,
-                    3 => self.oam[index].x_pos,
-                    _ => 0x0,
-                }
+                let data = self.get_oam_data();
+                self.set_io_latch(data, 0xff);
+                data
             }
-            0x5 => 0x0, //PPUSCROLL is write-only
-            0x6 => 0x0, //PPUADDR is write-only
+            0x5 => self.get_io_latch(), //PPUSCROLL is write-only
+            0x6 => self.get_io_latch(), //PPUADDR is write-only
             0x7 => {
                 if rd_only {
                     return 0x0;
                 }
 
-                let mut data = *self.registers.ppu_data_buffer.borrow();
-                let mut loopy_v = self.registers.loopy_v.borrow_mut();
+                let addr = self.registers.loopy_v.borrow().get_raw() & 0x3fff;
 
-                let loopy_v_raw = (*loopy_v).get_raw();
-                *self.registers.ppu_data_buffer.borrow_mut() =
-                    self.ppu_bus.read(loopy_v_raw & 0x3fff);
-
-                if loopy_v_raw >= 0x3f00 {
-                    data = *self.registers.ppu_data_buffer.borrow();
-                }
-
-                let offset = if self.registers.ppu_ctrl.increment() {
-                    32
+                let data = if addr >= 0x3f00 {
+                    //Palette reads are not buffered, but the buffer is filled
+                    //from the nametable underneath. The palette drives only
+                    //6 bits; the top 2 come from the I/O latch
+                    *self.registers.ppu_data_buffer.borrow_mut() = self.ppu_bus.read(addr - 0x1000);
+                    self.set_io_latch(self.ppu_bus.read(addr), 0x3f);
+                    self.get_io_latch()
                 } else {
-                    1
+                    let data = *self.registers.ppu_data_buffer.borrow();
+                    *self.registers.ppu_data_buffer.borrow_mut() = self.ppu_bus.read(addr);
+                    self.set_io_latch(data, 0xff);
+                    data
                 };
 
-                let to_write = loopy_v_raw + offset;
-
-                *loopy_v = LoopyRegister::from_bytes([
-                    (to_write & 0xff) as u8,
-                    ((to_write & 0xff00) >> 8) as u8,
-                ]);
-
+                self.increment_v_after_data_access();
                 data
             }
             _ => panic!("PPU Register {offset} is invalid, must be from 0x0 to 0x7"),
@@ -162,7 +284,13 @@ impl PPU for NESPPU<'_> {
             return;
         }
 
+        self.set_io_latch(data, 0xff);
+
         let offset = (addr - 0x2000) & 0x7;
+        if matches!(offset, 0x0 | 0x1 | 0x5 | 0x6) && self.is_warming_up() {
+            return;
+        }
+
         match offset {
             0x0 => {
                 self.registers.ppu_ctrl = PPUCtrl::from_bytes([data]);
@@ -212,7 +340,8 @@ impl PPU for NESPPU<'_> {
                     bytes[0] = data;
                     *self.registers.loopy_t.borrow_mut() = LoopyRegister::from_bytes(bytes);
 
-                    *self.registers.loopy_v.borrow_mut() = self.registers.loopy_t;
+                    self.registers.pending_v =
+                        Some((Self::V_UPDATE_DELAY_DOTS, self.registers.loopy_t));
                 } else {
                     *self.registers.write_latch.borrow_mut() = true;
                     let mut bytes = self.registers.loopy_t.into_bytes();
@@ -221,24 +350,50 @@ impl PPU for NESPPU<'_> {
                 }
             }
             0x7 => {
-                let mut loopy_v = self.registers.loopy_v.borrow_mut();
-                self.ppu_bus.write(loopy_v.get_raw() & 0x3fff, data);
-
-                let offset = if self.registers.ppu_ctrl.increment() {
-                    32
-                } else {
-                    1
-                };
+                let addr = self.registers.loopy_v.borrow().get_raw() & 0x3fff;
+                self.ppu_bus.write(addr, data);
 
-                let raw = loopy_v.get_raw() + offset;
-
-                *loopy_v =
-                    LoopyRegister::from_bytes([(raw & 0xff) as u8, ((raw & 0xff00) >> 8) as u8]);
+                self.increment_v_after_data_access();
             }
             _ => panic!("Register {offset} is invalid, must be from 0x0 to 0x7"),
         }
     }
 
+    fn power_on(&mut self, state: &PowerOnState) {
+        self.registers = Registers::new();
+        self.render_args = RenderArgs::new();
+
+        for (index, sprite) in self.oam.iter_mut().enumerate() {
+            let bytes = &state.oam[index * 4..index * 4 + 4];
+            *sprite = OAMSprite {
+                y_pos: bytes[0],
+                tile_index: bytes[1],
+                attr: bytes[2],
+                x_pos: bytes[3],
+            };
+        }
+        self.oam_addr = 0x0;
+
+        for addr in 0x2000..=0x2fff {
+            self.ppu_bus.write(addr, 0x0);
+        }
+        for (offset, &data) in state.palette.iter().enumerate() {
+            self.ppu_bus.write(0x3f00 + offset as u16, data);
+        }
+
+        self.scanline = -1;
+        self.cycle = 0;
+        self.nmi_output = 0b000;
+        *self.vblank_suppressed.borrow_mut() = false;
+        *self.io_latch.borrow_mut() = 0x0;
+
+        self.front_buffer.fill([0x0; 256]);
+        self.back_buffer.fill([0x0; 256]);
+        *self.completed_frame.borrow_mut() = false;
+
+        self.powered_on_at = Some(self.dots);
+    }
+
     fn reset(&mut self) {
         self.registers.ppu_ctrl = PPUCtrl::from_bytes([0x0]);
         self.registers.ppu_mask = PPUMask::from_bytes([0x0]);
@@ -251,12 +406,19 @@ impl PPU for NESPPU<'_> {
 
         *write_latch = false;
         self.registers.loopy_t = LoopyRegister::from_bytes([0x0; 2]);
+        self.registers.pending_v = None;
+        self.registers.fine_x = 0x0;
+        *self.registers.ppu_data_buffer.borrow_mut() = 0x0;
         self.registers.odd_frame = false;
     }
 
-    fn clock(&mut self, cpu: &mut dyn CPU) {
+    fn clock(&mut self) -> bool {
+        //Send an NMI on a rising edge of the NMI output
+        let nmi = self.sample_nmi_output();
+
         //Update registers
-        self.update_registers(cpu);
+        self.update_registers();
+        self.update_pending_v();
 
         //Draw pixel
         self.draw_pixel();
@@ -265,6 +427,9 @@ impl PPU for NESPPU<'_> {
 
         //Increment cycle/scanline
         self.increment_cycle();
+        self.dots += 1;
+
+        nmi
     }
 
     fn is_frame_completed(&self) -> bool {
@@ -277,8 +442,38 @@ impl PPU for NESPPU<'_> {
         false
     }
 
-    fn get_frame(&self) -> Frame {
-        self.front_buffer
+    fn peek(&self, addr: u16) -> u8 {
+        assert!((0x2000..=0x3fff).contains(&addr) || addr == 0x4014);
+
+        if addr == 0x4014 {
+            return 0x0;
+        }
+
+        let io_latch = self.peek_io_latch();
+        match (addr - 0x2000) & 0x7 {
+            0x2 => {
+                let status = self.registers.ppu_status.borrow().into_bytes()[0];
+                (status & 0xe0) | (io_latch & 0x1f)
+            }
+            0x4 => self.get_oam_data(),
+            0x7 => {
+                let addr = self.registers.loopy_v.borrow().get_raw() & 0x3fff;
+                if addr >= 0x3f00 {
+                    (self.ppu_bus.peek(addr) & 0x3f) | (io_latch & 0xc0)
+                } else {
+                    *self.registers.ppu_data_buffer.borrow()
+                }
+            }
+            _ => io_latch,
+        }
+    }
+
+    fn get_frame(&self) -> &Frame {
+        &self.front_buffer
+    }
+
+    fn set_region(&mut self, region: Region) {
+        self.region = region;
     }
 }
 
@@ -341,15 +536,177 @@ mod ppu_tests {
         *ppu.registers.write_latch.borrow_mut() = true;
         *ppu.registers.ppu_status.borrow_mut() = PPUStatus::from_bytes([0xff]);
 
-        assert_eq!(0xff, ppu.read(0x2002, false));
+        //The low 5 bits are not driven and come from the I/O latch
+        assert_eq!(0xe0, ppu.read(0x2002, false));
         assert_eq!(false, *ppu.registers.write_latch.borrow());
         assert_eq!(0x7f, ppu.registers.ppu_status.borrow().into_bytes()[0]);
 
-        assert_eq!(0x7f, ppu.read(0x2002, false));
+        assert_eq!(0x60, ppu.read(0x2002, false));
         assert_eq!(false, *ppu.registers.write_latch.borrow());
         assert_eq!(0x7f, ppu.registers.ppu_status.borrow().into_bytes()[0]);
     }
 
+    #[test]
+    fn test_ppu_peek_has_no_side_effects() {
+        let mut bus = MockBus::new();
+        bus.expect_read().never();
+        bus.expect_peek().with(eq(0x3f00)).once().return_const(0x1d);
+
+        let mut ppu = NESPPU::new(Box::new(bus));
+        ppu.write(0x2003, 0xa5);
+        *ppu.registers.write_latch.borrow_mut() = true;
+        *ppu.registers.ppu_status.borrow_mut() = PPUStatus::from_bytes([0xff]);
+        *ppu.registers.ppu_data_buffer.borrow_mut() = 0x42;
+        ppu.registers.loopy_v = RefCell::new(LoopyRegister::from_bytes([0x00, 0x20]));
+
+        assert_eq!(0xe5, ppu.peek(0x2002));
+        assert_eq!(true, *ppu.registers.write_latch.borrow());
+        assert_eq!(0xff, ppu.registers.ppu_status.borrow().into_bytes()[0]);
+
+        assert_eq!(0x42, ppu.peek(0x2007));
+        assert_eq!(0x2000, ppu.registers.loopy_v.borrow().get_raw());
+
+        //Palette entries are 6 bits, the rest comes from the I/O latch
+        ppu.registers.loopy_v = RefCell::new(LoopyRegister::from_bytes([0x00, 0x3f]));
+        assert_eq!(0x9d, ppu.peek(0x2007));
+        assert_eq!(0x42, *ppu.registers.ppu_data_buffer.borrow());
+        assert_eq!(0xa5, ppu.peek(0x2000));
+    }
+
+    #[test]
+    fn test_write_only_registers_read_back_io_latch() {
+        let mut ppu = NESPPU::new(Box::new(MockBus::new()));
+
+        ppu.write(0x2003, 0xa5);
+        for addr in [0x2000, 0x2001, 0x2003, 0x2005, 0x2006] {
+            assert_eq!(0xa5, ppu.read(addr, false));
+        }
+
+        //PPUSTATUS drives only its top 3 bits
+        assert_eq!(0x05, ppu.read(0x2002, false));
+        assert_eq!(0x05, ppu.read(0x2000, false));
+    }
+
+    #[test]
+    fn test_io_latch_bits_decay_unless_refreshed() {
+        let mut ppu = NESPPU::new(Box::new(MockBus::new()));
+        let decay = NESPPU::<MockBus>::IO_LATCH_DECAY_DOTS;
+
+        ppu.write(0x2000, 0xff);
+
+        //Refreshes bits 7-5 only
+        ppu.dots = decay - 1;
+        *ppu.registers.ppu_status.borrow_mut() = PPUStatus::from_bytes([0x80]);
+        assert_eq!(0x9f, ppu.read(0x2002, false));
+
+        ppu.dots = decay;
+        assert_eq!(0x80, ppu.read(0x2005, false));
+
+        ppu.dots = 2 * decay - 1;
+        assert_eq!(0x0, ppu.read(0x2005, false));
+    }
+
+    fn ppu_before_vblank(nmi_enable: bool) -> NESPPU<MockBus> {
+        let mut bus = MockBus::new();
+        bus.expect_read().return_const(0x0);
+
+        let mut ppu = NESPPU::new(Box::new(bus));
+        ppu.write(0x2000, (nmi_enable as u8) << 7);
+        ppu.scanline = 241;
+        ppu.cycle = 1;
+
+        ppu
+    }
+
+    //Number of NMIs sent over the given number of dots
+    fn count_nmis(ppu: &mut NESPPU<MockBus>, dots: usize) -> usize {
+        (0..dots).filter(|_| ppu.clock()).count()
+    }
+
+    #[test]
+    fn test_ppu_sends_nmi_after_vblank() {
+        let mut ppu = ppu_before_vblank(true);
+
+        assert_eq!(1, count_nmis(&mut ppu, 10));
+        assert_eq!(0x80, ppu.read(0x2002, false) & 0x80);
+    }
+
+    #[test]
+    fn test_ppu_status_read_on_vblank_dot_suppresses_nmi() {
+        for dots_after in 0..2 {
+            let mut ppu = ppu_before_vblank(true);
+
+            let mut nmis = count_nmis(&mut ppu, 1 + dots_after);
+            assert_eq!(0x80, ppu.read(0x2002, false) & 0x80);
+
+            nmis += count_nmis(&mut ppu, 10);
+            assert_eq!(0, nmis);
+        }
+    }
+
+    #[test]
+    fn test_ppu_status_read_before_vblank_suppresses_flag() {
+        let mut ppu = ppu_before_vblank(true);
+
+        assert_eq!(0x0, ppu.read(0x2002, false) & 0x80);
+        assert_eq!(0, count_nmis(&mut ppu, 10));
+        assert_eq!(0x0, ppu.read(0x2002, false) & 0x80);
+    }
+
+    #[test]
+    fn test_ppu_enabling_nmi_during_vblank_sends_nmi() {
+        let mut ppu = ppu_before_vblank(false);
+        assert_eq!(0, count_nmis(&mut ppu, 10));
+
+        ppu.write(0x2000, 0x80);
+        assert_eq!(1, count_nmis(&mut ppu, 10));
+    }
+
+    fn dots_in_two_frames(region: Region) -> u32 {
+        let mut bus = MockBus::new();
+        bus.expect_read().return_const(0x0);
+        let mut ppu = NESPPU::new(Box::new(bus));
+        ppu.set_region(region);
+
+        let mut dots = 0;
+        for _ in 0..2 {
+            loop {
+                ppu.clock();
+                dots += 1;
+                if ppu.is_frame_completed() {
+                    break;
+                }
+            }
+        }
+        dots
+    }
+
+    #[test]
+    fn test_frame_length_depends_on_region() {
+        //Only NTSC skips a dot on odd frames
+        assert_eq!(2 * 341 * 262 - 1, dots_in_two_frames(Region::NTSC));
+        assert_eq!(2 * 341 * 312, dots_in_two_frames(Region::PAL));
+        assert_eq!(2 * 341 * 312, dots_in_two_frames(Region::DENDY));
+    }
+
+    #[test]
+    fn test_dendy_vblank_starts_on_scanline_291() {
+        let mut ppu = ppu_before_vblank(false);
+        ppu.set_region(Region::DENDY);
+
+        for _ in 0..10 {
+            ppu.clock();
+        }
+        assert_eq!(0x0, ppu.read(0x2002, false) & 0x80);
+
+        ppu.scanline = 291;
+        ppu.cycle = 1;
+        for _ in 0..10 {
+            ppu.clock();
+        }
+        assert_eq!(0x80, ppu.read(0x2002, false) & 0x80);
+    }
+
     #[test]
     fn test_ppu_write_scroll() {
         let mut ppu = NESPPU::new(Box::new(MockBus::new()));
@@ -375,12 +732,95 @@ mod ppu_tests {
         ppu.write(0x2006, 0xfe);
         assert_eq!(false, *ppu.registers.write_latch.borrow());
         assert_eq!(0b0011_1111_1111_1110, ppu.registers.loopy_t.get_raw());
+
+        //v only follows a few dots later
+        ppu.clock();
+        ppu.clock();
+        assert_eq!(0x0, ppu.registers.loopy_v.borrow().get_raw());
+        ppu.clock();
         assert_eq!(
             0b0011_1111_1111_1110,
             ppu.registers.loopy_v.borrow().get_raw()
         );
     }
 
+    //Both layers on, about to run the given dot
+    fn ppu_rendering_at(scanline: i16, cycle: u16) -> NESPPU<MockBus> {
+        let mut bus = MockBus::new();
+        bus.expect_read().return_const(0x0);
+        bus.expect_write().return_const(());
+
+        let mut ppu = NESPPU::new(Box::new(bus));
+        ppu.write(0x2001, 0x18);
+        ppu.scanline = scanline;
+        ppu.cycle = cycle;
+
+        ppu
+    }
+
+    fn clock_until(ppu: &mut NESPPU<MockBus>, cycle: u16) {
+        while ppu.cycle != cycle {
+            ppu.clock();
+        }
+    }
+
+    #[test]
+    fn test_ppu_addr_write_mid_scanline_reaches_v_after_delay() {
+        let mut ppu = ppu_rendering_at(50, 100);
+        ppu.write(0x2006, 0x08);
+        ppu.write(0x2006, 0x42);
+
+        clock_until(&mut ppu, 102);
+        assert_eq!(0x0, ppu.registers.loopy_v.borrow().get_raw());
+        clock_until(&mut ppu, 103);
+        assert_eq!(0x0842, ppu.registers.loopy_v.borrow().get_raw());
+
+        //Rendering carries on from the new address
+        clock_until(&mut ppu, 105);
+        assert_eq!(0x0843, ppu.registers.loopy_v.borrow().get_raw());
+    }
+
+    #[test]
+    fn test_scroll_write_mid_scanline_reaches_v_at_dot_257() {
+        let mut ppu = ppu_rendering_at(50, 100);
+        ppu.write(0x2005, 0x50);
+
+        //Dots 104-256 each moved coarse X on by one tile
+        clock_until(&mut ppu, 257);
+        assert_eq!(20, ppu.registers.loopy_v.borrow().coarse_x());
+        clock_until(&mut ppu, 258);
+        assert_eq!(10, ppu.registers.loopy_v.borrow().coarse_x());
+    }
+
+    #[test]
+    fn test_ppu_data_access_during_rendering_increments_coarse_x_and_y() {
+        for scanline in [-1, 100, 239] {
+            let mut ppu = ppu_rendering_at(scanline, 100);
+            ppu.registers.ppu_ctrl.set_increment(true);
+
+            //Coarse X 31, coarse Y 29 and fine Y 7 all wrap
+            ppu.registers.loopy_v = RefCell::new(LoopyRegister::from_bytes([0xbf, 0x73]));
+            ppu.read(0x2007, false);
+            assert_eq!(0x0c00, ppu.registers.loopy_v.borrow().get_raw());
+
+            ppu.registers.loopy_v = RefCell::new(LoopyRegister::from_bytes([0x00, 0x20]));
+            ppu.write(0x2007, 0x0);
+            assert_eq!(0x3001, ppu.registers.loopy_v.borrow().get_raw());
+        }
+    }
+
+    #[test]
+    fn test_ppu_data_access_during_vblank_increments_normally() {
+        let mut ppu = ppu_rendering_at(241, 100);
+
+        ppu.registers.loopy_v = RefCell::new(LoopyRegister::from_bytes([0xbf, 0x73]));
+        ppu.read(0x2007, false);
+        assert_eq!(0x73c0, ppu.registers.loopy_v.borrow().get_raw());
+
+        ppu.write(0x2007, 0x0);
+        assert_eq!(0x73c1, ppu.registers.loopy_v.borrow().get_raw());
+    }
+
     #[test]
     fn test_ppu_reset() {
         let mut ppu = NESPPU::new(Box::new(MockBus::new()));
@@ -409,6 +849,120 @@ mod ppu_tests {
         assert_eq!(0x0, ppu.oam_addr);
     }
 
+    #[test]
+    fn test_ppu_reset_clears_scroll_and_read_buffer() {
+        let mut ppu = NESPPU::new(Box::new(MockBus::new()));
+        ppu.registers.fine_x = 0x7;
+        *ppu.registers.ppu_data_buffer.borrow_mut() = 0xaa;
+        ppu.reset();
+
+        assert_eq!(0x0, ppu.registers.fine_x);
+        assert_eq!(0x0, *ppu.registers.ppu_data_buffer.borrow());
+
+        //Only power-on has a warm-up
+        ppu.write(0x2000, 0x80);
+        assert_eq!(0x80, ppu.registers.ppu_ctrl.into_bytes()[0]);
+    }
+
+    #[test]
+    fn test_ppu_reset_drops_pending_addr_write() {
+        let mut ppu = NESPPU::new(Box::new(MockBus::new()));
+        ppu.write(0x2006, 0x21);
+        ppu.write(0x2006, 0x08);
+        ppu.reset();
+        for _ in 0..3 {
+            ppu.clock();
+        }
+        assert_eq!(0x0, ppu.registers.loopy_v.borrow().get_raw());
+    }
+
+    fn bus_expecting_palette(palette: &[u8; 0x20]) -> MockBus {
+        let mut bus = MockBus::new();
+        bus.expect_write()
+            .withf(|&addr, &data| (0x2000..=0x2fff).contains(&addr) && data == 0x0)
+            .times(0x1000)
+            .return_const(());
+        for (offset, &data) in palette.iter().enumerate() {
+            bus.expect_write()
+                .with(eq(0x3f00 + offset as u16), eq(data))
+                .once()
+                .return_const(());
+        }
+        bus
+    }
+
+    fn powered_on_ppu(state: &PowerOnState) -> NESPPU<MockBus> {
+        let mut ppu = NESPPU::new(Box::new(bus_expecting_palette(&state.palette)));
+        ppu.power_on(state);
+        ppu
+    }
+
+    #[test]
+    fn test_ppu_power_on_loads_initial_state() {
+        let mut state = PowerOnState::default();
+        state.oam[0x4..0x8].copy_from_slice(&[0x10, 0x20, 0x30, 0x40]);
+        state.palette[0x1] = 0x2a;
+
+        let mut ppu = NESPPU::new(Box::new(bus_expecting_palette(&state.palette)));
+        ppu.oam_addr = 0x80;
+        ppu.registers.loopy_v = RefCell::new(LoopyRegister::from_bytes([0xff, 0x7f]));
+        ppu.registers.ppu_mask = PPUMask::from_bytes([0x1e]);
+        ppu.scanline = 100;
+        ppu.power_on(&state);
+
+        assert_eq!(
+            OAMSprite {
+                y_pos: 0x10,
+                tile_index: 0x20,
+                attr: 0x30,
+                x_pos: 0x40,
+            },
+            ppu.oam[1]
+        );
+        assert_eq!(0x0, ppu.oam_addr);
+        assert_eq!(0x0, ppu.registers.loopy_v.borrow().get_raw());
+        assert_eq!(0x0, ppu.registers.ppu_mask.into_bytes()[0]);
+        assert_eq!((-1, 0), (ppu.scanline, ppu.cycle));
+    }
+
+    #[test]
+    fn test_ppu_ignores_writes_while_warming_up() {
+        let mut ppu = powered_on_ppu(&PowerOnState::default());
+        ppu.write(0x2000, 0x80);
+        ppu.write(0x2001, 0x1e);
+        ppu.write(0x2005, 0xff);
+        ppu.write(0x2006, 0x3f);
+
+        assert_eq!(0x0, ppu.registers.ppu_ctrl.into_bytes()[0]);
+        assert_eq!(0x0, ppu.registers.ppu_mask.into_bytes()[0]);
+        assert_eq!(0x0, ppu.registers.loopy_t.get_raw());
+        assert_eq!(false, *ppu.registers.write_latch.borrow());
+
+        //OAM works straight away
+        ppu.write(0x2003, 0x8);
+        ppu.write(0x2004, 0x42);
+        assert_eq!(0x42, ppu.oam[2].y_pos);
+
+        //29658 CPU cycles later
+        ppu.dots = 29658 * 3;
+        ppu.write(0x2000, 0x80);
+        assert_eq!(0x80, ppu.registers.ppu_ctrl.into_bytes()[0]);
+    }
+
+    #[test]
+    fn test_pal_warm_up_is_measured_in_cpu_cycles() {
+        let mut ppu = powered_on_ppu(&PowerOnState::default());
+        ppu.set_region(Region::PAL);
+
+        ppu.dots = 29658 * 3;
+        ppu.write(0x2000, 0x80);
+        assert_eq!(0x0, ppu.registers.ppu_ctrl.into_bytes()[0]);
+
+        ppu.dots = 29658 * 16 / 5;
+        ppu.write(0x2000, 0x80);
+        assert_eq!(0x80, ppu.registers.ppu_ctrl.into_bytes()[0]);
+    }
+
     #[test]
     fn test_ppu_mask() {
         let mut ppu = NESPPU::new(Box::new(MockBus::new()));
@@ -421,7 +975,8 @@ mod ppu_tests {
         let mut bus = MockBus::new();
         bus.expect_read().with(eq(0x2000)).once().return_const(0xff);
         bus.expect_read().with(eq(0x2001)).once().return_const(0xee);
-        bus.expect_read().with(eq(0x3f00)).once().return_const(0xdd);
+        bus.expect_read().with(eq(0x3f00)).once().return_const(0x1d);
+        bus.expect_read().with(eq(0x2f00)).once().return_const(0xdd);
         bus.expect_read().with(eq(0x2400)).once().return_const(0xcc);
 
         let mut ppu = NESPPU::new(Box::new(bus));
@@ -435,6 +990,8 @@ mod ppu_tests {
         assert_eq!(0x2002, ppu.registers.loopy_v.borrow().get_raw());
         assert_eq!(0xee, *ppu.registers.ppu_data_buffer.borrow());
 
+        //Palette reads are immediate and buffer the nametable underneath;
+        //the top 2 bits are left on the I/O latch by the last read ($FF)
         ppu.registers.loopy_v = RefCell::new(LoopyRegister::from_bytes([0x00, 0x3f]));
         assert_eq!(0xdd, ppu.read(0x2007, false));
         assert_eq!(0x3f01, ppu.registers.loopy_v.borrow().get_raw());
@@ -519,4 +1076,99 @@ mod ppu_tests {
         ppu.oam_addr += 1;
         assert_eq!(0x4, ppu.read(0x2004, true));
     }
+
+    //Background pixel 1 of palette 0 on every dot
+    fn ppu_drawing_bg(mask: u8) -> NESPPU<MockBus> {
+        let mut bus = MockBus::new();
+        bus.expect_read().with(eq(0x3f00)).return_const(0x0f);
+        bus.expect_read().with(eq(0x3f01)).return_const(0x16);
+
+        let mut ppu = NESPPU::new(Box::new(bus));
+        ppu.write(0x2001, mask);
+        ppu.render_args.shift_lsb = 0xffff;
+        ppu.scanline = 0;
+
+        ppu
+    }
+
+    fn draw_pixel_at(ppu: &mut NESPPU<MockBus>, cycle: u16) -> u16 {
+        ppu.cycle = cycle;
+        ppu.draw_pixel();
+        ppu.back_buffer[0][cycle as usize]
+    }
+
+    #[test]
+    fn test_bg_is_transparent_in_left_8_pixels_when_masked() {
+        let mut ppu = ppu_drawing_bg(0x08);
+
+        assert_eq!(0x0f, draw_pixel_at(&mut ppu, 0));
+        assert_eq!(0x0f, draw_pixel_at(&mut ppu, 7));
+        assert_eq!(0x16, draw_pixel_at(&mut ppu, 8));
+
+        let mut ppu = ppu_drawing_bg(0x0a);
+        assert_eq!(0x16, draw_pixel_at(&mut ppu, 0));
+    }
+
+    #[test]
+    fn test_greyscale_keeps_palette_index_column() {
+        let mut ppu = ppu_drawing_bg(0x09);
+
+        assert_eq!(0x10, draw_pixel_at(&mut ppu, 8));
+        assert_eq!(0x00, draw_pixel_at(&mut ppu, 0));
+    }
+
+    #[test]
+    fn test_emphasis_bits_are_carried_in_frame() {
+        let mut ppu = ppu_drawing_bg(0xa8);
+
+        assert_eq!(0b101 << 6 | 0x16, draw_pixel_at(&mut ppu, 8));
+
+        //Forced blank still shows the backdrop with emphasis
+        let mut ppu = ppu_drawing_bg(0x40);
+        assert_eq!(0b010 << 6 | 0x0f, draw_pixel_at(&mut ppu, 8));
+    }
+
+    #[test]
+    fn test_pal_emphasis_swaps_red_and_green() {
+        for region in [Region::PAL, Region::DENDY] {
+            //Bit 5 is green and bit 6 red
+            let mut ppu = ppu_drawing_bg(0x28);
+            ppu.set_region(region);
+            assert_eq!(0b010 << 6 | 0x16, draw_pixel_at(&mut ppu, 8));
+
+            let mut ppu = ppu_drawing_bg(0xc8);
+            ppu.set_region(region);
+            assert_eq!(0b101 << 6 | 0x16, draw_pixel_at(&mut ppu, 8));
+        }
+    }
+
+    #[test]
+    fn test_transparent_bg_pixel_shows_backdrop() {
+        let mut bus = MockBus::new();
+        bus.expect_read().with(eq(0x3f04)).never();
+        bus.expect_read().with(eq(0x3f00)).return_const(0x0f);
+
+        let mut ppu = NESPPU::new(Box::new(bus));
+        ppu.write(0x2001, 0x0a);
+        ppu.render_args.palette_shift_lsb = 0xffff;
+        ppu.scanline = 0;
+
+        assert_eq!(0x0f, draw_pixel_at(&mut ppu, 8));
+    }
+
+    #[test]
+    fn test_forced_blank_shows_palette_entry_pointed_at_by_v() {
+        let mut bus = MockBus::new();
+        bus.expect_read().with(eq(0x3f00)).return_const(0x0f);
+        bus.expect_read().with(eq(0x3f05)).return_const(0x2a);
+
+        let mut ppu = NESPPU::new(Box::new(bus));
+        ppu.scanline = 0;
+
+        ppu.registers.loopy_v = RefCell::new(LoopyRegister::from_bytes([0x00, 0x20]));
+        assert_eq!(0x0f, draw_pixel_at(&mut ppu, 0));
+
+        ppu.registers.loopy_v = RefCell::new(LoopyRegister::from_bytes([0x05, 0x3f]));
+        assert_eq!(0x2a, draw_pixel_at(&mut ppu, 1));
+    }
 }
@@ -0,0 +1,315 @@
+use super::{palette::to_argb, NESPPU};
+use crate::bus::Bus;
+
+//Outline drawn around the visible screen on the nametable view
+const SCROLL_OVERLAY: u32 = 0xffff_00ff;
+
+//ARGB pixels in rows, in the same format as palette::frame_to_argb
+pub struct DebugImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+impl DebugImage {
+    fn new(width: usize, height: usize) -> Self {
+        DebugImage {
+            width,
+            height,
+            pixels: vec![0xff00_0000; width * height],
+        }
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> u32 {
+        self.pixels[y * self.width + x]
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, colour: u32) {
+        self.pixels[y * self.width + x] = colour;
+    }
+}
+
+//The views only peek through the PPU bus, so they can be built at any point
+//without affecting emulation
+impl<B: Bus + ?Sized> NESPPU<B> {
+    //Decodes the two bitplanes of a tile row, leftmost pixel first
+    fn tile_row(&self, table: u16, tile: u8, row: u16) -> [u8; 8] {
+        let addr = table | (tile as u16) << 4 | row;
+        let low = self.ppu_bus.peek(addr);
+        let high = self.ppu_bus.peek(addr + 8);
+
+        let mut pixels = [0x0; 8];
+        for (col, pixel) in pixels.iter_mut().enumerate() {
+            let bit = 7 - col;
+            *pixel = (low >> bit) & 1 | ((high >> bit) & 1) << 1;
+        }
+        pixels
+    }
+
+    //Palettes 0-3 are the background's, 4-7 the sprites'
+    fn palette_colour(&self, palette: u8, pixel: u8) -> u32 {
+        let addr = match pixel {
+            0 => 0x3f00,
+            _ => 0x3f00 | (palette as u16) << 2 | pixel as u16,
+        };
+        to_argb((self.ppu_bus.peek(addr) & 0x3f) as u16)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_tile(
+        &self,
+        image: &mut DebugImage,
+        (x, y): (usize, usize),
+        table: u16,
+        tile: u8,
+        palette: u8,
+        flip_h: bool,
+        flip_v: bool,
+    ) {
+        for row in 0..8 {
+            let pixels = self.tile_row(table, tile, if flip_v { 7 - row } else { row });
+            for (col, &pixel) in pixels.iter().enumerate() {
+                let col = if flip_h { 7 - col } else { col };
+                image.set_pixel(
+                    x + col,
+                    y + row as usize,
+                    self.palette_colour(palette, pixel),
+                );
+            }
+        }
+    }
+
+    //The four logical nametables as a 512x480 image, $2000 and $2400 on top,
+    //$2800 and $2C00 below, with the screen's position from t outlined
+    pub fn render_nametables(&self) -> DebugImage {
+        let mut image = DebugImage::new(512, 480);
+        let table = (self.registers.ppu_ctrl.bg_addr() as u16) << 12;
+
+        for nametable in 0..4u16 {
+            let base = 0x2000 | nametable << 10;
+            let left = (nametable & 1) as usize * 256;
+            let top = (nametable >> 1) as usize * 240;
+
+            for tile_y in 0..30u16 {
+                for tile_x in 0..32u16 {
+                    let tile = self.ppu_bus.peek(base | tile_y << 5 | tile_x);
+                    let attr = self
+                        .ppu_bus
+                        .peek(base | 0x3c0 | (tile_y >> 2) << 3 | tile_x >> 2);
+                    let shift = (tile_y & 0x2) << 1 | (tile_x & 0x2);
+
+                    self.draw_tile(
+                        &mut image,
+                        (left + tile_x as usize * 8, top + tile_y as usize * 8),
+                        table,
+                        tile,
+                        (attr >> shift) & 0x3,
+                        false,
+                        false,
+                    );
+                }
+            }
+        }
+
+        self.draw_scroll_overlay(&mut image);
+        image
+    }
+
+    fn draw_scroll_overlay(&self, image: &mut DebugImage) {
+        let t = self.registers.loopy_t;
+        let x = t.nametable_x() as usize * 256
+            + t.coarse_x() as usize * 8
+            + self.registers.fine_x as usize;
+        let y = t.nametable_y() as usize * 240 + t.coarse_y() as usize * 8 + t.fine_y() as usize;
+
+        //The screen wraps around the edges like the scroll does
+        for offset in 0..256 {
+            image.set_pixel((x + offset) % 512, y % 480, SCROLL_OVERLAY);
+            image.set_pixel((x + offset) % 512, (y + 239) % 480, SCROLL_OVERLAY);
+        }
+        for offset in 0..240 {
+            image.set_pixel(x % 512, (y + offset) % 480, SCROLL_OVERLAY);
+            image.set_pixel((x + 255) % 512, (y + offset) % 480, SCROLL_OVERLAY);
+        }
+    }
+
+    //Both pattern tables side by side as a 256x128 image, coloured with
+    //palette 0-7
+    pub fn render_pattern_tables(&self, palette: u8) -> DebugImage {
+        assert!(palette < 8, "There are only 8 palettes");
+        let mut image = DebugImage::new(256, 128);
+
+        for table in 0..2u16 {
+            for tile in 0..=0xffu8 {
+                let x = table as usize * 128 + (tile & 0xf) as usize * 8;
+                let y = (tile >> 4) as usize * 8;
+                self.draw_tile(&mut image, (x, y), table << 12, tile, palette, false, false);
+            }
+        }
+        image
+    }
+
+    //All 64 sprites in OAM order on an 8x8 grid of 8x16 cells, as a 64x128
+    //image; 8x8 sprites only fill the top half of their cell
+    pub fn render_oam(&self) -> DebugImage {
+        let mut image = DebugImage::new(64, 128);
+        let tall = self.registers.ppu_ctrl.spr_size();
+
+        for (index, sprite) in self.oam.iter().enumerate() {
+            let x = (index % 8) * 8;
+            let y = (index / 8) * 16;
+            let palette = 0x4 | (sprite.attr & 0x3);
+            let flip_h = sprite.attr & 0x40 != 0;
+            let flip_v = sprite.attr & 0x80 != 0;
+
+            if tall {
+                //Bit 0 of the index picks the table, and a vertical flip
+                //also swaps the two halves
+                let table = (sprite.tile_index as u16 & 0x1) << 12;
+                let top = sprite.tile_index & 0xfe;
+                let (upper, lower) = if flip_v {
+                    (top + 1, top)
+                } else {
+                    (top, top + 1)
+                };
+                self.draw_tile(&mut image, (x, y), table, upper, palette, flip_h, flip_v);
+                self.draw_tile(
+                    &mut image,
+                    (x, y + 8),
+                    table,
+                    lower,
+                    palette,
+                    flip_h,
+                    flip_v,
+                );
+            } else {
+                let table = (self.registers.ppu_ctrl.spr_addr() as u16) << 12;
+                let tile = sprite.tile_index;
+                self.draw_tile(&mut image, (x, y), table, tile, palette, flip_h, flip_v);
+            }
+        }
+        image
+    }
+
+    //The 32 palette RAM entries as a 16x2 image, background palettes on top
+    pub fn render_palettes(&self) -> DebugImage {
+        let mut image = DebugImage::new(16, 2);
+        for entry in 0..0x20u16 {
+            let colour = self.ppu_bus.peek(0x3f00 | entry) & 0x3f;
+            image.set_pixel(
+                (entry & 0xf) as usize,
+                (entry >> 4) as usize,
+                to_argb(colour as u16),
+            );
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod debug_tests {
+    use crate::{bus::MockBus, ppu::PPU};
+
+    use super::*;
+
+    //Tile 1 of each table has a single row: colour 3 in the leftmost pixel,
+    //colour 1 in the rightmost. Palette entries hold their own offset
+    fn ppu_with_vram() -> NESPPU<MockBus> {
+        let mut bus = MockBus::new();
+        //The views must not have read side effects
+        bus.expect_read().never();
+        bus.expect_peek().returning(|addr| match addr & 0x3fff {
+            0x0010 | 0x1010 => 0x81,
+            0x0018 | 0x1018 => 0x80,
+            0x3f00..=0x3fff => (addr & 0x1f) as u8,
+            //Every nametable entry is tile 1
+            0x2000..=0x2fff if addr & 0x3ff < 0x3c0 => 0x1,
+            _ => 0x0,
+        });
+        NESPPU::new(Box::new(bus))
+    }
+
+    #[test]
+    fn test_render_palettes() {
+        let ppu = ppu_with_vram();
+        let image = ppu.render_palettes();
+
+        assert_eq!(to_argb(0x00), image.get_pixel(0, 0));
+        assert_eq!(to_argb(0x0f), image.get_pixel(15, 0));
+        assert_eq!(to_argb(0x13), image.get_pixel(3, 1));
+    }
+
+    #[test]
+    fn test_render_pattern_tables_with_chosen_palette() {
+        let ppu = ppu_with_vram();
+        let image = ppu.render_pattern_tables(2);
+
+        //Tile 1 sits at (8, 0) in the left table and (136, 0) in the right
+        for left in [8, 136] {
+            assert_eq!(to_argb(0x0b), image.get_pixel(left, 0));
+            assert_eq!(to_argb(0x00), image.get_pixel(left + 1, 0));
+            assert_eq!(to_argb(0x09), image.get_pixel(left + 7, 0));
+            assert_eq!(to_argb(0x00), image.get_pixel(left, 1));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_render_pattern_tables_rejects_palette_8() {
+        ppu_with_vram().render_pattern_tables(8);
+    }
+
+    #[test]
+    fn test_render_nametables_outlines_scroll() {
+        let mut ppu = ppu_with_vram();
+        //Scroll to (12, 20) in the $2400 nametable
+        ppu.write(0x2000, 0x1);
+        ppu.write(0x2005, 12);
+        ppu.write(0x2005, 20);
+        let image = ppu.render_nametables();
+
+        assert_eq!((512, 480), (image.width, image.height));
+        assert_eq!(SCROLL_OVERLAY, image.get_pixel(256 + 12, 20));
+        assert_eq!(SCROLL_OVERLAY, image.get_pixel(256 + 12, 20 + 239));
+        //The right edge wraps into the $2000 nametable
+        assert_eq!(SCROLL_OVERLAY, image.get_pixel(11, 20));
+        assert_eq!(SCROLL_OVERLAY, image.get_pixel(11, 100));
+
+        //Tile 1 drawn with background palette 0 everywhere else
+        assert_eq!(to_argb(0x03), image.get_pixel(0, 0));
+        assert_eq!(to_argb(0x01), image.get_pixel(256 + 7, 240 + 8));
+    }
+
+    #[test]
+    fn test_render_oam_honours_flips_and_palette() {
+        let mut ppu = ppu_with_vram();
+        //Sprite 1: tile 1, palette 5, horizontally flipped
+        ppu.write(0x2003, 0x4);
+        for data in [0x10, 0x1, 0x41, 0x20] {
+            ppu.write(0x2004, data);
+        }
+        let image = ppu.render_oam();
+
+        assert_eq!(to_argb(0x15), image.get_pixel(8, 0));
+        assert_eq!(to_argb(0x17), image.get_pixel(15, 0));
+        //Sprite 0 is tile 0, which is blank
+        assert_eq!(to_argb(0x00), image.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn test_render_oam_8x16_vertical_flip_swaps_halves() {
+        let mut ppu = ppu_with_vram();
+        ppu.write(0x2000, 0x20);
+        //Tile 1 is odd, so the sprite is tiles 0 and 1 of the right table
+        ppu.write(0x2003, 0x0);
+        for data in [0x0, 0x1, 0x80, 0x0] {
+            ppu.write(0x2004, data);
+        }
+        let image = ppu.render_oam();
+
+        //Tile 1's only row ends up at the bottom of the upper half
+        assert_eq!(to_argb(0x00), image.get_pixel(0, 0));
+        assert_eq!(to_argb(0x13), image.get_pixel(0, 7));
+        assert_eq!(to_argb(0x00), image.get_pixel(0, 15));
+    }
+}
@@ -0,0 +1,110 @@
+use super::Frame;
+
+#[rustfmt::skip]
+pub const SCREEN_COLORS: [(u8, u8, u8); 0x40] = [
+    ( 84,  84,  84), (  0,  30, 116), (  8,  16, 144), ( 48,   0, 136), ( 68,   0, 100), ( 92,   0,  48), ( 84,   4,   0), ( 60,  24,   0),
+    ( 32,  42,   0), (  8,  58,   0), (  0,  64,   0), (  0,  60,   0), (  0,  50,  60), (  0,   0,   0), (  0,   0,   0), (  0,   0,   0),
+    (152, 150, 152), (  8,  76, 196), ( 48,  50, 236), ( 92,  30, 228), (136,  20, 176), (160,  20, 100), (152,  34,  32), (120,  60,   0),
+    ( 84,  90,   0), ( 40, 114,   0), (  8, 124,   0), (  0, 118,  40), (  0, 102, 120), (  0,   0,   0), (  0,   0,   0), (  0,   0,   0),
+    (236, 238, 236), ( 76, 154, 236), (120, 124, 236), (176,  98, 236), (228,  84, 236), (236,  88, 180), (236, 106, 100), (212, 136,  32),
+    (160, 170,   0), (116, 196,   0), ( 76, 208,  32), ( 56, 204, 108), ( 56, 180, 204), ( 60,  60,  60), (  0,   0,   0), (  0,   0,   0),
+    (236, 238, 236), (168, 204, 236), (188, 188, 236), (212, 178, 236), (236, 174, 236), (236, 174, 212), (236, 180, 176), (228, 196, 144),
+    (204, 210, 120), (180, 222, 120), (168, 226, 144), (152, 226, 180), (160, 214, 228), (160, 162, 160), (  0,   0,   0), (  0,   0,   0),
+];
+
+//Scale applied to a channel darkened by emphasis
+const ATTENUATION: (u32, u32) = (816, 1000);
+
+//Converts a frame pixel to 0xAARRGGBB with full alpha. Each colour
+//emphasis bit darkens the other two channels
+pub fn to_argb(pixel: u16) -> u32 {
+    let (r, g, b) = SCREEN_COLORS[(pixel & 0x3f) as usize];
+    let emphasis = pixel >> 6;
+    let attenuate = |value: u8, others: u16| {
+        if emphasis & others != 0 {
+            value as u32 * ATTENUATION.0 / ATTENUATION.1
+        } else {
+            value as u32
+        }
+    };
+
+    0xff00_0000 | attenuate(r, 0b110) << 16 | attenuate(g, 0b101) << 8 | attenuate(b, 0b011)
+}
+
+//Writes a frame into a caller-owned buffer of 256*240 ARGB pixels
+pub fn frame_to_argb(frame: &Frame, buffer: &mut [u32]) {
+    assert_eq!(
+        256 * 240,
+        buffer.len(),
+        "ARGB buffer must hold 256x240 pixels"
+    );
+
+    for (out, &pixel) in buffer.iter_mut().zip(frame.iter().flatten()) {
+        *out = to_argb(pixel);
+    }
+}
+
+//Writes a frame into a caller-owned buffer of 256*240*4 bytes, ordered R, G, B, A
+pub fn frame_to_rgba(frame: &Frame, buffer: &mut [u8]) {
+    assert_eq!(
+        256 * 240 * 4,
+        buffer.len(),
+        "RGBA buffer must hold 256x240 pixels"
+    );
+
+    for (out, &pixel) in buffer.chunks_exact_mut(4).zip(frame.iter().flatten()) {
+        let [a, r, g, b] = to_argb(pixel).to_be_bytes();
+        out.copy_from_slice(&[r, g, b, a]);
+    }
+}
+
+#[cfg(test)]
+mod palette_tests {
+    use super::*;
+
+    #[test]
+    fn test_to_argb() {
+        assert_eq!(0xff54_5454, to_argb(0x00));
+        assert_eq!(0xffec_eeec, to_argb(0x30));
+    }
+
+    #[test]
+    fn test_to_argb_attenuates_non_emphasised_channels() {
+        //Red emphasis dims green and blue
+        assert_eq!(0xffec_c2c0, to_argb(0x30 | 0b001 << 6));
+        //All three dim everything
+        assert_eq!(0xffc0_c2c0, to_argb(0x30 | 0b111 << 6));
+    }
+
+    #[test]
+    fn test_frame_to_argb() {
+        let mut frame = [[0x0; 256]; 240];
+        frame[0][1] = 0x30;
+        frame[239][255] = 0x01;
+
+        let mut buffer = vec![0x0; 256 * 240];
+        frame_to_argb(&frame, &mut buffer);
+
+        assert_eq!(0xff54_5454, buffer[0]);
+        assert_eq!(0xffec_eeec, buffer[1]);
+        assert_eq!(0xff00_1e74, buffer[256 * 240 - 1]);
+    }
+
+    #[test]
+    fn test_frame_to_rgba() {
+        let mut frame = [[0x0; 256]; 240];
+        frame[0][1] = 0x01;
+
+        let mut buffer = vec![0x0; 256 * 240 * 4];
+        frame_to_rgba(&frame, &mut buffer);
+
+        assert_eq!([0x54, 0x54, 0x54, 0xff], buffer[0..4]);
+        assert_eq!([0x00, 0x1e, 0x74, 0xff], buffer[4..8]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_frame_to_argb_rejects_short_buffer() {
+        frame_to_argb(&[[0x0; 256]; 240], &mut [0x0; 256]);
+    }
+}
@@ -66,6 +66,9 @@ pub(super) struct Registers {
     pub loopy_t: LoopyRegister,
     pub loopy_v: RefCell<LoopyRegister>,
     pub fine_x: u8,
+    //t as left by a second PPUADDR write, loaded into v once the dots
+    //left run out
+    pub pending_v: Option<(u8, LoopyRegister)>,
 
     pub ppu_ctrl: PPUCtrl,
     pub ppu_mask: PPUMask,
@@ -82,6 +85,7 @@ impl Registers {
             loopy_t: LoopyRegister::from_bytes([0x0; 2]),
             loopy_v: RefCell::new(LoopyRegister::from_bytes([0x0; 2])),
             fine_x: 0x0,
+            pending_v: None,
 
             ppu_ctrl: PPUCtrl::from_bytes([0x0]),
             ppu_mask: PPUMask::from_bytes([0x0]),
@@ -1,8 +1,8 @@
 use std::cell::RefCell;
 
-use crate::cpu::CPU;
+use crate::bus::Bus;
 
-use super::NESPPU;
+use super::{registers::LoopyRegister, NESPPU};
 
 pub(super) struct RenderArgs {
     pub(super) nt_data: u8,
@@ -32,7 +32,7 @@ impl RenderArgs {
     }
 }
 
-impl NESPPU<'_> {
+impl<B: Bus + ?Sized> NESPPU<B> {
     #[inline]
     fn fetch_bg_msb(&mut self) {
         if !(self.registers.ppu_mask.show_bg() || self.registers.ppu_mask.show_spr()) {
@@ -127,7 +127,45 @@ impl NESPPU<'_> {
     }
 
     #[inline]
-    fn increment_x(&mut self) {
+    fn is_rendering(&self) -> bool {
+        (self.registers.ppu_mask.show_bg() || self.registers.ppu_mask.show_spr())
+            && self.scanline < 240
+    }
+
+    //Outside rendering v moves on by 1 or 32. During it, a PPUDATA access
+    //clocks the fetch logic's coarse X and Y increments at once instead
+    pub(super) fn increment_v_after_data_access(&self) {
+        if self.is_rendering() {
+            self.increment_x();
+            self.increment_y();
+            return;
+        }
+
+        let offset = if self.registers.ppu_ctrl.increment() {
+            32
+        } else {
+            1
+        };
+
+        let mut loopy_v = self.registers.loopy_v.borrow_mut();
+        let raw = loopy_v.get_raw() + offset;
+        *loopy_v = LoopyRegister::from_bytes([(raw & 0xff) as u8, ((raw & 0xff00) >> 8) as u8]);
+    }
+
+    #[inline]
+    pub(super) fn update_pending_v(&mut self) {
+        match self.registers.pending_v {
+            Some((1, loopy_t)) => {
+                *self.registers.loopy_v.borrow_mut() = loopy_t;
+                self.registers.pending_v = None;
+            }
+            Some((dots, loopy_t)) => self.registers.pending_v = Some((dots - 1, loopy_t)),
+            None => {}
+        }
+    }
+
+    #[inline]
+    fn increment_x(&self) {
         if !(self.registers.ppu_mask.show_bg() || self.registers.ppu_mask.show_spr()) {
             return;
         }
@@ -136,7 +174,7 @@ impl NESPPU<'_> {
         if loopy_v.coarse_x() == 31 {
             loopy_v.set_coarse_x(0);
             let other_nt = !loopy_v.nametable_x();
-            loopy_v.set_nametable_x(!other_nt);
+            loopy_v.set_nametable_x(other_nt);
         } else {
             let new_coarse_x = loopy_v.coarse_x() + 1;
             loopy_v.set_coarse_x(new_coarse_x);
@@ -144,7 +182,7 @@ impl NESPPU<'_> {
     }
 
     #[inline]
-    fn increment_y(&mut self) {
+    fn increment_y(&self) {
         if !(self.registers.ppu_mask.show_bg() || self.registers.ppu_mask.show_spr()) {
             return;
         }
@@ -199,11 +237,54 @@ impl NESPPU<'_> {
             return;
         }
 
-        if !self.registers.ppu_mask.show_bg() {
-            self.back_buffer[self.scanline as usize][self.cycle as usize] = 0x0;
-            return;
-        }
+        let ppu_mask = self.registers.ppu_mask;
+        let colour = if ppu_mask.show_bg() || ppu_mask.show_spr() {
+            //The background is transparent in the leftmost 8 pixels if masked.
+            //Sprites aren't rendered, so spr_in_left_8 has nothing to clip
+            let (pixel, palette) =
+                if ppu_mask.show_bg() && (self.cycle >= 8 || ppu_mask.bg_in_left_8()) {
+                    self.bg_pixel()
+                } else {
+                    (0, 0)
+                };
+
+            //Transparent pixels show the backdrop colour
+            if pixel == 0 {
+                self.ppu_bus.read(0x3f00)
+            } else {
+                self.ppu_bus.read(0x3f00 + palette * 4 + pixel)
+            }
+        } else {
+            //With rendering off the backdrop is shown, unless v points into
+            //the palette, in which case that colour is
+            let loopy_v = self.registers.loopy_v.borrow().get_raw() & 0x3fff;
+            if loopy_v >= 0x3f00 {
+                self.ppu_bus.read(loopy_v)
+            } else {
+                self.ppu_bus.read(0x3f00)
+            }
+        };
 
+        let colour = if ppu_mask.greyscale() {
+            colour & 0x30
+        } else {
+            colour & 0x3f
+        };
+        let emphasis = (ppu_mask.into_bytes()[0] >> 5) as u16;
+        //Frames always carry red in bit 0 and green in bit 1
+        let emphasis = if self.region.swaps_red_green_emphasis() {
+            emphasis & 0b100 | (emphasis & 0b001) << 1 | (emphasis & 0b010) >> 1
+        } else {
+            emphasis
+        };
+
+        self.back_buffer[self.scanline as usize][self.cycle as usize] =
+            emphasis << 6 | colour as u16;
+    }
+
+    //Pixel and palette numbers of the background at the current dot
+    #[inline]
+    fn bg_pixel(&self) -> (u16, u16) {
         let fine_x_bitmux: u16 = 0x8000 >> self.registers.fine_x;
 
         let pixel_lsb = ((self.render_args.shift_lsb & fine_x_bitmux) != 0) as u16;
@@ -212,11 +293,10 @@ impl NESPPU<'_> {
         let palette_lsb = ((self.render_args.palette_shift_lsb & fine_x_bitmux) != 0) as u16;
         let palette_msb = ((self.render_args.palette_shift_msb & fine_x_bitmux) != 0) as u16;
 
-        let pixel = (pixel_msb << 1) | pixel_lsb;
-        let palette = (palette_msb << 1) | palette_lsb;
-
-        self.back_buffer[self.scanline as usize][self.cycle as usize] =
-            self.ppu_bus.read(0x3f00 + palette * 4 + pixel);
+        (
+            (pixel_msb << 1) | pixel_lsb,
+            (palette_msb << 1) | palette_lsb,
+        )
     }
 
     #[inline]
@@ -233,7 +313,7 @@ impl NESPPU<'_> {
     }
 
     #[inline]
-    pub(super) fn update_registers(&mut self, cpu: &mut dyn CPU) {
+    pub(super) fn update_registers(&mut self) {
         if self.scanline == -1 {
             match self.cycle {
                 1 => {
@@ -249,7 +329,7 @@ impl NESPPU<'_> {
         if let -1..=239 = self.scanline {
             //Render
             match self.cycle {
-                328 | 336 | (8..=248) if (self.cycle - 8) % 8 == 0 => {
+                328 | 336 | (8..=248) if (self.cycle - 8).is_multiple_of(8) => {
                     self.fetch_nt_data();
                     self.fetch_at_data();
                     self.fetch_bg_lsb();
@@ -267,26 +347,38 @@ impl NESPPU<'_> {
             }
         }
 
-        if self.scanline == 241 && self.cycle == 1 {
-            //VBlank, send an NMI to the CPU
+        if self.scanline == self.region.vblank_scanline() && self.cycle == 1 {
+            //VBlank, the NMI is sent once the output has been sampled high
+            let suppressed = self.vblank_suppressed.replace(false);
             let mut ppu_status = self.registers.ppu_status.borrow_mut();
-            (*ppu_status).set_vblank(true);
-
-            if self.registers.ppu_ctrl.nmi_enable() {
-                cpu.cpu_nmi();
-            }
+            (*ppu_status).set_vblank(!suppressed);
         }
     }
 
+    //The output has to be high on two consecutive dots, so a PPUSTATUS read
+    //on the dot VBlank is set or the one after clears it before the CPU sees
+    //an edge. Enabling NMI during VBlank also raises the output.
+    #[inline]
+    pub(super) fn sample_nmi_output(&mut self) -> bool {
+        let output =
+            self.registers.ppu_status.borrow().vblank() && self.registers.ppu_ctrl.nmi_enable();
+        self.nmi_output = (self.nmi_output << 1 | output as u8) & 0b111;
+
+        self.nmi_output == 0b011
+    }
+
     #[inline]
     pub(super) fn increment_cycle(&mut self) {
-        let skip_cycle: bool = self.scanline == -1 && self.cycle == 339 && self.registers.odd_frame;
+        let skip_cycle: bool = self.scanline == -1
+            && self.cycle == 339
+            && self.registers.odd_frame
+            && self.region.skips_odd_frame_dot();
         self.cycle += 1 + (skip_cycle as u16);
         if self.cycle >= 341 {
             self.cycle = 0;
             self.scanline += 1;
 
-            if self.scanline >= 261 {
+            if self.scanline >= self.region.scanlines() - 1 {
                 std::mem::swap(&mut self.front_buffer, &mut self.back_buffer);
                 self.scanline = -1;
                 self.completed_frame = RefCell::new(true);
@@ -1,4 +1,4 @@
-use std::fs;
+use std::{fs, time::Duration};
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Mirroring {
@@ -6,15 +6,75 @@ pub enum Mirroring {
     VERTICAL,
 }
 
+//TV system the console is timed for
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Region {
+    NTSC,
+    PAL,
+    //Famiclone timing: PAL frame length with NTSC clock ratio and VBlank
+    DENDY,
+}
+
+impl Region {
+    //Scanlines per frame, including the pre-render line
+    pub fn scanlines(&self) -> i16 {
+        match self {
+            Region::NTSC => 262,
+            Region::PAL | Region::DENDY => 312,
+        }
+    }
+
+    //Scanline on which VBlank starts
+    pub fn vblank_scanline(&self) -> i16 {
+        match self {
+            Region::NTSC | Region::PAL => 241,
+            Region::DENDY => 291,
+        }
+    }
+
+    //PPU dots per CPU cycles, as (dots, cycles)
+    pub fn clock_ratio(&self) -> (u8, u8) {
+        match self {
+            Region::NTSC | Region::DENDY => (3, 1),
+            Region::PAL => (16, 5),
+        }
+    }
+
+    //Whether the pre-render line is a dot shorter on odd frames
+    pub fn skips_odd_frame_dot(&self) -> bool {
+        *self == Region::NTSC
+    }
+
+    //Whether PPUMASK's red and green emphasis bits trade places
+    pub fn swaps_red_green_emphasis(&self) -> bool {
+        *self != Region::NTSC
+    }
+
+    pub fn frame_duration(&self) -> Duration {
+        match self {
+            Region::NTSC => Duration::from_nanos(16_639_267), //60.0988Hz
+            Region::PAL => Duration::from_nanos(19_997_200),  //50.0070Hz
+            Region::DENDY => Duration::from_nanos(19_997_200),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct INESHeader {
     pub prg_rom_banks: u8,
     pub chr_rom_banks: u8,
-    pub mapper_num: u8,
+    pub mapper_num: u16,
+    //Always 0 without NES 2.0
+    pub submapper: u8,
     pub mirroring: Mirroring,
     pub battery: bool,
+    //Bytes of work RAM at $6000-$7FFF, battery-backed or not; 0 if there is
+    //none and the range is open bus
+    pub prg_ram_bytes: u32,
     pub trainer: bool,
     pub four_screen_vram: bool,
+    //Taken from the NES 2.0 timing byte, NTSC otherwise
+    pub region: Region,
 }
 
 pub fn read_bytes_from_file(file_path: String) -> Vec<u8> {
@@ -34,15 +94,60 @@ pub fn extract_header(bytes: &[u8]) -> INESHeader {
     INESHeader {
         prg_rom_banks: bytes[4],
         chr_rom_banks: bytes[5],
-        mapper_num: (bytes[7] & 0xf0) | ((bytes[6] & 0xf0) >> 4),
+        mapper_num: extract_mapper_num(bytes),
+        submapper: if nes_2_0(bytes) { bytes[8] >> 4 } else { 0 },
         mirroring: if (bytes[6] & 0x1) == 0 {
             Mirroring::HORIZONTAL
         } else {
             Mirroring::VERTICAL
         },
         battery: (bytes[6] & 0x2) != 0,
+        prg_ram_bytes: extract_prg_ram_bytes(bytes),
         trainer: (bytes[6] & 0x4) != 0,
         four_screen_vram: (bytes[6] & 0x8) != 0,
+        region: extract_region(bytes),
+    }
+}
+
+fn nes_2_0(bytes: &[u8]) -> bool {
+    (bytes[7] & 0x0c) == 0x08
+}
+
+fn extract_mapper_num(bytes: &[u8]) -> u16 {
+    let mapper_num = ((bytes[7] & 0xf0) | ((bytes[6] & 0xf0) >> 4)) as u16;
+    if nes_2_0(bytes) {
+        //NES 2.0 adds a high nibble, in the low bits of byte 8
+        ((bytes[8] & 0x0f) as u16) << 8 | mapper_num
+    } else {
+        mapper_num
+    }
+}
+
+fn extract_region(bytes: &[u8]) -> Region {
+    if !nes_2_0(bytes) {
+        return Region::NTSC;
+    }
+
+    //Multi-region ROMs (2) run as NTSC
+    match bytes[12] & 0x3 {
+        1 => Region::PAL,
+        3 => Region::DENDY,
+        _ => Region::NTSC,
+    }
+}
+
+fn extract_prg_ram_bytes(bytes: &[u8]) -> u32 {
+    if nes_2_0(bytes) {
+        //Volatile and battery-backed RAM are each 64 << shift bytes, with a
+        //shift of 0 meaning none
+        let size = |shift: u8| if shift == 0 { 0 } else { 64 << shift };
+        return size(bytes[10] & 0xf) + size(bytes[10] >> 4);
+    }
+
+    //iNES counts 8KB banks, with 0 meaning one for compatibility
+    match bytes[8] {
+        0 => 8192,
+        banks => banks as u32 * 8192,
     }
 }
 
@@ -73,7 +178,7 @@ mod util_tests {
         let bytes = read_bytes_from_file("tests/roms/nestest.nes".to_owned());
 
         assert_eq!(24592, bytes.len());
-        assert_eq!(['N' as u8, 'E' as u8, 'S' as u8, 0x1A], &bytes[0..4]);
+        assert_eq!([b'N', b'E', b'S', 0x1A], &bytes[0..4]);
         assert_eq!(0x1A, bytes[3]);
     }
 
@@ -93,15 +198,76 @@ mod util_tests {
                 prg_rom_banks: 1,
                 chr_rom_banks: 1,
                 mapper_num: 0,
+                submapper: 0,
                 mirroring: Mirroring::HORIZONTAL,
                 battery: false,
+                prg_ram_bytes: 8192,
                 trainer: false,
                 four_screen_vram: false,
+                region: Region::NTSC,
             },
             header
         );
     }
 
+    #[test]
+    fn test_extract_region_from_nes_2_0_timing() {
+        let mut bytes = read_bytes_from_file("tests/roms/nestest.nes".to_owned());
+
+        //Ignored without the NES 2.0 identifier
+        bytes[12] = 0x1;
+        assert_eq!(Region::NTSC, extract_header(&bytes).region);
+
+        bytes[7] |= 0x08;
+        for (timing, region) in [
+            (0x0, Region::NTSC),
+            (0x1, Region::PAL),
+            (0x2, Region::NTSC),
+            (0x3, Region::DENDY),
+        ] {
+            bytes[12] = timing;
+            assert_eq!(region, extract_header(&bytes).region);
+        }
+    }
+
+    #[test]
+    fn test_extract_nes_2_0_mapper_and_submapper() {
+        let mut bytes = read_bytes_from_file("tests/roms/nestest.nes".to_owned());
+        bytes[6] |= 0x40;
+        bytes[7] |= 0x10;
+        bytes[8] = 0x32;
+
+        //Byte 8 is the PRG RAM size in iNES
+        let header = extract_header(&bytes);
+        assert_eq!(0x14, header.mapper_num);
+        assert_eq!(0, header.submapper);
+
+        bytes[7] |= 0x08;
+        let header = extract_header(&bytes);
+        assert_eq!(0x214, header.mapper_num);
+        assert_eq!(3, header.submapper);
+    }
+
+    #[test]
+    fn test_extract_prg_ram_size() {
+        let mut bytes = read_bytes_from_file("tests/roms/nestest.nes".to_owned());
+        assert_eq!(8192, extract_header(&bytes).prg_ram_bytes);
+
+        //iNES, with or without a battery
+        bytes[6] |= 0x2;
+        assert_eq!(8192, extract_header(&bytes).prg_ram_bytes);
+        bytes[8] = 0x4;
+        assert_eq!(4 * 8192, extract_header(&bytes).prg_ram_bytes);
+
+        //NES 2.0 ignores byte 8 and the battery bit
+        bytes[7] |= 0x08;
+        assert_eq!(0, extract_header(&bytes).prg_ram_bytes);
+        bytes[10] = 0x07;
+        assert_eq!(8192, extract_header(&bytes).prg_ram_bytes);
+        bytes[10] = 0x77;
+        assert_eq!(2 * 8192, extract_header(&bytes).prg_ram_bytes);
+    }
+
     #[test]
     fn test_extract_prg_rom_no_trainer() {
         let mut bytes = read_bytes_from_file("tests/roms/nestest.nes".to_owned());
@@ -122,16 +288,19 @@ mod util_tests {
             prg_rom_banks: 2,
             chr_rom_banks: 1,
             mapper_num: 0,
+            submapper: 0,
             mirroring: Mirroring::HORIZONTAL,
             battery: false,
+            prg_ram_bytes: 0,
             trainer: false,
             four_screen_vram: false,
+            region: Region::NTSC,
         };
 
         let mut bytes: [u8; 32784] = [0; 16 + 2 * 16384];
-        bytes[0] = 'N' as u8;
-        bytes[1] = 'E' as u8;
-        bytes[2] = 'S' as u8;
+        bytes[0] = b'N';
+        bytes[1] = b'E';
+        bytes[2] = b'S';
         bytes[3] = 0x1a;
 
         bytes[16] = 0xff;
@@ -149,16 +318,19 @@ mod util_tests {
             prg_rom_banks: 1,
             chr_rom_banks: 1,
             mapper_num: 0,
+            submapper: 0,
             mirroring: Mirroring::HORIZONTAL,
             battery: false,
+            prg_ram_bytes: 0,
             trainer: true,
             four_screen_vram: false,
+            region: Region::NTSC,
         };
 
         let mut bytes: [u8; 16 + 512 + 16384] = [0; 16 + 512 + 16384];
-        bytes[0] = 'N' as u8;
-        bytes[1] = 'E' as u8;
-        bytes[2] = 'S' as u8;
+        bytes[0] = b'N';
+        bytes[1] = b'E';
+        bytes[2] = b'S';
         bytes[3] = 0x1a;
 
         bytes[16 + 512] = 0xff;
@@ -199,16 +371,19 @@ mod util_tests {
             prg_rom_banks: 2,
             chr_rom_banks: 2,
             mapper_num: 0,
+            submapper: 0,
             mirroring: Mirroring::HORIZONTAL,
             battery: false,
+            prg_ram_bytes: 0,
             trainer: true,
             four_screen_vram: false,
+            region: Region::NTSC,
         };
 
         let mut bytes: [u8; 16 + 512 + 2 * 16384 + 2 * 8192] = [0; 16 + 512 + 2 * 16384 + 2 * 8192];
-        bytes[0] = 'N' as u8;
-        bytes[1] = 'E' as u8;
-        bytes[2] = 'S' as u8;
+        bytes[0] = b'N';
+        bytes[1] = b'E';
+        bytes[2] = b'S';
         bytes[3] = 0x1a;
 
         bytes[16 + 512 + 2 * 16384] = 0xff;
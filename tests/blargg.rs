@@ -0,0 +1,308 @@
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+use nes_emu::bus::cpu_bus::CPUBus;
+use nes_emu::bus::ppu_bus::PPUBus;
+use nes_emu::bus::Bus;
+use nes_emu::cartridge::NESCartridge;
+use nes_emu::controller::NESController;
+use nes_emu::cpu::asm::assemble;
+use nes_emu::cpu::{CpuVariant, NESCPU};
+use nes_emu::mapper::submapper_factory;
+use nes_emu::ppu::NESPPU;
+use nes_emu::util::extract_chr_rom;
+use nes_emu::util::extract_header;
+use nes_emu::util::extract_prg_rom;
+
+use common::{assemble_rom, nrom_image};
+
+mod common;
+
+//blargg's test ROMs report through $6000-$7FFF: $6000 holds the status,
+//$6001-$6003 the signature DE B0 61 once the other bytes are valid, and
+//$6004 onwards a zero terminated message
+const STATUS: u16 = 0x6000;
+const SIGNATURE: [u8; 3] = [0xde, 0xb0, 0x61];
+const MESSAGE: u16 = 0x6004;
+
+const RUNNING: u8 = 0x80;
+const RESET_REQUESTED: u8 = 0x81;
+
+//The ROM asks to be reset at least 100ms after writing $81
+const RESET_DELAY_FRAMES: u32 = 6;
+
+#[derive(Debug, PartialEq)]
+struct TestRomResult {
+    passed: bool,
+    //None when the ROM never reported a result
+    status: Option<u8>,
+    message: String,
+}
+
+fn read_message(bus: &impl Bus) -> String {
+    let mut message = vec![];
+    let mut addr = MESSAGE;
+    while addr <= 0x7fff && bus.peek(addr) != 0x0 {
+        message.push(bus.peek(addr));
+        addr += 1;
+    }
+
+    String::from_utf8_lossy(&message).trim().to_owned()
+}
+
+//Runs the ROM headlessly until it reports a result or max_frames pass
+fn run_test_rom(bytes: &[u8], max_frames: u32) -> TestRomResult {
+    let header = extract_header(bytes);
+    let prg_rom = extract_prg_rom(&header, bytes);
+    let chr_rom = extract_chr_rom(&header, bytes);
+
+    let cartridge = Rc::new(NESCartridge::new(
+        prg_rom,
+        chr_rom,
+        header.prg_ram_bytes,
+        submapper_factory(header.mapper_num, header.submapper),
+        header.mirroring,
+    ));
+
+    let mut cpu = NESCPU::new();
+    let mut main_bus = CPUBus::new(
+        Box::new(NESPPU::new(Box::new(PPUBus::new(cartridge.clone())))),
+        cartridge,
+        Rc::new(RefCell::new(NESController::new())),
+        Rc::new(RefCell::new(NESController::new())),
+    );
+
+    let mut reset_frame = None;
+    for frame in 0..max_frames {
+        if let Err(jam) = main_bus.run_frame(&mut cpu) {
+            return TestRomResult {
+                passed: false,
+                status: None,
+                message: jam.to_string(),
+            };
+        }
+
+        if [0x6001, 0x6002, 0x6003].map(|addr| main_bus.peek(addr)) != SIGNATURE {
+            continue;
+        }
+
+        match main_bus.peek(STATUS) {
+            RUNNING => reset_frame = None,
+            RESET_REQUESTED => match reset_frame {
+                None => reset_frame = Some(frame + RESET_DELAY_FRAMES),
+                //Only reset once per request; the ROM rewrites the status afterwards
+                Some(reset) if reset == frame => main_bus.reset(&mut cpu),
+                Some(_) => {}
+            },
+            status => {
+                return TestRomResult {
+                    passed: status == 0x0,
+                    status: Some(status),
+                    message: read_message(&main_bus),
+                }
+            }
+        }
+    }
+
+    TestRomResult {
+        passed: false,
+        status: None,
+        message: format!("timed out after {} frames", max_frames),
+    }
+}
+
+//Builds an NROM image whose program reports the given result, asking
+//for a reset first if requested
+fn build_test_rom(status: u8, message: &str, reset_first: bool) -> Vec<u8> {
+    let message: Vec<String> = message
+        .bytes()
+        .chain([0x0])
+        .map(|b| format!("${:02X}", b))
+        .collect();
+
+    let source = format!(
+        "
+        reset:
+            LDA #$80
+            STA $6000
+            LDA #$DE
+            STA $6001
+            LDA #$B0
+            STA $6002
+            LDA #$61
+            STA $6003
+            LDA #{reset_first}
+            BEQ report
+            LDA $6010           ; work RAM survives the reset
+            BNE report
+            INC $6010
+            LDA #$81
+            STA $6000
+        wait:
+            JMP wait
+        report:
+            LDX #0
+        copy:
+            LDA message,X
+            STA $6004,X
+            BEQ done
+            INX
+            BNE copy
+        done:
+            LDA #{status}
+            STA $6000
+        forever:
+            JMP forever
+        nmi:
+            RTI
+        message:
+            .byte {message}
+            .org $FFFA
+            .word nmi, reset, nmi
+        ",
+        reset_first = reset_first as u8,
+        status = status,
+        message = message.join(",")
+    );
+
+    nrom_image(&assemble(&source, 0xc000, CpuVariant::Ricoh2A03).unwrap())
+}
+
+#[test]
+fn test_harness_reports_pass() {
+    assert_eq!(
+        TestRomResult {
+            passed: true,
+            status: Some(0x0),
+            message: "Passed".to_owned()
+        },
+        run_test_rom(&build_test_rom(0x0, "\nPassed\n", false), 60)
+    );
+}
+
+#[test]
+fn test_harness_reports_failure_code_and_message() {
+    assert_eq!(
+        TestRomResult {
+            passed: false,
+            status: Some(0x2),
+            message: "Failed #2".to_owned()
+        },
+        run_test_rom(&build_test_rom(0x2, "Failed #2", false), 60)
+    );
+}
+
+#[test]
+fn test_harness_resets_on_request() {
+    assert_eq!(
+        TestRomResult {
+            passed: true,
+            status: Some(0x0),
+            message: "Passed after reset".to_owned()
+        },
+        run_test_rom(&build_test_rom(0x0, "Passed after reset", true), 60)
+    );
+}
+
+#[test]
+fn test_harness_times_out() {
+    let mut rom = build_test_rom(0x0, "", false);
+    //Jump to itself at the reset vector before anything is reported
+    rom[16..19].copy_from_slice(&[0x4c, 0x00, 0xc0]);
+
+    assert_eq!(
+        TestRomResult {
+            passed: false,
+            status: None,
+            message: "timed out after 10 frames".to_owned()
+        },
+        run_test_rom(&rom, 10)
+    );
+}
+
+#[test]
+fn test_harness_sees_nothing_without_prg_ram() {
+    let mut rom = build_test_rom(0x0, "Passed", false);
+    //Only NES 2.0 can declare no PRG RAM, in which case the status writes
+    //go nowhere and $6000-$7FFF stays open bus
+    rom[7] = 0x08;
+    rom[10] = 0x0;
+
+    assert_eq!(
+        TestRomResult {
+            passed: false,
+            status: None,
+            message: "timed out after 10 frames".to_owned()
+        },
+        run_test_rom(&rom, 10)
+    );
+}
+
+//Stands in for cpu_interrupts_v2's 2-nmi_and_brk
+#[test]
+fn test_nmi_hijacks_brk() {
+    assert_eq!(
+        TestRomResult {
+            passed: true,
+            status: Some(0x0),
+            message: "Passed".to_owned()
+        },
+        run_test_rom(&assemble_rom("nmi_brk"), 60)
+    );
+}
+
+//Stands in for palette_ram.nes
+#[test]
+fn test_palette_ram() {
+    assert_eq!(
+        TestRomResult {
+            passed: true,
+            status: Some(0x0),
+            message: "Passed".to_owned()
+        },
+        run_test_rom(&assemble_rom("palette_ram"), 60)
+    );
+}
+
+//Set BLARGG_ROMS to a directory of test ROMs (searched recursively) to run them
+#[test]
+fn test_blargg_roms() {
+    let Ok(root) = env::var("BLARGG_ROMS") else {
+        return;
+    };
+
+    fn find_roms(dir: &Path, roms: &mut Vec<std::path::PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                find_roms(&path, roms);
+            } else if path.extension().is_some_and(|ext| ext == "nes") {
+                roms.push(path);
+            }
+        }
+    }
+
+    let mut roms = vec![];
+    find_roms(Path::new(&root), &mut roms);
+    roms.sort();
+
+    let mut failures = vec![];
+    for rom in roms {
+        let result = run_test_rom(&fs::read(&rom).unwrap(), 60 * 60);
+        println!(
+            "{}: {} {}",
+            rom.display(),
+            if result.passed { "pass" } else { "FAIL" },
+            result.message
+        );
+
+        if !result.passed {
+            failures.push(format!("{}: {}", rom.display(), result.message));
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
@@ -0,0 +1,28 @@
+//Each test crate only uses some of these
+#![allow(dead_code)]
+
+use std::fs;
+
+use nes_emu::cpu::asm::assemble;
+use nes_emu::cpu::CpuVariant;
+
+//A 16KB NROM image around a program assembled for $C000, with CHR RAM
+//and 8KB of PRG RAM
+pub fn nrom_image(prg_rom: &[u8]) -> Vec<u8> {
+    //Plain iNES, where a PRG RAM size of 0 means 8KB
+    let mut rom = vec![b'N', b'E', b'S', 0x1a, 0x1, 0x0];
+    rom.resize(16, 0x0);
+    rom.extend(prg_rom);
+    rom.resize(16 + 0x4000, 0x0);
+    rom
+}
+
+//Assembles tests/roms/src/<name>.s into an NROM image
+pub fn assemble_rom(name: &str) -> Vec<u8> {
+    let path = format!("tests/roms/src/{}.s", name);
+    let source = fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {}", path, err));
+    let prg_rom = assemble(&source, 0xc000, CpuVariant::Ricoh2A03)
+        .unwrap_or_else(|err| panic!("{}: {}", path, err));
+
+    nrom_image(&prg_rom)
+}
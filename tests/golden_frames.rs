@@ -0,0 +1,246 @@
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use nes_emu::bus::cpu_bus::CPUBus;
+use nes_emu::bus::ppu_bus::PPUBus;
+use nes_emu::cartridge::NESCartridge;
+use nes_emu::controller::NESController;
+use nes_emu::cpu::NESCPU;
+use nes_emu::mapper::submapper_factory;
+use nes_emu::ppu::{Frame, NESPPU};
+use nes_emu::util::extract_chr_rom;
+use nes_emu::util::extract_header;
+use nes_emu::util::extract_prg_rom;
+use nes_emu::util::read_bytes_from_file;
+
+use common::assemble_rom;
+
+mod common;
+
+//Golden frames are stored as 16-bit binary PGM images holding each pixel
+//of the frame (palette index and emphasis bits), so they are lossless and
+//can be opened in an image viewer.
+//Run with UPDATE_GOLDEN=1 to (re)write the references after an intended
+//rendering change. On a mismatch the actual frame and a diff image (red
+//where pixels differ) are written to the test's temporary directory.
+const GOLDEN_DIR: &str = "tests/golden";
+
+const A: u8 = 0x01;
+const SELECT: u8 = 0x04;
+const START: u8 = 0x08;
+const DOWN: u8 = 0x20;
+
+enum Rom {
+    File(&'static str),
+    //Assembled from tests/roms/src/<name>.s when the test runs
+    Source(&'static str),
+}
+
+struct GoldenCase {
+    name: &'static str,
+    rom: Rom,
+    frames: u32,
+    //Buttons held on controller 1 from the given frame onwards
+    input: &'static [(u32, u8)],
+}
+
+const CASES: [GoldenCase; 5] = [
+    GoldenCase {
+        name: "nestest_menu",
+        rom: Rom::File("tests/roms/nestest.nes"),
+        frames: 30,
+        input: &[],
+    },
+    GoldenCase {
+        name: "nestest_official_opcodes",
+        rom: Rom::File("tests/roms/nestest.nes"),
+        frames: 60,
+        input: &[(20, START), (22, 0x0)],
+    },
+    GoldenCase {
+        name: "nestest_unofficial_opcodes",
+        rom: Rom::File("tests/roms/nestest.nes"),
+        frames: 60,
+        input: &[
+            (20, SELECT),
+            (22, 0x0),
+            (24, DOWN),
+            (26, 0x0),
+            (28, A),
+            (30, 0x0),
+        ],
+    },
+    GoldenCase {
+        name: "scroll_split",
+        rom: Rom::Source("scroll"),
+        frames: 10,
+        input: &[],
+    },
+    GoldenCase {
+        name: "full_palette",
+        rom: Rom::Source("full_palette"),
+        frames: 10,
+        input: &[],
+    },
+];
+
+fn set_buttons(controller: &mut NESController, buttons: u8) {
+    controller.a_latch = buttons & 0x01 != 0;
+    controller.b_latch = buttons & 0x02 != 0;
+    controller.select_latch = buttons & 0x04 != 0;
+    controller.start_latch = buttons & 0x08 != 0;
+    controller.up_latch = buttons & 0x10 != 0;
+    controller.down_latch = buttons & 0x20 != 0;
+    controller.left_latch = buttons & 0x40 != 0;
+    controller.right_latch = buttons & 0x80 != 0;
+}
+
+fn run_frames(case: &GoldenCase) -> Frame {
+    let bytes = match case.rom {
+        Rom::File(path) => read_bytes_from_file(path.to_owned()),
+        Rom::Source(name) => assemble_rom(name),
+    };
+    let header = extract_header(&bytes);
+    let prg_rom = extract_prg_rom(&header, &bytes);
+    let chr_rom = extract_chr_rom(&header, &bytes);
+
+    let cartridge = Rc::new(NESCartridge::new(
+        prg_rom,
+        chr_rom,
+        header.prg_ram_bytes,
+        submapper_factory(header.mapper_num, header.submapper),
+        header.mirroring,
+    ));
+
+    let controller_1 = Rc::new(RefCell::new(NESController::new()));
+    let mut cpu = NESCPU::new();
+    let mut main_bus = CPUBus::new(
+        Box::new(NESPPU::new(Box::new(PPUBus::new(cartridge.clone())))),
+        cartridge,
+        controller_1.clone(),
+        Rc::new(RefCell::new(NESController::new())),
+    );
+
+    for frame in 0..case.frames {
+        if let Some(&(_, buttons)) = case.input.iter().rev().find(|(at, _)| *at <= frame) {
+            set_buttons(&mut controller_1.borrow_mut(), buttons);
+        }
+
+        main_bus.run_frame(&mut cpu).unwrap();
+    }
+
+    *main_bus.get_frame_from_ppu()
+}
+
+const PGM_HEADER: &[u8] = b"P5\n256 240\n511\n";
+
+fn to_pgm(frame: &Frame) -> Vec<u8> {
+    let mut image = PGM_HEADER.to_vec();
+    image.extend(frame.iter().flatten().flat_map(|pixel| pixel.to_be_bytes()));
+    image
+}
+
+fn from_pgm(image: &[u8]) -> Frame {
+    assert!(image.starts_with(PGM_HEADER), "not a 256x240 golden frame");
+
+    let mut frame = [[0x0; 256]; 240];
+    let mut pixels = image[PGM_HEADER.len()..]
+        .chunks(2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]));
+    for pixel in frame.iter_mut().flatten() {
+        *pixel = pixels.next().unwrap();
+    }
+    frame
+}
+
+//Differing pixels are red, matching ones are a dimmed copy of the reference
+fn to_diff_ppm(expected: &Frame, actual: &Frame) -> Vec<u8> {
+    let mut image = b"P6\n256 240\n255\n".to_vec();
+    for (expected_row, actual_row) in expected.iter().zip(actual) {
+        for (&expected, &actual) in expected_row.iter().zip(actual_row) {
+            if expected == actual {
+                let grey = (expected & 0x3f) as u8 * 2;
+                image.extend([grey, grey, grey]);
+            } else {
+                image.extend([0xff, 0x0, 0x0]);
+            }
+        }
+    }
+    image
+}
+
+//Returns a description of the mismatch, if any
+fn check_golden_frame(case: &GoldenCase) -> Option<String> {
+    let actual = run_frames(case);
+    let reference = Path::new(GOLDEN_DIR).join(format!("{}.pgm", case.name));
+
+    if env::var("UPDATE_GOLDEN").is_ok() {
+        fs::create_dir_all(GOLDEN_DIR).unwrap();
+        fs::write(&reference, to_pgm(&actual)).unwrap();
+        return None;
+    }
+
+    let output = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    fs::create_dir_all(&output).unwrap();
+    let actual_path = output.join(format!("{}.actual.pgm", case.name));
+
+    let Ok(image) = fs::read(&reference) else {
+        fs::write(&actual_path, to_pgm(&actual)).unwrap();
+        return Some(format!(
+            "{}: no reference at {}, actual frame written to {} (run with UPDATE_GOLDEN=1 to accept it)",
+            case.name,
+            reference.display(),
+            actual_path.display()
+        ));
+    };
+
+    let expected = from_pgm(&image);
+    let differing = expected
+        .iter()
+        .flatten()
+        .zip(actual.iter().flatten())
+        .filter(|(expected, actual)| expected != actual)
+        .count();
+
+    if differing == 0 {
+        return None;
+    }
+
+    let diff_path = output.join(format!("{}.diff.ppm", case.name));
+    fs::write(&actual_path, to_pgm(&actual)).unwrap();
+    fs::write(&diff_path, to_diff_ppm(&expected, &actual)).unwrap();
+
+    Some(format!(
+        "{}: {} pixels differ, see {} and {}",
+        case.name,
+        differing,
+        actual_path.display(),
+        diff_path.display()
+    ))
+}
+
+#[test]
+fn test_golden_frames() {
+    let failures: Vec<String> = CASES.iter().filter_map(check_golden_frame).collect();
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_diff_image_marks_differing_pixels() {
+    let expected = [[0x10; 256]; 240];
+    let mut actual = expected;
+    actual[1][2] = 0x20;
+
+    let diff = to_diff_ppm(&expected, &actual);
+    let header = b"P6\n256 240\n255\n".len();
+    let pixel =
+        |x: usize, y: usize| &diff[header + 3 * (256 * y + x)..header + 3 * (256 * y + x) + 3];
+
+    assert_eq!(header + 256 * 240 * 3, diff.len());
+    assert_eq!(&[0xff, 0x0, 0x0], pixel(2, 1));
+    assert_eq!(&[0x20, 0x20, 0x20], pixel(0, 0));
+}
@@ -0,0 +1,104 @@
+; All 64 colours under every emphasis setting, for a golden frame, in the
+; spirit of full_palette.nes
+;
+; Rendering stays off, so the PPU shows the palette entry v points at.
+; Each frame the palette is loaded with colours $00-$1F, and v is moved
+; through it in 32 bands down the top half of the screen; halfway down
+; the palette is reloaded with $20-$3F for the bottom half. The emphasis
+; bits change every 8 bands.
+
+BAND = $00
+
+PPUCTRL = $2000
+PPUMASK = $2001
+PPUSTATUS = $2002
+PPUADDR = $2006
+PPUDATA = $2007
+
+reset:
+        SEI
+        CLD
+        LDX #$FF
+        TXS
+        LDA #0
+        STA PPUCTRL
+        STA PPUMASK
+vbl1:
+        BIT PPUSTATUS
+        BPL vbl1
+vbl2:
+        BIT PPUSTATUS
+        BPL vbl2
+
+frame:
+vbl:
+        BIT PPUSTATUS
+        BPL vbl
+        LDA #0
+        STA PPUMASK
+        JSR load_palette
+
+        ; Wait out VBlank and the pre-render line
+        LDX #2
+delay_outer:
+        LDY #0
+delay_inner:
+        DEY
+        BNE delay_inner
+        DEX
+        BNE delay_outer
+
+        LDA #0
+        STA BAND
+band:
+        LDA BAND
+        CMP #32
+        BNE same_palette
+        LDA #$20
+        JSR load_palette
+same_palette:
+        ; Emphasis from bits 3-5 of the band
+        LDA BAND
+        ASL A
+        ASL A
+        AND #$E0
+        STA PPUMASK
+
+        LDA #$3F
+        STA PPUADDR
+        LDA BAND
+        AND #$1F
+        STA PPUADDR
+
+        LDY #70
+band_delay:
+        DEY
+        BNE band_delay
+
+        INC BAND
+        LDA BAND
+        CMP #64
+        BNE band
+        JMP frame
+
+; Loads colours A to A+$1F into the palette
+load_palette:
+        LDX #$3F
+        STX PPUADDR
+        LDX #$00
+        STX PPUADDR
+        LDX #32
+load_next:
+        STA PPUDATA
+        CLC
+        ADC #1
+        DEX
+        BNE load_next
+        RTS
+
+nmi:
+irq:
+        RTI
+
+        .org $FFFA
+        .word nmi, reset, irq
@@ -0,0 +1,155 @@
+; NMI hijacking BRK, reported through blargg's $6000 protocol
+;
+; BRK runs in a loop with NMIs enabled. An NMI that arrives before BRK
+; fetches its vector takes the vector over, so the NMI handler runs with
+; the B flag set in the pushed status and the BRK never reaches the IRQ
+; handler. Every BRK has to end up in exactly one of the two handlers.
+
+ITERATIONS = $00        ; 16-bit counters
+BRKS = $02
+HIJACKS = $04
+NMIS = $06
+MESSAGE = $10           ; pointer to the result message
+
+PPUCTRL = $2000
+PPUSTATUS = $2002
+
+reset:
+        SEI
+        CLD
+        LDX #$FF
+        TXS
+        LDA #$80
+        STA $6000
+        LDA #$DE
+        STA $6001
+        LDA #$B0
+        STA $6002
+        LDA #$61
+        STA $6003
+
+        LDA #0
+        LDX #7
+clear:
+        STA $00,X
+        DEX
+        BPL clear
+
+vbl1:
+        BIT PPUSTATUS
+        BPL vbl1
+vbl2:
+        BIT PPUSTATUS
+        BPL vbl2
+        LDA #$80
+        STA PPUCTRL
+
+loop:
+        BRK                     ; with the padding byte the return skips
+        INC ITERATIONS
+        BNE check
+        INC ITERATIONS+1
+check:
+        LDA ITERATIONS+1
+        CMP #$40
+        BNE loop
+
+        LDA #0
+        STA PPUCTRL
+
+        LDA NMIS
+        ORA NMIS+1
+        BNE saw_nmi
+        LDA #<no_nmi
+        LDX #>no_nmi
+        LDY #2
+        JMP report
+saw_nmi:
+        LDA HIJACKS
+        ORA HIJACKS+1
+        BNE saw_hijack
+        LDA #<no_hijack
+        LDX #>no_hijack
+        LDY #3
+        JMP report
+saw_hijack:
+        CLC
+        LDA BRKS
+        ADC HIJACKS
+        TAX
+        LDA BRKS+1
+        ADC HIJACKS+1
+        CMP ITERATIONS+1
+        BNE lost
+        CPX ITERATIONS
+        BNE lost
+        LDA #<passed
+        LDX #>passed
+        LDY #0
+        JMP report
+lost:
+        LDA #<lost_brk
+        LDX #>lost_brk
+        LDY #4
+        JMP report
+
+; Copies the message at X:A to $6004 and writes the status in Y
+report:
+        STA MESSAGE
+        STX MESSAGE+1
+        TYA
+        PHA
+        LDY #0
+copy:
+        LDA (MESSAGE),Y
+        STA $6004,Y
+        BEQ copied
+        INY
+        BNE copy
+copied:
+        PLA
+        STA $6000
+forever:
+        JMP forever
+
+nmi:
+        PHA
+        TXA
+        PHA
+        TSX
+        LDA $0103,X             ; status pushed by the interrupt
+        AND #$10
+        BEQ plain_nmi
+        INC HIJACKS
+        BNE nmi_done
+        INC HIJACKS+1
+        JMP nmi_done
+plain_nmi:
+        INC NMIS
+        BNE nmi_done
+        INC NMIS+1
+nmi_done:
+        PLA
+        TAX
+        PLA
+        RTI
+
+irq:
+        INC BRKS
+        BNE irq_done
+        INC BRKS+1
+irq_done:
+        RTI
+
+; Messages, as ASCII
+passed:
+        .byte $50, $61, $73, $73, $65, $64, 0
+no_nmi:
+        .byte $4E, $4D, $49, $20, $6E, $65, $76, $65, $72, $20, $6F, $63, $63, $75, $72, $72, $65, $64, 0
+no_hijack:
+        .byte $4E, $4D, $49, $20, $6E, $65, $76, $65, $72, $20, $68, $69, $6A, $61, $63, $6B, $65, $64, $20, $42, $52, $4B, 0
+lost_brk:
+        .byte $42, $52, $4B, $20, $6C, $6F, $73, $74, $20, $6F, $72, $20, $72, $75, $6E, $20, $74, $77, $69, $63, $65, 0
+
+        .org $FFFA
+        .word nmi, reset, irq
@@ -0,0 +1,184 @@
+; Palette RAM reads, writes and mirroring, reported through blargg's
+; $6000 protocol like palette_ram.nes
+;
+; Palette reads through $2007 aren't buffered. $3F10, $3F14, $3F18 and
+; $3F1C are the same entries as $3F00, $3F04, $3F08 and $3F0C, while the
+; other sprite entries are separate, and $3F20-$3FFF mirrors the lot.
+
+EXPECTED = $00
+CODE = $01              ; status reported if a check fails
+MESSAGE = $10           ; pointer to the result message
+
+PPUSTATUS = $2002
+PPUADDR = $2006
+PPUDATA = $2007
+
+reset:
+        SEI
+        CLD
+        LDX #$FF
+        TXS
+        LDA #$80
+        STA $6000
+        LDA #$DE
+        STA $6001
+        LDA #$B0
+        STA $6002
+        LDA #$61
+        STA $6003
+
+vbl1:
+        BIT PPUSTATUS
+        BPL vbl1
+vbl2:
+        BIT PPUSTATUS
+        BPL vbl2
+
+        ; 2) Entries read back straight away
+        LDA #2
+        STA CODE
+        LDX #$01
+        LDA #$12
+        JSR write_palette
+        LDX #$02
+        LDA #$2A
+        JSR write_palette
+        LDX #$01
+        LDA #$12
+        JSR expect_palette
+        LDX #$02
+        LDA #$2A
+        JSR expect_palette
+
+        ; 3) The backdrop entries of the sprite palettes mirror those of
+        ; the background, in both directions
+        LDA #3
+        STA CODE
+        LDX #$10
+        LDA #$05
+        JSR write_palette
+        LDX #$00
+        LDA #$05
+        JSR expect_palette
+        LDX #$04
+        LDA #$09
+        JSR write_palette
+        LDX #$14
+        LDA #$09
+        JSR expect_palette
+        LDX #$18
+        LDA #$1B
+        JSR write_palette
+        LDX #$08
+        LDA #$1B
+        JSR expect_palette
+        LDX #$0C
+        LDA #$3C
+        JSR write_palette
+        LDX #$1C
+        LDA #$3C
+        JSR expect_palette
+
+        ; 4) The other sprite entries are separate
+        LDA #4
+        STA CODE
+        LDX #$11
+        LDA #$23
+        JSR write_palette
+        LDX #$01
+        LDA #$12
+        JSR expect_palette
+        LDX #$11
+        LDA #$23
+        JSR expect_palette
+
+        ; 5) $3F20-$3FFF mirrors $3F00-$3F1F
+        LDA #5
+        STA CODE
+        LDX #$22
+        LDA #$2A
+        JSR expect_palette
+        LDX #$E2
+        LDA #$2A
+        JSR expect_palette
+        LDX #$FF
+        LDA #$31
+        JSR write_palette
+        LDX #$1F
+        LDA #$31
+        JSR expect_palette
+
+        LDA #<passed
+        LDX #>passed
+        LDY #0
+        JMP report
+
+; Writes A to $3F00+X
+write_palette:
+        BIT PPUSTATUS
+        LDY #$3F
+        STY PPUADDR
+        STX PPUADDR
+        STA PPUDATA
+        RTS
+
+; Fails with CODE unless $3F00+X holds A
+expect_palette:
+        STA EXPECTED
+        BIT PPUSTATUS
+        LDY #$3F
+        STY PPUADDR
+        STX PPUADDR
+        LDA PPUDATA
+        AND #$3F
+        CMP EXPECTED
+        BNE fail
+        RTS
+fail:
+        LDY CODE
+        LDA messages_low-2,Y
+        LDX messages_high-2,Y
+        JMP report
+
+; Copies the message at X:A to $6004 and writes the status in Y
+report:
+        STA MESSAGE
+        STX MESSAGE+1
+        TYA
+        PHA
+        LDY #0
+copy:
+        LDA (MESSAGE),Y
+        STA $6004,Y
+        BEQ copied
+        INY
+        BNE copy
+copied:
+        PLA
+        STA $6000
+forever:
+        JMP forever
+
+nmi:
+irq:
+        RTI
+
+messages_low:
+        .byte <read_back, <backdrop_mirror, <sprite_entries, <upper_mirror
+messages_high:
+        .byte >read_back, >backdrop_mirror, >sprite_entries, >upper_mirror
+
+; Messages, as ASCII
+passed:
+        .byte $50, $61, $73, $73, $65, $64, 0
+read_back:
+        .byte $50, $61, $6C, $65, $74, $74, $65, $20, $65, $6E, $74, $72, $79, $20, $64, $69, $64, $6E, $27, $74, $20, $72, $65, $61, $64, $20, $62, $61, $63, $6B, 0
+backdrop_mirror:
+        .byte $24, $33, $46, $31, $30, $2D, $24, $33, $46, $31, $43, $20, $62, $61, $63, $6B, $64, $72, $6F, $70, $73, $20, $61, $72, $65, $6E, $27, $74, $20, $6D, $69, $72, $72, $6F, $72, $65, $64, 0
+sprite_entries:
+        .byte $53, $70, $72, $69, $74, $65, $20, $70, $61, $6C, $65, $74, $74, $65, $20, $65, $6E, $74, $72, $79, $20, $69, $73, $20, $6D, $69, $72, $72, $6F, $72, $65, $64, 0
+upper_mirror:
+        .byte $24, $33, $46, $32, $30, $2D, $24, $33, $46, $46, $46, $20, $69, $73, $6E, $27, $74, $20, $6D, $69, $72, $72, $6F, $72, $65, $64, 0
+
+        .org $FFFA
+        .word nmi, reset, irq
@@ -0,0 +1,159 @@
+; Scrolled background with a mid-frame split, for a golden frame
+;
+; Tiles are uploaded to CHR RAM. Nametable $2000 (mirrored at $2400) has
+; diagonal stripes of tiles over varied attributes, and $2800 has columns
+; in palette 3. Each frame starts scrolled to (13, 37), so the bottom of
+; the screen wraps into $2800, and about halfway down a $2005 write moves
+; the lines below to X = 100.
+
+PPUCTRL = $2000
+PPUMASK = $2001
+PPUSTATUS = $2002
+PPUSCROLL = $2005
+PPUADDR = $2006
+PPUDATA = $2007
+
+reset:
+        SEI
+        CLD
+        LDX #$FF
+        TXS
+        LDA #0
+        STA PPUCTRL
+        STA PPUMASK
+vbl1:
+        BIT PPUSTATUS
+        BPL vbl1
+vbl2:
+        BIT PPUSTATUS
+        BPL vbl2
+
+        ; Tiles 0-3 at $0000
+        LDA #$00
+        STA PPUADDR
+        STA PPUADDR
+        LDX #0
+tile_copy:
+        LDA tiles,X
+        STA PPUDATA
+        INX
+        CPX #64
+        BNE tile_copy
+
+        ; $2000: tile (column + row) & 3, attributes counting up
+        LDA #$20
+        STA PPUADDR
+        LDA #$00
+        STA PPUADDR
+        LDY #0                  ; row
+row_a:
+        LDX #0                  ; column
+column_a:
+        STX $00
+        TYA
+        CLC
+        ADC $00
+        AND #3
+        STA PPUDATA
+        INX
+        CPX #32
+        BNE column_a
+        INY
+        CPY #30
+        BNE row_a
+        LDX #0
+attr_a:
+        STX PPUDATA
+        INX
+        CPX #64
+        BNE attr_a
+
+        ; $2800: tile column & 3, all palette 3
+        LDA #$28
+        STA PPUADDR
+        LDA #$00
+        STA PPUADDR
+        LDY #30
+row_b:
+        LDX #0
+column_b:
+        TXA
+        AND #3
+        STA PPUDATA
+        INX
+        CPX #32
+        BNE column_b
+        DEY
+        BNE row_b
+        LDA #$FF
+        LDX #64
+attr_b:
+        STA PPUDATA
+        DEX
+        BNE attr_b
+
+        ; Background palettes
+        LDA #$3F
+        STA PPUADDR
+        LDA #$00
+        STA PPUADDR
+        LDX #0
+palette_copy:
+        LDA palettes,X
+        STA PPUDATA
+        INX
+        CPX #16
+        BNE palette_copy
+
+frame:
+        BIT PPUSTATUS
+vbl:
+        BIT PPUSTATUS
+        BPL vbl
+        LDA #0
+        STA PPUCTRL
+        LDA #13
+        STA PPUSCROLL
+        LDA #37
+        STA PPUSCROLL
+        LDA #$0A                ; background on, including the left column
+        STA PPUMASK
+
+        ; Wait through VBlank and about 120 lines
+        LDX #13
+delay_outer:
+        LDY #0
+delay_inner:
+        DEY
+        BNE delay_inner
+        DEX
+        BNE delay_outer
+
+        LDA #100
+        STA PPUSCROLL
+        LDA #0
+        STA PPUSCROLL
+        JMP frame
+
+nmi:
+irq:
+        RTI
+
+tiles:
+        .byte $00, $00, $00, $00, $00, $00, $00, $00   ; 0: blank
+        .byte $00, $00, $00, $00, $00, $00, $00, $00
+        .byte $FF, $FF, $FF, $FF, $FF, $FF, $FF, $FF   ; 1: solid colour 1
+        .byte $00, $00, $00, $00, $00, $00, $00, $00
+        .byte $AA, $55, $AA, $55, $AA, $55, $AA, $55   ; 2: checkerboard of colours 2 and 3
+        .byte $FF, $FF, $FF, $FF, $FF, $FF, $FF, $FF
+        .byte $80, $40, $20, $10, $08, $04, $02, $01   ; 3: diagonal line in a box
+        .byte $FF, $81, $81, $81, $81, $81, $81, $FF
+
+palettes:
+        .byte $0F, $16, $27, $18
+        .byte $0F, $1A, $2A, $3A
+        .byte $0F, $11, $21, $31
+        .byte $0F, $15, $25, $35
+
+        .org $FFFA
+        .word nmi, reset, irq
@@ -10,7 +10,7 @@ use nes_emu::cartridge::NESCartridge;
 use nes_emu::controller::MockController;
 use nes_emu::cpu::CPU;
 use nes_emu::cpu::NESCPU;
-use nes_emu::mapper::mapper_factory;
+use nes_emu::mapper::submapper_factory;
 use nes_emu::ppu::NESPPU;
 use nes_emu::util::extract_chr_rom;
 use nes_emu::util::extract_header;
@@ -29,10 +29,17 @@ fn test_nestest_rom() {
     let prg_rom = extract_prg_rom(&header, &bytes);
     let chr_rom = extract_chr_rom(&header, &bytes);
 
-    let mapper = mapper_factory(header.mapper_num);
-    let cartridge = NESCartridge::new(prg_rom, chr_rom, Box::new(mapper), header.mirroring);
+    let mapper = submapper_factory(header.mapper_num, header.submapper);
+    let cartridge = NESCartridge::new(
+        prg_rom,
+        chr_rom,
+        header.prg_ram_bytes,
+        mapper,
+        header.mirroring,
+    );
 
     let mut cpu = NESCPU::new();
+    cpu.set_tracing(true);
     let mut main_bus = CPUBus::new(
         Box::new(NESPPU::new(Box::new(MockBus::new()))),
         Rc::new(cartridge),
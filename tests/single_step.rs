@@ -0,0 +1,264 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use nes_emu::bus::Bus;
+use nes_emu::cpu::{CpuState, CpuVariant, CPU, NESCPU};
+
+//Runs Tom Harte's ProcessorTests (SingleStepTests) JSON files. Set
+//SINGLE_STEP_TESTS to a checkout of the suite to run them; each variant
+//directory found (nes6502/v1, 6502/v1, wdc65c02/v1) is run with the
+//matching CPU.
+const VARIANTS: [(&str, CpuVariant); 3] = [
+    ("nes6502/v1", CpuVariant::Ricoh2A03),
+    ("6502/v1", CpuVariant::Nmos6502),
+    ("wdc65c02/v1", CpuVariant::Wdc65C02),
+];
+
+//The JAM opcodes lock up the CPU, which the suite does not model
+const SKIPPED: [&str; 12] = [
+    "02", "12", "22", "32", "42", "52", "62", "72", "92", "b2", "d2", "f2",
+];
+
+type Access = (u16, u8, &'static str);
+
+//64KB of RAM that logs every access
+struct RecordingBus {
+    ram: Vec<u8>,
+    accesses: RefCell<Vec<Access>>,
+}
+
+impl Bus for RecordingBus {
+    fn read(&self, addr: u16) -> u8 {
+        let data = self.ram[addr as usize];
+        self.accesses.borrow_mut().push((addr, data, "read"));
+        data
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.ram[addr as usize] = data;
+        self.accesses.borrow_mut().push((addr, data, "write"));
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.ram[addr as usize]
+    }
+}
+
+fn number(value: &Value, key: &str) -> u64 {
+    value[key].as_u64().unwrap()
+}
+
+fn state(value: &Value) -> CpuState {
+    CpuState {
+        pc: number(value, "pc") as u16,
+        sp: number(value, "s") as u8,
+
+        a: number(value, "a") as u8,
+        x: number(value, "x") as u8,
+        y: number(value, "y") as u8,
+
+        status: number(value, "p") as u8,
+
+        pending_nmi: false,
+        elapsed_cycles: 0,
+    }
+}
+
+fn ram(value: &Value) -> Vec<(u16, u8)> {
+    value["ram"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            (
+                entry[0].as_u64().unwrap() as u16,
+                entry[1].as_u64().unwrap() as u8,
+            )
+        })
+        .collect()
+}
+
+fn cycles(value: &Value) -> Vec<Access> {
+    value["cycles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|cycle| {
+            let kind = match cycle[2].as_str().unwrap() {
+                "read" => "read",
+                _ => "write",
+            };
+            (
+                cycle[0].as_u64().unwrap() as u16,
+                cycle[1].as_u64().unwrap() as u8,
+                kind,
+            )
+        })
+        .collect()
+}
+
+//Returns a description of every mismatch, or an empty string if the case passed
+fn run_case(case: &Value, variant: CpuVariant) -> String {
+    let mut bus = RecordingBus {
+        ram: vec![0x0; 0x10000],
+        accesses: RefCell::new(vec![]),
+    };
+
+    //Execute reset routine
+    let mut cpu = NESCPU::with_variant(variant);
+    for _ in 0..7 {
+        cpu.clock(&mut bus);
+    }
+
+    for (addr, data) in ram(&case["initial"]) {
+        bus.ram[addr as usize] = data;
+    }
+    cpu.set_state(&state(&case["initial"]));
+    bus.accesses.borrow_mut().clear();
+
+    let expected_cycles = cycles(case);
+    for _ in 0..expected_cycles.len() {
+        cpu.clock(&mut bus);
+    }
+
+    let mut report = String::new();
+    let expected = state(&case["final"]);
+    let actual = cpu.get_state();
+
+    //Bits 4 and 5 are not stored in the status register
+    for (name, expected, actual) in [
+        ("pc", expected.pc, actual.pc),
+        ("s", expected.sp as u16, actual.sp as u16),
+        ("a", expected.a as u16, actual.a as u16),
+        ("x", expected.x as u16, actual.x as u16),
+        ("y", expected.y as u16, actual.y as u16),
+        (
+            "p",
+            (expected.status & 0xcf) as u16,
+            (actual.status & 0xcf) as u16,
+        ),
+    ] {
+        if expected != actual {
+            writeln!(
+                report,
+                "    {}: expected ${:02X}, got ${:02X}",
+                name, expected, actual
+            )
+            .unwrap();
+        }
+    }
+
+    for (addr, data) in ram(&case["final"]) {
+        if bus.ram[addr as usize] != data {
+            writeln!(
+                report,
+                "    ram ${:04X}: expected ${:02X}, got ${:02X}",
+                addr, data, bus.ram[addr as usize]
+            )
+            .unwrap();
+        }
+    }
+
+    let accesses = bus.accesses.borrow();
+    if *accesses != expected_cycles {
+        for i in 0..expected_cycles.len().max(accesses.len()) {
+            let show = |access: Option<&Access>| match access {
+                Some((addr, data, kind)) => format!("{: <5} ${:04X} ${:02X}", kind, addr, data),
+                None => "-".to_owned(),
+            };
+            let marker = if expected_cycles.get(i) == accesses.get(i) {
+                " "
+            } else {
+                "!"
+            };
+            writeln!(
+                report,
+                "   {}cycle {}: expected {: <17} got {}",
+                marker,
+                i + 1,
+                show(expected_cycles.get(i)),
+                show(accesses.get(i))
+            )
+            .unwrap();
+        }
+    }
+
+    report
+}
+
+fn run_directory(dir: &Path, variant: CpuVariant) -> Vec<String> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+
+    let mut results = BTreeMap::new();
+    let mut failures = vec![];
+
+    for file in files {
+        let opcode = file.file_stem().unwrap().to_string_lossy().to_lowercase();
+        if SKIPPED.contains(&opcode.as_str()) && variant != CpuVariant::Wdc65C02 {
+            continue;
+        }
+
+        let cases: Value = serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        let cases = cases.as_array().unwrap();
+
+        let mut passed = 0;
+        let mut first_failure = None;
+        for case in cases {
+            let report = run_case(case, variant);
+            if report.is_empty() {
+                passed += 1;
+            } else if first_failure.is_none() {
+                first_failure = Some(format!("  {}\n{}", case["name"].as_str().unwrap(), report));
+            }
+        }
+
+        results.insert(opcode.clone(), (passed, cases.len()));
+        if let Some(first_failure) = first_failure {
+            failures.push(format!(
+                "{:?} opcode ${}:\n{}",
+                variant, opcode, first_failure
+            ));
+        }
+    }
+
+    for (opcode, (passed, total)) in &results {
+        println!(
+            "{:?} ${}: {}/{} {}",
+            variant,
+            opcode,
+            passed,
+            total,
+            if passed == total { "pass" } else { "FAIL" }
+        );
+    }
+
+    failures
+}
+
+#[test]
+fn test_single_step_tests() {
+    let Ok(root) = env::var("SINGLE_STEP_TESTS") else {
+        return;
+    };
+
+    let mut failures = vec![];
+    for (subdir, variant) in VARIANTS {
+        let dir = Path::new(&root).join(subdir);
+        if dir.is_dir() {
+            failures.extend(run_directory(&dir, variant));
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}